@@ -50,7 +50,8 @@ use std::{
     cmp::Ordering,
     collections::{
         HashMap,
-        HashSet
+        HashSet,
+        VecDeque
     }
 };
 
@@ -60,13 +61,30 @@ use crate::serbin::{
 };
 
 mod ancestors;
+mod diff;
+mod inspect;
 mod iomap;
+mod journal;
+mod json;
+mod merge;
+mod migration;
 mod peer;
 mod serbin;
 mod statistics;
+mod task;
 mod tick;
 
-pub use serbin::FORMAT_VERSION;
+pub use journal::journal_filepath;
+pub use merge::MergeSelection;
+pub use serbin::{
+    BackgroundSave,
+    FORMAT_VERSION
+};
+pub use statistics::{
+    ChannelsStatistics,
+    ControllerGenerationStatistics,
+    IoPairStatistics
+};
 
 // Constants; the lesser of them, the better (arbitrariness...)?
 
@@ -84,7 +102,13 @@ pub const NUM_CTRL_INTEGER_CHANNELS: usize = 0x20;
 
 const DEFAULT_MAX_NUM_CHAINS_BINLOG: u8 = 22; // 1 << this - maximum number of nodes and controllers
 
-const DEFAULT_ÆLHOMETTA_FILENAME: &str = "aelhometta.bin";
+pub const DEFAULT_ÆLHOMETTA_FILENAME: &str = "aelhometta.bin";
+
+const MAX_BONUS_TICKS: usize = 0x10000; // pending extra ticks granted as task rewards
+
+const IO_SAMPLES_MAX_LEN: usize = 0x400; // window of input/output channels' values for information flow statistics
+const IO_SAMPLED_PER_MAPPING: usize = 0x10; // first channels of each mapping, since images and sounds span thousands
+const IO_SAMPLED_MAX: usize = 0x40; // of all input, and of all output, mappings, so at most 0x1000 pairs
 
 pub type Uid = u32;
 pub type Optuid = Option<Uid>;
@@ -335,6 +359,21 @@ pub struct OtherPeer {
     ut_last_update: i64 // microseconds since Unix epoch
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum FileFormat {
+    Raw             = 0, // 8-byte little-endian integers
+    Pgm             = 1, // binary greyscale image (P5), pixel per channel
+    Ppm             = 2, // binary colour image (P6), 3 channels (R, G, B) per pixel
+    Wav             = 3  // PCM audio, sample per channel
+}
+
+pub const FILE_FORMATS: [(FileFormat, &str); 4] = [
+    (FileFormat::Raw, "raw"),
+    (FileFormat::Pgm, "pgm"),
+    (FileFormat::Ppm, "ppm"),
+    (FileFormat::Wav, "wav")
+];
+
 #[derive(Clone)]
 pub struct IntegersFileMapping {
     start: usize, // index of integer channel
@@ -342,6 +381,126 @@ pub struct IntegersFileMapping {
     interval: i64, // microseconds
     filepath: String, // must be without spaces
     ut_last_update: i64, // microseconds since Unix epoch
+    format: FileFormat,
+    format_param: usize, // input image & audio: downsampling step; output image: width; output audio: sample rate; 0 means default
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum Sensor {
+    Time            = 0, // microseconds since Unix epoch
+    Age             = 1,
+    Nodes           = 2,
+    Controllers     = 3,
+    Commands        = 4, // executed since previous update
+    Glitches        = 5, // background, replicate, construct
+    LoadAverage     = 6, // 1, 5, 15 minutes, ×1000
+    Memory          = 7, // total, available, KiB
+    Uptime          = 8  // seconds
+}
+
+pub const SENSORS: [(Sensor, &str); 9] = [
+    (Sensor::Time, "time"),
+    (Sensor::Age, "age"),
+    (Sensor::Nodes, "nodes"),
+    (Sensor::Controllers, "ctrls"),
+    (Sensor::Commands, "comms"),
+    (Sensor::Glitches, "glitches"),
+    (Sensor::LoadAverage, "loadavg"),
+    (Sensor::Memory, "mem"),
+    (Sensor::Uptime, "uptime")
+];
+
+#[derive(Clone)]
+pub struct SensorMapping {
+    sensor: Sensor,
+    start: usize, // index of 1st integer channel, there are sensor.length() of them
+    interval: i64, // microseconds
+    ut_last_update: i64, // microseconds since Unix epoch
+    last_total: u128 // for sensors that report increments
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum Waveform {
+    Constant        = 0, // offset
+    Sine            = 1, // offset ± amplitude
+    Square          = 2, // offset ± amplitude, half a period each
+    Sawtooth        = 3, // from offset - amplitude up to offset + amplitude
+    RandomWalk      = 4, // starts at offset, steps by ±amplitude once per period
+    Pulse           = 5, // offset + amplitude during width, offset during the rest of period
+    Sequence        = 6  // one of values per period, cyclically
+}
+
+pub const WAVEFORMS: [(Waveform, &str); 7] = [
+    (Waveform::Constant, "const"),
+    (Waveform::Sine, "sine"),
+    (Waveform::Square, "square"),
+    (Waveform::Sawtooth, "saw"),
+    (Waveform::RandomWalk, "walk"),
+    (Waveform::Pulse, "pulse"),
+    (Waveform::Sequence, "seq")
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum Clock {
+    Ticks           = 0, // age
+    Time            = 1  // microseconds since Unix epoch
+}
+
+#[derive(Clone)]
+pub struct GeneratorMapping {
+    waveform: Waveform,
+    start: usize, // index of integer channel
+    length: usize, // number of integer channels from the start, all get the same value
+    clock: Clock,
+    period: i64, // ticks or microseconds, depending on clock
+    amplitude: Integer,
+    offset: Integer,
+    width: i64, // of pulse, same units as period
+    values: Vec<Integer>, // of sequence
+    value: Integer, // last generated
+    last_step: i64 // number of whole periods elapsed at last update
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum TaskFunction {
+    Identity        = 0, // a
+    Not             = 1, // !a
+    And             = 2, // a & b
+    Or              = 3, // a | b
+    Xor             = 4, // a ^ b
+    Sum             = 5, // a + b
+    Less            = 6, // a < b ? 1 : 0
+    Equal           = 7, // a == b ? 1 : 0
+    Greater         = 8  // a > b ? 1 : 0
+}
+
+pub const TASK_FUNCTIONS: [(TaskFunction, &str); 9] = [
+    (TaskFunction::Identity, "id"),
+    (TaskFunction::Not, "not"),
+    (TaskFunction::And, "and"),
+    (TaskFunction::Or, "or"),
+    (TaskFunction::Xor, "xor"),
+    (TaskFunction::Sum, "sum"),
+    (TaskFunction::Less, "less"),
+    (TaskFunction::Equal, "equal"),
+    (TaskFunction::Greater, "greater")
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Primitive)]
+pub enum Reward {
+    Ticks           = 0, // extra ticks of the controller, as if it were chosen more often
+    Renew           = 1  // controller survives being evicted by newer ones that many times
+}
+
+#[derive(Clone)]
+pub struct Task {
+    function: TaskFunction,
+    output: usize, // index of integer channel, checked at TransmitInteger to it
+    inputs: Vec<usize>, // indices of integer channels, 1 or 2 depending on function
+    reward: Reward,
+    amount: u32,
+    attempts_count: u128, // all TransmitInteger-s to output channel
+    completions_count: u128 // those of them with matching value
 }
 
 pub struct Ælhometta {
@@ -401,14 +560,25 @@ pub struct Ælhometta {
     // IO-related
     output_mappings: Vec<IntegersFileMapping>,
     input_mappings: Vec<IntegersFileMapping>,
+    sensor_mappings: Vec<SensorMapping>,
+    generator_mappings: Vec<GeneratorMapping>,
+
+    // Task-related
+    tasks: Vec<Task>,
+    bonus_ticks: VecDeque<Uid>,
+    renewals: HashMap<Uid, u32>,
 
     // Non-serialisable part
     max_num_chains: usize,
     max_num_chains_binmask: usize,
 
-    rng: ThreadRng,
+    rng: StdRng,
 
     efunguz: Option<Efunguz>,
+
+    io_samples: VecDeque<(Vec<Integer>, Vec<Integer>)>, // values of input- and output-mapped channels at input updates
+
+    journal: Option<journal::JournalTracking>, // changes since last base or delta, None if not journaling
 }
 
 pub struct TickData {
@@ -682,13 +852,15 @@ impl OtherPeer {
 }
 
 impl IntegersFileMapping {
-    pub fn new(start: usize, length: usize, interval: i64, filepath: &str) -> Self {
+    pub fn new(start: usize, length: usize, interval: i64, filepath: &str, format: FileFormat, format_param: usize) -> Self {
         Self {
             start,
             length,
             interval,
             filepath: filepath.to_string(),
-            ut_last_update: -1 
+            ut_last_update: -1,
+            format,
+            format_param
         }
     }
 
@@ -712,6 +884,256 @@ impl IntegersFileMapping {
         self.ut_last_update
     }
 
+    pub fn format(&self) -> FileFormat {
+        self.format
+    }
+
+    pub fn format_param(&self) -> usize {
+        self.format_param
+    }
+
+}
+
+impl FileFormat {
+    pub fn name(&self) -> &'static str {
+        FILE_FORMATS.iter().find(|(format, _)| format == self).map_or("?", |(_, name)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        FILE_FORMATS.iter().find(|(_, n)| *n == name).map(|(format, _)| *format)
+    }
+
+}
+
+impl Sensor {
+    pub fn length(&self) -> usize {
+        match *self {
+            Self::Glitches | Self::LoadAverage => 3,
+            Self::Memory => 2,
+            _ => 1
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        SENSORS.iter().find(|(sensor, _)| sensor == self).map_or("?", |(_, name)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SENSORS.iter().find(|(_, n)| *n == name).map(|(sensor, _)| *sensor)
+    }
+
+}
+
+impl SensorMapping {
+    pub fn new(sensor: Sensor, start: usize, interval: i64) -> Self {
+        Self {
+            sensor,
+            start,
+            interval,
+            ut_last_update: -1,
+            last_total: 0
+        }
+    }
+
+    pub fn sensor(&self) -> Sensor {
+        self.sensor
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn interval(&self) -> i64 {
+        self.interval
+    }
+
+    pub fn ut_last_update(&self) -> i64 {
+        self.ut_last_update
+    }
+
+}
+
+impl Waveform {
+    pub fn name(&self) -> &'static str {
+        WAVEFORMS.iter().find(|(waveform, _)| waveform == self).map_or("?", |(_, name)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        WAVEFORMS.iter().find(|(_, n)| *n == name).map(|(waveform, _)| *waveform)
+    }
+
+}
+
+impl Clock {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Ticks => "tick",
+            Self::Time => "time"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tick" | "ticks" => Some(Self::Ticks),
+            "time" => Some(Self::Time),
+            _ => None
+        }
+    }
+
+}
+
+impl GeneratorMapping {
+    pub fn new(waveform: Waveform, start: usize, length: usize, clock: Clock, period: i64) -> Self {
+        Self {
+            waveform,
+            start,
+            length,
+            clock,
+            period,
+            amplitude: 0,
+            offset: 0,
+            width: 0,
+            values: Vec::new(),
+            value: 0,
+            last_step: -1
+        }
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        self.waveform
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    pub fn period(&self) -> i64 {
+        self.period
+    }
+
+    pub fn amplitude(&self) -> Integer {
+        self.amplitude
+    }
+
+    pub fn offset(&self) -> Integer {
+        self.offset
+    }
+
+    pub fn width(&self) -> i64 {
+        self.width
+    }
+
+    pub fn values(&self) -> & Vec<Integer> {
+        & self.values
+    }
+
+    pub fn value(&self) -> Integer {
+        self.value
+    }
+
+}
+
+impl TaskFunction {
+    pub fn arity(&self) -> usize {
+        match *self {
+            Self::Identity | Self::Not => 1,
+            _ => 2
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        TASK_FUNCTIONS.iter().find(|(function, _)| function == self).map_or("?", |(_, name)| name)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        TASK_FUNCTIONS.iter().find(|(_, n)| *n == name).map(|(function, _)| *function)
+    }
+
+    pub fn apply(&self, args: &[Integer]) -> Integer {
+        let a = args.first().copied().unwrap_or(0);
+        let b = args.get(1).copied().unwrap_or(0);
+        match *self {
+            Self::Identity => a,
+            Self::Not => !a,
+            Self::And => a & b,
+            Self::Or => a | b,
+            Self::Xor => a ^ b,
+            Self::Sum => a.wrapping_add(b),
+            Self::Less => (a < b) as Integer,
+            Self::Equal => (a == b) as Integer,
+            Self::Greater => (a > b) as Integer
+        }
+    }
+
+}
+
+impl Reward {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::Ticks => "ticks",
+            Self::Renew => "renew"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ticks" => Some(Self::Ticks),
+            "renew" => Some(Self::Renew),
+            _ => None
+        }
+    }
+
+}
+
+impl Task {
+    pub fn new(function: TaskFunction, output: usize, inputs: Vec<usize>, reward: Reward, amount: u32) -> Self {
+        Self {
+            function,
+            output,
+            inputs,
+            reward,
+            amount,
+            attempts_count: 0,
+            completions_count: 0
+        }
+    }
+
+    pub fn function(&self) -> TaskFunction {
+        self.function
+    }
+
+    pub fn output(&self) -> usize {
+        self.output
+    }
+
+    pub fn inputs(&self) -> & Vec<usize> {
+        & self.inputs
+    }
+
+    pub fn reward(&self) -> Reward {
+        self.reward
+    }
+
+    pub fn amount(&self) -> u32 {
+        self.amount
+    }
+
+    pub fn attempts_count(&self) -> u128 {
+        self.attempts_count
+    }
+
+    pub fn completions_count(&self) -> u128 {
+        self.completions_count
+    }
+
 }
 
 impl Ælhometta {
@@ -721,9 +1143,12 @@ impl Ælhometta {
         let node = Node::new(content.to_bits(), None.to_bits(), None.to_bits());
         if let Some(onuid) = self.nodes_historing[self.i_nodes_historing] {
             self.nodes.remove(&onuid);
+            self.journal_node_removed(onuid);
         }
         self.nodes.insert(nuid, node);
+        self.journal_node(nuid);
         self.nodes_historing[self.i_nodes_historing] = Some(nuid);
+        self.journal_node_historing(self.i_nodes_historing);
         self.i_nodes_historing = (self.i_nodes_historing + 1) & self.max_num_chains_binmask;
         Some(nuid)    
     }
@@ -731,6 +1156,7 @@ impl Ælhometta {
     fn add_new_node_to_existing(&mut self, content: Content, toouid: &Optuid) -> Optuid {
         if let &Some(touid) = toouid {
             let ouid = self.add_new_node(content);
+            self.journal_node(touid);
             let mut aouid: Optuid = None;
             self.nodes.entry(touid).and_modify(|tonode| {
                 tonode.b_next = ouid.to_bits();
@@ -746,20 +1172,32 @@ impl Ælhometta {
                 let b_ruid = 0x80000000 | ruid;
                 let b_r_next = self.nodes.get(&ruid).unwrap().b_next;
                 self.nodes.remove(&ruid);
-                for ouid in &mut self.nodes_historing { // slow...
+                self.journal_node_removed(ruid);
+                for (i, ouid) in self.nodes_historing.iter_mut().enumerate() { // slow...
                     if let &mut Some(uid) = ouid {
                         if uid == ruid {
                             *ouid = None;
+                            if let Some(ref mut jt) = self.journal {
+                                jt.nodes_historing.insert(i);
+                            }
                             break;
                         }
                     }
                 }
-                for (_, node) in &mut self.nodes { // slow...
+                for (uid, node) in &mut self.nodes { // slow...
+                    let mut relinked = false;
                     if node.b_next == b_ruid {
                         node.b_next = b_r_next;
+                        relinked = true;
                     }
                     if node.b_altnext == b_ruid {
                         node.b_next = b_r_next;
+                        relinked = true;
+                    }
+                    if relinked {
+                        if let Some(ref mut jt) = self.journal {
+                            jt.nodes.insert(*uid);
+                        }
                     }
                 }
                 Some(ruid)
@@ -770,11 +1208,29 @@ impl Ælhometta {
     fn add_controller(&mut self, ctrl: Controller) -> Optuid {
         let cuid = self.new_controller_uid;
         self.new_controller_uid = (self.new_controller_uid + 1) & 0x7FFFFFFF;
+        // Renewed controllers stay where they are, becoming the newest, and the next oldest one is considered instead
+        for _ in 0..self.max_num_chains {
+            match self.controllers_historing[self.i_controllers_historing].and_then(|ocuid| self.renewals.get_mut(&ocuid)) {
+                Some(n) => {
+                    *n = n.saturating_sub(1);
+                    if *n == 0 {
+                        let ocuid = self.controllers_historing[self.i_controllers_historing].unwrap_or(0);
+                        self.renewals.remove(&ocuid);
+                    }
+                    self.i_controllers_historing = (self.i_controllers_historing + 1) & self.max_num_chains_binmask;
+                },
+                None => break
+            }
+        }
         if let Some(ocuid) = self.controllers_historing[self.i_controllers_historing] {
             self.controllers.remove(&ocuid);
+            self.journal_controller_removed(ocuid);
+            self.renewals.remove(&ocuid);
         }
         self.controllers.insert(cuid, ctrl);
+        self.journal_controller(cuid);
         self.controllers_historing[self.i_controllers_historing] = Some(cuid);
+        self.journal_controller_historing(self.i_controllers_historing);
         self.i_controllers_historing = (self.i_controllers_historing + 1) & self.max_num_chains_binmask;
         Some(cuid)
     }
@@ -783,10 +1239,15 @@ impl Ælhometta {
         if let &Some(ruid) = rouid {
             if self.controllers.contains_key(&ruid) {
                 self.controllers.remove(&ruid);
-                for ouid in &mut self.controllers_historing { // slow...
+                self.journal_controller_removed(ruid);
+                self.renewals.remove(&ruid);
+                for (i, ouid) in self.controllers_historing.iter_mut().enumerate() { // slow...
                     if let Some(uid) = ouid {
                         if *uid == ruid {
                             *ouid = None;
+                            if let Some(ref mut jt) = self.journal {
+                                jt.controllers_historing.insert(i);
+                            }
                             break;
                         }
                     }
@@ -810,7 +1271,7 @@ impl Ælhometta {
     }
 
     pub fn new(max_num_chains_binlog: u8) -> Self {
-        let rng = thread_rng();
+        let rng = StdRng::from_entropy();
         let max_num_chains: usize = 1 << max_num_chains_binlog;
 
         let mut commandswitch = u128::MAX;
@@ -858,9 +1319,18 @@ impl Ælhometta {
             in_attempted_before_num: 0,
             output_mappings: Vec::new(),
             input_mappings: Vec::new(),
+            sensor_mappings: Vec::new(),
+            generator_mappings: Vec::new(),
+            tasks: Vec::new(),
+            bonus_ticks: VecDeque::new(),
+            renewals: HashMap::new(),
 
             rng,
             efunguz: None,
+
+            io_samples: VecDeque::new(),
+
+            journal: None,
         }
     }
 
@@ -928,6 +1398,25 @@ impl Ælhometta {
         ).sum::<usize>()
 
         + (self.output_mappings.capacity() + self.input_mappings.capacity()) * size_of::<IntegersFileMapping>()
+        + self.sensor_mappings.capacity() * size_of::<SensorMapping>()
+        + self.generator_mappings.iter().map(|gm|
+            size_of::<GeneratorMapping>() + gm.values.capacity() * size_of::<Integer>()
+        ).sum::<usize>()
+
+        + self.tasks.capacity() * (size_of::<Task>() + 2 * size_of::<usize>())
+        + self.bonus_ticks.capacity() * size_of::<Uid>()
+        + self.renewals.capacity() * (size_of::<Uid>() + size_of::<u32>())
+
+        + self.io_samples.iter().map(|(ins, outs)|
+            (ins.capacity() + outs.capacity()) * size_of::<Integer>()
+        ).sum::<usize>()
+
+        + self.journal.as_ref().map_or(0, |jt| jt.mem_usage())
+    }
+
+    // Same random choices for the same seed, though order of hash maps still varies between processes
+    pub fn seed_rng(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 
     pub fn random_node_optuid(&mut self) -> Optuid {
@@ -1101,6 +1590,14 @@ impl Ælhometta {
         & self.input_mappings
     }
 
+    pub fn sensor_mappings(&self) -> & Vec<SensorMapping> {
+        & self.sensor_mappings
+    }
+
+    pub fn generator_mappings(&self) -> & Vec<GeneratorMapping> {
+        & self.generator_mappings
+    }
+
     pub fn cleanse(&mut self) {
         self.new_node_uid = 0;
         self.nodes.clear();
@@ -1157,13 +1654,25 @@ impl Ælhometta {
 
         self.output_mappings.clear();
         self.input_mappings.clear();
+        self.sensor_mappings.clear();
+        self.generator_mappings.clear();
+
+        self.tasks.clear();
+        self.bonus_ticks.clear();
+        self.renewals.clear();
 
-        self.rng = thread_rng();
+        self.rng = StdRng::from_entropy();
 
         self.efunguz = None;
+
+        self.io_samples.clear();
+
+        self.journal = None;
     }
 
     pub fn change_limit(&mut self, max_num_chains_binlog: u8) {
+        self.journal = None;
+
         let max_num_chains: usize = 1 << max_num_chains_binlog;
         match self.max_num_chains.cmp(&max_num_chains) {
             Ordering::Less => { // insert
@@ -1209,6 +1718,7 @@ impl Ælhometta {
                 self.controllers.shrink_to(max_num_chains);
                 self.controllers_historing = self.controllers_historing[n..self.max_num_chains].to_vec();
                 self.i_controllers_historing = 0;
+                self.renewals.retain(|cuid, _| self.controllers.contains_key(cuid));
 
                 self.max_num_chains_binlog = max_num_chains_binlog;
                 self.max_num_chains = max_num_chains;
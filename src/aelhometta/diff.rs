@@ -0,0 +1,204 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Differences between two states, e.g. snapshots of the same lineage or runs with different settings
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet
+};
+
+use crate::serbin::{
+    ToBits,
+    WriteBin
+};
+
+use super::{
+    serbin::read_file,
+    Content,
+    Controller,
+    Uid,
+    Ælhometta
+};
+
+pub struct WorldDiff {
+    pub ages: (u128, u128),
+    pub limits: (u8, u8), // binlogs
+    pub nums_nodes: (usize, usize),
+    pub nums_controllers: (usize, usize),
+    pub contents: Vec<(Content, usize, usize)>, // only those with different numbers
+    pub nodes_only: (Vec<Uid>, Vec<Uid>), // sorted
+    pub nodes_changed: usize, // present in both, with different content or links
+    pub controllers_only: (Vec<Uid>, Vec<Uid>), // sorted
+    pub controllers_changed: usize, // present in both, in different state
+    pub counts: Vec<(String, u128, u128)>, // spaces, branches, commands, constructions; only different
+    pub glitches: Vec<(&'static str, f64, f64, u128, u128)>, // kind, probabilities, counts
+    pub commandswitches: (u128, u128),
+    pub optuid_channels_changed: usize,
+    pub integer_channels_changed: usize,
+    pub peer: Vec<(&'static str, String, String)>, // only different
+    pub other_peers_only: (Vec<String>, Vec<String>), // public keys
+    pub whitelist_only: (Vec<String>, Vec<String>),
+    pub mappings_only: (Vec<String>, Vec<String>), // descriptions, without state of updates
+    pub tasks_only: (Vec<String>, Vec<String>)
+}
+
+fn only<T: Ord + Clone>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> (Vec<T>, Vec<T>) {
+    (a.difference(b).cloned().collect(), b.difference(a).cloned().collect())
+}
+
+fn controller_bytes(ctrl: &Controller) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.write_bin(ctrl).unwrap_or(());
+    bytes
+}
+
+fn mappings_described(æh: &Ælhometta) -> BTreeSet<String> {
+    let mut descs = BTreeSet::new();
+    for om in & æh.output_mappings {
+        descs.insert(format!("out {}+{} {} {} {} every {} μs", om.start, om.length, &om.filepath, om.format.name(), om.format_param, om.interval));
+    }
+    for im in & æh.input_mappings {
+        descs.insert(format!("in {}+{} {} {} {} every {} μs", im.start, im.length, &im.filepath, im.format.name(), im.format_param, im.interval));
+    }
+    for sm in & æh.sensor_mappings {
+        descs.insert(format!("sens {} {} every {} μs", sm.start, sm.sensor.name(), sm.interval));
+    }
+    for gm in & æh.generator_mappings {
+        descs.insert(format!("gen {}+{} {} {} {} amp {} off {} width {} values {:?}", gm.start, gm.length, gm.waveform.name(), gm.clock.name(), gm.period, gm.amplitude, gm.offset, gm.width, &gm.values));
+    }
+    descs
+}
+
+fn tasks_described(æh: &Ælhometta) -> BTreeSet<String> {
+    æh.tasks.iter().map(|task| format!("{} {} ← {:?} {} {}", task.function.name(), task.output, &task.inputs, task.reward.name(), task.amount)).collect()
+}
+
+impl Ælhometta {
+    // Both are read as by inspect: peers not exposed, journals not applied
+    pub fn diff_files(filepath_a: &str, filepath_b: &str) -> Result<WorldDiff, String> {
+        let (æh_a, _) = read_file(filepath_a, None)?;
+        let (æh_b, _) = read_file(filepath_b, None)?;
+        Ok(æh_a.diff(&æh_b))
+    }
+
+    pub fn diff(&self, other: &Self) -> WorldDiff {
+        let (a, b) = (self, other);
+
+        let (cont_a, cont_b) = (a.content_statistics(), b.content_statistics());
+        let mut contents: BTreeMap<u8, (Content, usize, usize)> = BTreeMap::new(); // in the order of encoding
+        for (content, n) in & cont_a {
+            contents.entry(content.to_bits()).or_insert((*content, 0, 0)).1 = *n;
+        }
+        for (content, n) in & cont_b {
+            contents.entry(content.to_bits()).or_insert((*content, 0, 0)).2 = *n;
+        }
+
+        let nodes_a: BTreeSet<Uid> = a.nodes.keys().copied().collect();
+        let nodes_b: BTreeSet<Uid> = b.nodes.keys().copied().collect();
+        let nodes_changed = nodes_a.intersection(&nodes_b).filter(|uid| {
+            let (na, nb) = (& a.nodes[*uid], & b.nodes[*uid]);
+            (na.b_content, na.b_next, na.b_altnext) != (nb.b_content, nb.b_next, nb.b_altnext)
+        }).count();
+
+        let ctrls_a: BTreeSet<Uid> = a.controllers.keys().copied().collect();
+        let ctrls_b: BTreeSet<Uid> = b.controllers.keys().copied().collect();
+        let controllers_changed = ctrls_a.intersection(&ctrls_b).filter(|uid| {
+            controller_bytes(& a.controllers[*uid]) != controller_bytes(& b.controllers[*uid])
+        }).count();
+
+        let mut counts = vec![
+            (String::from("Spaces"), a.spaces_count, b.spaces_count),
+            (String::from("Branches (main)"), a.branches_main_count, b.branches_main_count),
+            (String::from("Branches (alt)"), a.branches_alt_count, b.branches_alt_count)
+        ];
+        let mut named: BTreeMap<String, (u128, u128)> = BTreeMap::new();
+        for (command, n) in & a.commands_count {
+            named.entry(format!("{:?}", Content::Command(*command))).or_default().0 = *n;
+        }
+        for (command, n) in & b.commands_count {
+            named.entry(format!("{:?}", Content::Command(*command))).or_default().1 = *n;
+        }
+        for (construction, n) in & a.constructions_count {
+            named.entry(format!("{:?}", Content::Construction(*construction))).or_default().0 = *n;
+        }
+        for (construction, n) in & b.constructions_count {
+            named.entry(format!("{:?}", Content::Construction(*construction))).or_default().1 = *n;
+        }
+        counts.extend(named.into_iter().map(|(name, (na, nb))| (name, na, nb)));
+        counts.retain(|(_, na, nb)| na != nb);
+
+        let glitches = vec![
+            ("Background", a.glitch_background_prob, b.glitch_background_prob, a.glitch_background_count, b.glitch_background_count),
+            ("Replicate", a.glitch_replicate_prob, b.glitch_replicate_prob, a.glitch_replicate_count, b.glitch_replicate_count),
+            ("Construct", a.glitch_construct_prob, b.glitch_construct_prob, a.glitch_construct_count, b.glitch_construct_count)
+        ];
+
+        let optuid_channels_changed = a.ether_optuids.iter().zip(b.ether_optuids.iter()).filter(|(oa, ob)| oa != ob).count();
+        let integer_channels_changed = a.ether_integers.iter().zip(b.ether_integers.iter()).filter(|(ia, ib)| ia != ib).count();
+
+        let mut peer = vec![
+            ("Port", a.port.to_string(), b.port.to_string()),
+            ("Tor proxy", format!("{}:{}", &a.torproxy_host, a.torproxy_port), format!("{}:{}", &b.torproxy_host, b.torproxy_port)),
+            ("Exposed", a.exposed.to_string(), b.exposed.to_string()),
+            ("Share size", a.share_size.to_string(), b.share_size.to_string()),
+            ("Share interval (μs)", a.share_interval.to_string(), b.share_interval.to_string())
+        ];
+        peer.retain(|(_, va, vb)| va != vb);
+
+        let other_peers_a: BTreeSet<String> = a.other_peers.iter().map(|op| op.publickey.clone()).collect();
+        let other_peers_b: BTreeSet<String> = b.other_peers.iter().map(|op| op.publickey.clone()).collect();
+        let whitelist_a: BTreeSet<String> = a.whitelist.iter().cloned().collect();
+        let whitelist_b: BTreeSet<String> = b.whitelist.iter().cloned().collect();
+
+        WorldDiff {
+            ages: (a.age, b.age),
+            limits: (a.max_num_chains_binlog, b.max_num_chains_binlog),
+            nums_nodes: (a.nodes.len(), b.nodes.len()),
+            nums_controllers: (a.controllers.len(), b.controllers.len()),
+            contents: contents.into_values().filter(|(_, na, nb)| na != nb).collect(),
+            nodes_only: only(&nodes_a, &nodes_b),
+            nodes_changed,
+            controllers_only: only(&ctrls_a, &ctrls_b),
+            controllers_changed,
+            counts,
+            glitches,
+            commandswitches: (a.commandswitch, b.commandswitch),
+            optuid_channels_changed,
+            integer_channels_changed,
+            peer,
+            other_peers_only: only(&other_peers_a, &other_peers_b),
+            whitelist_only: only(&whitelist_a, &whitelist_b),
+            mappings_only: only(&mappings_described(a), &mappings_described(b)),
+            tasks_only: only(&tasks_described(a), &tasks_described(b))
+        }
+    }
+
+}
@@ -0,0 +1,164 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Inspection of saved state without loading it: nodes, controllers, and ether are counted as they are read, and the rest
+// is read as usual. Peer is not exposed, mapped files are not touched, journal is not applied
+
+use std::{
+    collections::HashMap,
+    fs
+};
+
+use crate::serbin::OtBits;
+
+use super::{
+    journal::journal_filepath,
+    migration::since,
+    serbin::read_file,
+    statistics::{
+        ChannelsStatistics,
+        ControllerGenerationStatistics
+    },
+    Content,
+    Controller,
+    Integer,
+    Node,
+    Optuid,
+    Ælhometta
+};
+
+#[derive(Default)]
+pub struct Tally {
+    contents: HashMap<u8, usize>,
+    num_nodes: usize,
+    num_controllers: usize,
+    generation_minimum: u128,
+    generation_sum: u128,
+    generation_maximum: u128,
+    nodes_historing_some: usize,
+    controllers_historing_some: usize,
+    ether_optuids: usize,
+    ether_optuids_some: usize,
+    ether_integers: usize,
+    ether_integers_nonzero: usize
+}
+
+pub struct Inspection {
+    pub file_size: u64,
+    pub compressed: bool,
+    pub format_version: String,
+    pub checksum: Option<u64>, // older formats have none
+    pub journal_size: Option<u64>, // not applied
+    pub num_nodes: usize,
+    pub num_controllers: usize,
+    pub contents: HashMap<Content, usize>,
+    pub generations: ControllerGenerationStatistics,
+    pub nodes_historing_some: usize,
+    pub controllers_historing_some: usize,
+    pub ether_optuids: usize,
+    pub ether_integers: usize,
+    pub channels: ChannelsStatistics,
+    pub world: Ælhometta // without nodes, controllers, historings, ether
+}
+
+impl Tally {
+    pub(super) fn node(&mut self, node: &Node) {
+        *self.contents.entry(node.b_content).or_insert(0) += 1;
+        self.num_nodes += 1;
+    }
+
+    pub(super) fn node_historing(&mut self, opt: Optuid) {
+        self.nodes_historing_some += opt.is_some() as usize;
+    }
+
+    pub(super) fn controller(&mut self, ctrl: &Controller) {
+        if self.num_controllers == 0 {
+            self.generation_minimum = ctrl.generation;
+        }
+        self.generation_minimum = self.generation_minimum.min(ctrl.generation);
+        self.generation_maximum = self.generation_maximum.max(ctrl.generation);
+        self.generation_sum += ctrl.generation;
+        self.num_controllers += 1;
+    }
+
+    pub(super) fn controller_historing(&mut self, opt: Optuid) {
+        self.controllers_historing_some += opt.is_some() as usize;
+    }
+
+    pub(super) fn ether_optuid(&mut self, opt: Optuid) {
+        self.ether_optuids += 1;
+        self.ether_optuids_some += opt.is_some() as usize;
+    }
+
+    pub(super) fn ether_integer(&mut self, integer: Integer) {
+        self.ether_integers += 1;
+        self.ether_integers_nonzero += (integer != 0) as usize;
+    }
+
+}
+
+impl Ælhometta {
+    pub fn inspect(filepath: &str) -> Result<Inspection, String> {
+        let file_size = fs::metadata(filepath).map_err(|err| format!("Cannot open '{}': {}", filepath, &err))?.len();
+        let mut tally = Tally::default();
+        let (world, info) = read_file(filepath, Some(&mut tally))?;
+
+        let mut contents = HashMap::new();
+        for (b, n) in & tally.contents {
+            *contents.entry(Content::ot_bits(*b)).or_insert(0) += *n;
+        }
+
+        Ok(Inspection {
+            file_size,
+            compressed: info.compressed,
+            checksum: if since(&info.format_version, "00001C") { Some(info.checksum) } else { None },
+            format_version: info.format_version,
+            journal_size: fs::metadata(journal_filepath(filepath)).ok().map(|md| md.len()),
+            num_nodes: tally.num_nodes,
+            num_controllers: tally.num_controllers,
+            contents,
+            generations: ControllerGenerationStatistics {
+                minimum: tally.generation_minimum,
+                average: tally.generation_sum / (tally.num_controllers as u128).max(1),
+                maximum: tally.generation_maximum
+            },
+            nodes_historing_some: tally.nodes_historing_some,
+            controllers_historing_some: tally.controllers_historing_some,
+            ether_optuids: tally.ether_optuids,
+            ether_integers: tally.ether_integers,
+            channels: ChannelsStatistics {
+                optuids_some: tally.ether_optuids_some,
+                integers_nonzero: tally.ether_integers_nonzero
+            },
+            world
+        })
+    }
+
+}
@@ -28,7 +28,10 @@
  * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use rand::prelude::*;
+
 use std::{
+    f64::consts::TAU,
     fs,
     time::{
         SystemTime,
@@ -37,92 +40,510 @@ use std::{
 };
 
 use super::{
+    Clock,
+    FileFormat,
+    GeneratorMapping,
+    Integer,
     IntegersFileMapping,
-    Ælhometta
+    Sensor,
+    SensorMapping,
+    Waveform,
+    Ælhometta,
+    IO_SAMPLED_MAX,
+    IO_SAMPLED_PER_MAPPING,
+    IO_SAMPLES_MAX_LEN
 };
 
+// Host metrics are taken from Linux procfs; elsewhere these sensors just do not update their channels
+
+fn read_proc_numbers(filepath: &str, count: usize, scale: f64) -> Option<Vec<Integer>> {
+    let text = fs::read_to_string(filepath).ok()?;
+    let numbers = text.split_whitespace().take(count).map(|s|
+        s.parse::<f64>().ok().map(|x| (x * scale) as Integer)
+    ).collect::<Option<Vec<Integer>>>()?;
+    if numbers.len() == count { Some(numbers) } else { None }
+}
+
+fn read_proc_meminfo() -> Option<Vec<Integer>> {
+    let text = fs::read_to_string("/proc/meminfo").ok()?;
+    let field = |name: &str| -> Option<Integer> {
+        text.lines().find(|line| line.starts_with(name))?
+            .split_whitespace().nth(1)?
+            .parse::<Integer>().ok()
+    };
+    Some(vec![field("MemTotal:")?, field("MemAvailable:")?])
+}
+
+// Files of formats other than raw are (de)coded here; inputs shorter than mapping fill only the beginning of its range
+
+const DEFAULT_WAV_SAMPLE_RATE: u32 = 8000;
+const MAX_WAV_SAMPLE_RATE: usize = 0x7FFF_FFFF; // so that bytes per second fit in u32
+const MAX_PNM_WIDTH: usize = 0xFFFF;
+const MAX_PNM_MAXVAL: usize = 0xFFFF;
+
+fn decode_raw(buf: &[u8], length: usize) -> Option<Vec<Integer>> {
+    if buf.len() >= (length << 3) {
+        let mut bufone = [0u8; 8];
+        Some((0..length).map(|i| {
+            bufone.copy_from_slice(& buf[(i << 3)..((i + 1) << 3)]);
+            i64::from_le_bytes(bufone)
+        }).collect())
+    } else {
+        None
+    }
+}
+
+fn encode_raw(values: &[Integer]) -> Vec<u8> {
+    values.iter().flat_map(|i| i.to_le_bytes()).collect()
+}
+
+// Netpbm header is "magic width height maxval", whitespace-separated, possibly with #-comments, followed by single whitespace
+
+fn pnm_header(buf: &[u8], magic: &[u8]) -> Option<(usize, usize, usize, usize)> {
+    if !buf.starts_with(magic) {
+        return None;
+    }
+    let mut pos = magic.len();
+    let mut numbers = [0usize; 3];
+    for number in &mut numbers {
+        loop {
+            match *buf.get(pos)? {
+                b'#' => while *buf.get(pos)? != b'\n' {
+                    pos += 1;
+                },
+                b if b.is_ascii_whitespace() => pos += 1,
+                _ => break
+            }
+        }
+        let begin = pos;
+        while buf.get(pos)?.is_ascii_digit() {
+            pos += 1;
+        }
+        *number = std::str::from_utf8(& buf[begin..pos]).ok()?.parse().ok()?;
+    }
+    if (numbers[2] == 0) || (numbers[2] > MAX_PNM_MAXVAL) {
+        return None;
+    }
+    Some((numbers[0], numbers[1], numbers[2], pos + 1))
+}
+
+fn decode_pnm(buf: &[u8], magic: &[u8], components: usize, step: usize, length: usize) -> Option<Vec<Integer>> {
+    let (width, height, maxval, data_start) = pnm_header(buf, magic)?;
+    let sample_size = if maxval < 0x100 { 1 } else { 2 };
+    // Header is untrusted, so sizes it implies are checked against the buffer rather than computed blindly
+    let data_size = width.checked_mul(height)?.checked_mul(components)?.checked_mul(sample_size)?;
+    let data = buf.get(data_start..data_start.checked_add(data_size)?)?;
+    let mut values = Vec::with_capacity(length);
+    'rows: for y in (0..height).step_by(step) {
+        for x in (0..width).step_by(step) {
+            for c in 0..components {
+                if values.len() >= length {
+                    break 'rows;
+                }
+                let i = ((y * width + x) * components + c) * sample_size;
+                values.push(if sample_size == 1 {
+                    data[i] as Integer
+                } else {
+                    u16::from_be_bytes([data[i], data[i + 1]]) as Integer
+                });
+            }
+        }
+    }
+    Some(values)
+}
+
+fn encode_pnm(values: &[Integer], magic: &str, components: usize, width: usize) -> Vec<u8> {
+    let pixels = values.len().div_ceil(components);
+    let width = if width > 0 {
+        width
+    } else {
+        ((pixels as f64).sqrt().ceil() as usize).max(1)
+    };
+    let height = pixels.div_ceil(width);
+    let mut buf = format!("{}\n{} {}\n255\n", magic, width, height).into_bytes();
+    buf.extend(values.iter().map(|&v| v.clamp(0, 0xFF) as u8));
+    buf.resize(buf.len() + width * height * components - values.len(), 0);
+    buf
+}
+
+// RIFF WAVE with PCM samples of 8 (unsigned), 16, 24, or 32 (signed) bits; only the 1st audio channel is mapped
+
+fn decode_wav(buf: &[u8], step: usize, length: usize) -> Option<Vec<Integer>> {
+    if (buf.get(0..4)? != b"RIFF") || (buf.get(8..12)? != b"WAVE") {
+        return None;
+    }
+    let mut pos = 12;
+    let mut format: Option<(usize, usize)> = None; // audio channels, bytes per sample
+    loop {
+        let id = buf.get(pos..(pos + 4))?;
+        let size = u32::from_le_bytes(buf.get((pos + 4)..(pos + 8))?.try_into().ok()?) as usize;
+        let chunk = buf.get((pos + 8)..(pos + 8 + size)).unwrap_or(& buf[(pos + 8)..]);
+        match id {
+            b"fmt " => {
+                let audio_format = u16::from_le_bytes(chunk.get(0..2)?.try_into().ok()?);
+                let channels = u16::from_le_bytes(chunk.get(2..4)?.try_into().ok()?) as usize;
+                let bits = u16::from_le_bytes(chunk.get(14..16)?.try_into().ok()?) as usize;
+                if (audio_format != 1) || (channels == 0) || !matches!(bits, 8 | 16 | 24 | 32) {
+                    return None;
+                }
+                format = Some((channels, bits >> 3));
+            },
+            b"data" => {
+                let (channels, sample_size) = format?;
+                let frame_size = channels * sample_size;
+                return Some(chunk.chunks_exact(frame_size).step_by(step).take(length).map(|frame| {
+                    if sample_size == 1 {
+                        (frame[0] as Integer) - 0x80
+                    } else {
+                        let mut bytes = [0u8; 4];
+                        bytes[(4 - sample_size)..].copy_from_slice(& frame[..sample_size]);
+                        (i32::from_le_bytes(bytes) >> ((4 - sample_size) << 3)) as Integer
+                    }
+                }).collect());
+            },
+            _ => {}
+        }
+        pos += 8 + size + (size & 1);
+    }
+}
+
+fn encode_wav(values: &[Integer], sample_rate: usize) -> Vec<u8> {
+    // Out-of-range rate can only come from a state not checked by iomap_out_add(), e.g. edited JSON
+    let sample_rate = match u32::try_from(sample_rate) {
+        Ok(rate) if (rate > 0) && (rate as usize <= MAX_WAV_SAMPLE_RATE) => rate,
+        _ => DEFAULT_WAV_SAMPLE_RATE
+    };
+    // Sizes in header are u32, so too many samples are cut
+    let values = & values[..values.len().min(((u32::MAX - 36) >> 1) as usize)];
+    let data_size = values.len().checked_mul(2).and_then(|size| u32::try_from(size).ok()).unwrap_or(0);
+    let mut buf = Vec::with_capacity(44 + (data_size as usize));
+    buf.extend_from_slice(b"RIFF");
+    buf.extend_from_slice(& data_size.saturating_add(36).to_le_bytes());
+    buf.extend_from_slice(b"WAVEfmt ");
+    buf.extend_from_slice(& 16u32.to_le_bytes());
+    buf.extend_from_slice(& 1u16.to_le_bytes()); // PCM
+    buf.extend_from_slice(& 1u16.to_le_bytes()); // mono
+    buf.extend_from_slice(& sample_rate.to_le_bytes());
+    buf.extend_from_slice(& sample_rate.saturating_mul(2).to_le_bytes()); // bytes per second
+    buf.extend_from_slice(& 2u16.to_le_bytes()); // bytes per frame
+    buf.extend_from_slice(& 16u16.to_le_bytes()); // bits per sample
+    buf.extend_from_slice(b"data");
+    buf.extend_from_slice(& data_size.to_le_bytes());
+    for &v in values {
+        buf.extend_from_slice(& (v.clamp(i16::MIN as Integer, i16::MAX as Integer) as i16).to_le_bytes());
+    }
+    buf
+}
+
+// Channels whose values are sampled for information flow statistics
+pub(super) fn io_sampled_channels(mappings: &[IntegersFileMapping]) -> Vec<usize> {
+    mappings.iter().flat_map(|ifm| ifm.start..(ifm.start + ifm.length.min(IO_SAMPLED_PER_MAPPING))).take(IO_SAMPLED_MAX).collect()
+}
+
+// Mapping of length channels from start must fit in the ether
+fn check_span(start: usize, length: usize, num_channels: usize) -> Result<(), String> {
+    match start.checked_add(length) {
+        Some(end) if end <= num_channels => Ok(()),
+        _ => Err(format!("Mapping ends at {}, but there are only {} integer channels", start.saturating_add(length), num_channels))
+    }
+}
+
+fn check_positive(x: i64, what: &str) -> Result<(), String> {
+    if x > 0 {
+        Ok(())
+    } else {
+        Err(format!("{} must be greater than 0", what))
+    }
+}
+
+// The checks are the same when a mapping is added and when it comes from a file
+
+impl IntegersFileMapping {
+    pub(super) fn check(&self, is_output: bool, num_channels: usize) -> Result<(), String> {
+        if is_output {
+            match self.format {
+                FileFormat::Pgm | FileFormat::Ppm if self.format_param > MAX_PNM_WIDTH => return Err(format!("Width must be at most {}", MAX_PNM_WIDTH)),
+                FileFormat::Wav if self.format_param > MAX_WAV_SAMPLE_RATE => return Err(format!("Sample rate must be at most {}", MAX_WAV_SAMPLE_RATE)),
+                _ => {}
+            }
+        }
+        if self.length == 0 {
+            return Err(String::from("Length must be greater than 0"));
+        }
+        check_span(self.start, self.length, num_channels)?;
+        check_positive(self.interval, "Interval")
+    }
+
+}
+
+impl SensorMapping {
+    pub(super) fn check(&self, num_channels: usize) -> Result<(), String> {
+        check_span(self.start, self.sensor.length(), num_channels)?;
+        check_positive(self.interval, "Interval")
+    }
+
+}
+
+impl GeneratorMapping {
+    pub(super) fn check(&self, num_channels: usize) -> Result<(), String> {
+        if self.length == 0 {
+            return Err(String::from("Length must be greater than 0"));
+        }
+        check_span(self.start, self.length, num_channels)?;
+        check_positive(self.period, "Period")?;
+        if (self.waveform == Waveform::Sequence) && self.values.is_empty() {
+            return Err(String::from("Sequence must contain at least 1 value"));
+        }
+        Ok(())
+    }
+
+}
+
 impl Ælhometta {
+    // Of mappings read from a file, which may have been edited by hand
+    pub(super) fn iomap_check(&self) -> Result<(), String> {
+        let l = self.ether_integers.len();
+        for (i, om) in self.output_mappings.iter().enumerate() {
+            om.check(true, l).map_err(|err| format!("Output mapping {}: {}", i, &err))?;
+        }
+        for (i, im) in self.input_mappings.iter().enumerate() {
+            im.check(false, l).map_err(|err| format!("Input mapping {}: {}", i, &err))?;
+        }
+        for (i, sm) in self.sensor_mappings.iter().enumerate() {
+            sm.check(l).map_err(|err| format!("Sensor mapping {}: {}", i, &err))?;
+        }
+        for (i, gm) in self.generator_mappings.iter().enumerate() {
+            gm.check(l).map_err(|err| format!("Generator mapping {}: {}", i, &err))?;
+        }
+        Ok(())
+    }
+
+    fn generator_value(&mut self, gm: &mut GeneratorMapping, ut: i64) -> Integer {
+        let now = match gm.clock {
+            Clock::Ticks => self.age as i64,
+            Clock::Time => ut
+        };
+        let step = now / gm.period;
+        let phase = now % gm.period;
+        // Offset and amplitude are arbitrary, so all waveforms are computed wider and clamped
+        let (offset, amplitude) = (gm.offset as i128, gm.amplitude as i128);
+        let value = match gm.waveform {
+            Waveform::Constant => offset,
+            Waveform::Sine => offset + ((gm.amplitude as f64) * (TAU * (phase as f64) / (gm.period as f64)).sin()).round() as i128,
+            Waveform::Square => if phase < (gm.period >> 1) {
+                offset + amplitude
+            } else {
+                offset - amplitude
+            },
+            Waveform::Sawtooth => offset - amplitude + ((amplitude << 1) * (phase as i128)) / (gm.period as i128),
+            Waveform::RandomWalk => if step != gm.last_step {
+                if self.rng.gen_bool(0.5) {
+                    (gm.value as i128) + amplitude
+                } else {
+                    (gm.value as i128) - amplitude
+                }
+            } else {
+                gm.value as i128
+            },
+            Waveform::Pulse => if phase < gm.width {
+                offset + amplitude
+            } else {
+                offset
+            },
+            Waveform::Sequence => if !gm.values.is_empty() {
+                gm.values[(step as usize) % gm.values.len()] as i128
+            } else {
+                offset
+            }
+        }.clamp(Integer::MIN as i128, Integer::MAX as i128) as Integer;
+        gm.last_step = step;
+        gm.value = value;
+        value
+    }
+
+    fn sensor_values(&self, sm: &mut SensorMapping, ut: i64) -> Option<Vec<Integer>> {
+        match sm.sensor {
+            Sensor::Time => Some(vec![ut]),
+            Sensor::Age => Some(vec![self.age as Integer]),
+            Sensor::Nodes => Some(vec![self.nodes.len() as Integer]),
+            Sensor::Controllers => Some(vec![self.controllers.len() as Integer]),
+            Sensor::Commands => {
+                let total: u128 = self.commands_count.values().sum();
+                let recent = total.saturating_sub(sm.last_total);
+                sm.last_total = total;
+                Some(vec![recent as Integer])
+            },
+            Sensor::Glitches => Some(vec![
+                self.glitch_background_count as Integer,
+                self.glitch_replicate_count as Integer,
+                self.glitch_construct_count as Integer
+            ]),
+            Sensor::LoadAverage => read_proc_numbers("/proc/loadavg", 3, 1000.0),
+            Sensor::Memory => read_proc_meminfo(),
+            Sensor::Uptime => read_proc_numbers("/proc/uptime", 1, 1.0)
+        }
+    }
+
+
     pub fn iomap_update(&mut self) {
         let ut = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |dur| dur.as_micros()) as i64;
 
         for om in &mut self.output_mappings {
             if ut - om.ut_last_update > om.interval {
-                let buf = self.ether_integers[om.start..(om.start + om.length)].iter().map(|i| 
-                    i.to_le_bytes()).flatten().collect::<Vec<u8>>();
+                let values = & self.ether_integers[om.start..(om.start + om.length)];
+                let buf = match om.format {
+                    FileFormat::Raw => encode_raw(values),
+                    FileFormat::Pgm => encode_pnm(values, "P5", 1, om.format_param),
+                    FileFormat::Ppm => encode_pnm(values, "P6", 3, om.format_param),
+                    FileFormat::Wav => encode_wav(values, om.format_param)
+                };
                 let _ = fs::write(& om.filepath, &buf);
                 om.ut_last_update = ut;
             }
         }
 
+        let mut inputs_updated = false;
         for im in &mut self.input_mappings {
             if ut - im.ut_last_update > im.interval {
                 if let Ok(bufall) = fs::read(& im.filepath) {
-                    if bufall.len() >= (im.length << 3) {
-                        let mut bufone = [0u8; 8];
-                        for i in 0..im.length {
-                            bufone.copy_from_slice(& bufall[(i << 3)..((i + 1) << 3)]);
-                            self.ether_integers[im.start + i] = i64::from_le_bytes(bufone);
-                        }
+                    let step = im.format_param.max(1);
+                    let values = match im.format {
+                        FileFormat::Raw => decode_raw(&bufall, im.length),
+                        FileFormat::Pgm => decode_pnm(&bufall, b"P5", 1, step, im.length),
+                        FileFormat::Ppm => decode_pnm(&bufall, b"P6", 3, step, im.length),
+                        FileFormat::Wav => decode_wav(&bufall, step, im.length)
+                    };
+                    if let Some(values) = values {
+                        self.ether_integers[im.start..(im.start + values.len())].copy_from_slice(&values);
+                        inputs_updated = true;
                     }
                 }
                 im.ut_last_update = ut;
             }
         }
-    }
 
-    pub fn iomap_out_add(&mut self, start: usize, length: usize, interval: i64, filepath: &str) -> Result<(), String> {
-        if length > 0 {
-            if start + length <= self.ether_integers.len() {
-                if interval > 0 {
-                    self.output_mappings.push(IntegersFileMapping::new(
-                        start, length, interval, filepath
-                    ));
-                    Ok(())
-                } else {
-                    Err(String::from("Interval must be greater than 0"))
+        if inputs_updated && !self.output_mappings.is_empty() {
+            self.io_sample();
+        }
+
+        let mut sensor_mappings = std::mem::take(&mut self.sensor_mappings);
+        for sm in &mut sensor_mappings {
+            if ut - sm.ut_last_update > sm.interval {
+                if let Some(values) = self.sensor_values(sm, ut) {
+                    self.ether_integers[sm.start..(sm.start + values.len())].copy_from_slice(&values);
                 }
-            } else {
-                Err(format!("Mapping ends at {}, but there are only {} integer channels", start + length, self.ether_integers.len()))
-            }        
-        } else {
-            Err(String::from("Length must be greater than 0"))
+                sm.ut_last_update = ut;
+            }
+        }
+        self.sensor_mappings = sensor_mappings;
+
+        let mut generator_mappings = std::mem::take(&mut self.generator_mappings);
+        for gm in &mut generator_mappings {
+            let value = self.generator_value(gm, ut);
+            self.ether_integers[gm.start..(gm.start + gm.length)].fill(value);
         }
+        self.generator_mappings = generator_mappings;
+    }
+
+    fn io_sample(&mut self) {
+        let inputs: Vec<Integer> = io_sampled_channels(& self.input_mappings).into_iter().map(|chan| self.ether_integers[chan]).collect();
+        let outputs: Vec<Integer> = io_sampled_channels(& self.output_mappings).into_iter().map(|chan| self.ether_integers[chan]).collect();
+        if self.io_samples.len() >= IO_SAMPLES_MAX_LEN {
+            self.io_samples.pop_front();
+        }
+        self.io_samples.push_back((inputs, outputs));
+    }
+
+    pub fn iomap_out_add(&mut self, start: usize, length: usize, interval: i64, filepath: &str, format: FileFormat, format_param: usize) -> Result<(), String> {
+        let om = IntegersFileMapping::new(start, length, interval, filepath, format, format_param);
+        om.check(true, self.ether_integers.len())?;
+        self.output_mappings.push(om);
+        self.io_samples.clear();
+        Ok(())
     }
 
     pub fn iomap_out_del(&mut self, index: usize) -> Result<(), String> {
         if index < self.output_mappings.len() {
             self.output_mappings.remove(index);
+            self.io_samples.clear();
             Ok(())
         } else {
             Err(format!("There are only {} output mappings", self.output_mappings.len()))
         }
     }
 
-    pub fn iomap_in_add(&mut self, start: usize, length: usize, interval: i64, filepath: &str) -> Result<(), String> {
-        if length > 0 {
-            if start + length <= self.ether_integers.len() {
-                if interval > 0 {
-                    self.input_mappings.push(IntegersFileMapping::new(
-                        start, length, interval, filepath
-                    ));
-                    Ok(())
-                } else {
-                    Err(String::from("Interval must be greater than 0"))
-                }
-            } else {
-                Err(format!("Mapping ends at {}, but there are only {} integer channels", start + length, self.ether_integers.len()))
-            }        
-        } else {
-            Err(String::from("Length must be greater than 0"))
-        }
+    pub fn iomap_in_add(&mut self, start: usize, length: usize, interval: i64, filepath: &str, format: FileFormat, format_param: usize) -> Result<(), String> {
+        let im = IntegersFileMapping::new(start, length, interval, filepath, format, format_param);
+        im.check(false, self.ether_integers.len())?;
+        self.input_mappings.push(im);
+        self.io_samples.clear();
+        Ok(())
     }
 
     pub fn iomap_in_del(&mut self, index: usize) -> Result<(), String> {
         if index < self.input_mappings.len() {
             self.input_mappings.remove(index);
+            self.io_samples.clear();
             Ok(())
         } else {
             Err(format!("There are only {} input mappings", self.input_mappings.len()))
         }
     }
+
+    pub fn iomap_sens_add(&mut self, sensor: Sensor, start: usize, interval: i64) -> Result<(), String> {
+        let sm = SensorMapping::new(sensor, start, interval);
+        sm.check(self.ether_integers.len())?;
+        self.sensor_mappings.push(sm);
+        Ok(())
+    }
+
+    pub fn iomap_sens_del(&mut self, index: usize) -> Result<(), String> {
+        if index < self.sensor_mappings.len() {
+            self.sensor_mappings.remove(index);
+            Ok(())
+        } else {
+            Err(format!("There are only {} sensor mappings", self.sensor_mappings.len()))
+        }
+    }
+
+    pub fn iomap_gen_add(&mut self, waveform: Waveform, start: usize, length: usize, clock: Clock, period: i64, params: &[Integer]) -> Result<(), String> {
+        let mut gm = GeneratorMapping::new(waveform, start, length, clock, period);
+        let param = |i: usize, name: &str| -> Result<Integer, String> {
+            params.get(i).copied().ok_or(format!("{} not specified", name))
+        };
+        match waveform {
+            Waveform::Constant => {
+                gm.offset = param(0, "Value")?;
+            },
+            Waveform::Sine | Waveform::Square | Waveform::Sawtooth => {
+                gm.amplitude = param(0, "Amplitude")?;
+                gm.offset = param(1, "Offset").unwrap_or(0);
+            },
+            Waveform::RandomWalk => {
+                gm.amplitude = param(0, "Step")?;
+                gm.offset = param(1, "Offset").unwrap_or(0);
+            },
+            Waveform::Pulse => {
+                gm.amplitude = param(0, "Amplitude")?;
+                gm.width = param(1, "Width")?;
+                gm.offset = param(2, "Offset").unwrap_or(0);
+            },
+            Waveform::Sequence => {
+                gm.values = params.to_vec();
+            }
+        }
+        gm.value = gm.offset;
+        gm.check(self.ether_integers.len())?;
+        self.generator_mappings.push(gm);
+        Ok(())
+    }
+
+    pub fn iomap_gen_del(&mut self, index: usize) -> Result<(), String> {
+        if index < self.generator_mappings.len() {
+            self.generator_mappings.remove(index);
+            Ok(())
+        } else {
+            Err(format!("There are only {} generator mappings", self.generator_mappings.len()))
+        }
+    }
 }
\ No newline at end of file
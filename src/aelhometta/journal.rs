@@ -0,0 +1,458 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Journal: between full saves ("bases"), only what has changed since the previous save is appended as a delta to
+// "<base>.journal". Each delta refers to its base by checksum; at load, deltas are replayed onto the base, in order,
+// until one is torn, damaged, or refers to another base, and the journal is truncated there
+
+use std::{
+    collections::HashSet,
+    fs::{
+        self,
+        File,
+        OpenOptions
+    },
+    io::{
+        self,
+        ErrorKind,
+        Write
+    },
+    mem
+};
+
+use crate::serbin::{
+    ChecksumReader,
+    ChecksumWriter,
+    ReadBin,
+    WriteBin
+};
+
+use super::{
+    serbin::write_world,
+    Controller,
+    Integer,
+    Node,
+    Optuid,
+    Uid,
+    Ælhometta
+};
+
+const JOURNAL_SUFFIX: &str = ".journal";
+const DELTA_SIGNATURE: &[u8; 4] = b"AEJD";
+
+pub struct JournalTracking {
+    filepath: String, // of base
+    base_checksum: u64,
+    deltas: usize, // appended since base
+    new_node_uid: Uid, // as of previous save, to tell nodes that did not exist then
+    new_controller_uid: Uid,
+    pub(super) nodes: HashSet<Uid>, // inserted, changed, or removed since previous save
+    pub(super) nodes_historing: HashSet<usize>,
+    pub(super) controllers: HashSet<Uid>, // inserted, changed, or removed since previous save
+    pub(super) controllers_historing: HashSet<usize>,
+    ether_optuids: Vec<Optuid>, // as of previous save, to find changed channels
+    ether_integers: Vec<Integer>
+}
+
+struct Delta {
+    nodes: Vec<(Uid, Option<Node>)>, // None if removed
+    nodes_historing: Vec<(usize, Optuid)>,
+    controllers: Vec<(Uid, Option<Controller>)>,
+    controllers_historing: Vec<(usize, Optuid)>,
+    ether_optuids: Vec<(usize, Optuid)>,
+    ether_integers: Vec<(usize, Integer)>,
+    shell: Ælhometta // everything else, without bulk
+}
+
+// Whether uid has been given out after since_uid, uids being 31-bit and cyclic
+fn uid_newer(uid: Uid, since_uid: Uid, new_uid: Uid) -> bool {
+    (uid.wrapping_sub(since_uid) & 0x7FFFFFFF) < (new_uid.wrapping_sub(since_uid) & 0x7FFFFFFF)
+}
+
+pub fn journal_filepath(filepath: &str) -> String {
+    format!("{}{}", filepath, JOURNAL_SUFFIX)
+}
+
+pub(super) fn journal_remove(filepath: &str) -> Result<(), String> {
+    let journal_filepath = journal_filepath(filepath);
+    match fs::remove_file(&journal_filepath) {
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("Cannot remove '{}': {}", &journal_filepath, &err))
+    }
+}
+
+// Payload of intact delta at pos, and position of the next one
+fn delta_at(data: &[u8], pos: usize) -> Option<(&[u8], usize)> {
+    let rest = data.get(pos..)?;
+    if !rest.starts_with(DELTA_SIGNATURE) {
+        return None;
+    }
+    let l = u64::from_le_bytes(rest.get(4..12)?.try_into().ok()?) as usize;
+    let payload = rest.get(12..(12usize.checked_add(l)?))?;
+    let stored_checksum = u64::from_le_bytes(rest.get((12 + l)..(20 + l))?.try_into().ok()?);
+    let mut reader = ChecksumReader::new(payload);
+    io::copy(&mut reader, &mut io::sink()).ok()?;
+    if reader.checksum() == stored_checksum {
+        Some((payload, pos + 20 + l))
+    } else {
+        None
+    }
+}
+
+fn read_delta(payload: &[u8]) -> Result<(u64, usize, Delta), String> {
+    let mut reader = payload;
+    let base_checksum = reader.read_bin()?;
+    let index = reader.read_bin()?;
+
+    let l: usize = reader.read_bin()?;
+    let mut nodes = Vec::with_capacity(l);
+    for _ in 0..l {
+        nodes.push((reader.read_bin()?, reader.read_bin()?));
+    }
+    let l: usize = reader.read_bin()?;
+    let mut nodes_historing = Vec::with_capacity(l);
+    for _ in 0..l {
+        nodes_historing.push((reader.read_bin()?, reader.read_bin()?));
+    }
+
+    let l: usize = reader.read_bin()?;
+    let mut controllers = Vec::with_capacity(l);
+    for _ in 0..l {
+        controllers.push((reader.read_bin()?, reader.read_bin()?));
+    }
+    let l: usize = reader.read_bin()?;
+    let mut controllers_historing = Vec::with_capacity(l);
+    for _ in 0..l {
+        controllers_historing.push((reader.read_bin()?, reader.read_bin()?));
+    }
+
+    let l: usize = reader.read_bin()?;
+    let mut ether_optuids = Vec::with_capacity(l);
+    for _ in 0..l {
+        ether_optuids.push((reader.read_bin()?, reader.read_bin()?));
+    }
+    let l: usize = reader.read_bin()?;
+    let mut ether_integers = Vec::with_capacity(l);
+    for _ in 0..l {
+        ether_integers.push((reader.read_bin()?, reader.read_bin()?));
+    }
+
+    let shell = ChecksumReader::new(reader).read_bin()?;
+
+    Ok((base_checksum, index, Delta {
+        nodes,
+        nodes_historing,
+        controllers,
+        controllers_historing,
+        ether_optuids,
+        ether_integers,
+        shell
+    }))
+}
+
+impl JournalTracking {
+    fn new(filepath: &str, base_checksum: u64, deltas: usize, æh: &Ælhometta) -> Self {
+        Self {
+            filepath: filepath.to_string(),
+            base_checksum,
+            deltas,
+            new_node_uid: æh.new_node_uid,
+            new_controller_uid: æh.new_controller_uid,
+            nodes: HashSet::new(),
+            nodes_historing: HashSet::new(),
+            controllers: HashSet::new(),
+            controllers_historing: HashSet::new(),
+            ether_optuids: æh.ether_optuids.clone(),
+            ether_integers: æh.ether_integers.clone()
+        }
+    }
+
+    pub fn mem_usage(&self) -> usize {
+        use std::mem::size_of;
+        (self.nodes.capacity() + self.controllers.capacity()) * size_of::<Uid>()
+        + (self.nodes_historing.capacity() + self.controllers_historing.capacity()) * size_of::<usize>()
+        + self.ether_optuids.capacity() * size_of::<Optuid>()
+        + self.ether_integers.capacity() * size_of::<Integer>()
+    }
+
+}
+
+impl Ælhometta {
+    pub(super) fn journal_node(&mut self, uid: Uid) {
+        if let Some(ref mut jt) = self.journal {
+            jt.nodes.insert(uid);
+        }
+    }
+
+    // Nodes that have come and gone since previous save are of no concern to the next delta
+    pub(super) fn journal_node_removed(&mut self, uid: Uid) {
+        if let Some(ref mut jt) = self.journal {
+            if uid_newer(uid, jt.new_node_uid, self.new_node_uid) {
+                jt.nodes.remove(&uid);
+            } else {
+                jt.nodes.insert(uid);
+            }
+        }
+    }
+
+    pub(super) fn journal_node_historing(&mut self, i: usize) {
+        if let Some(ref mut jt) = self.journal {
+            jt.nodes_historing.insert(i);
+        }
+    }
+
+    pub(super) fn journal_controller(&mut self, uid: Uid) {
+        if let Some(ref mut jt) = self.journal {
+            jt.controllers.insert(uid);
+        }
+    }
+
+    pub(super) fn journal_controller_removed(&mut self, uid: Uid) {
+        if let Some(ref mut jt) = self.journal {
+            if uid_newer(uid, jt.new_controller_uid, self.new_controller_uid) {
+                jt.controllers.remove(&uid);
+            } else {
+                jt.controllers.insert(uid);
+            }
+        }
+    }
+
+    pub(super) fn journal_controller_historing(&mut self, i: usize) {
+        if let Some(ref mut jt) = self.journal {
+            jt.controllers_historing.insert(i);
+        }
+    }
+
+    // Filepath of the base changes are tracked against, if any
+    pub fn journal_base(&self) -> Option<&str> {
+        self.journal.as_ref().map(|jt| jt.filepath.as_str())
+    }
+
+    pub fn journal_deltas(&self) -> usize {
+        self.journal.as_ref().map_or(0, |jt| jt.deltas)
+    }
+
+    // Numbers of nodes and controllers to go into the next delta
+    pub fn journal_pending(&self) -> (usize, usize) {
+        self.journal.as_ref().map_or((0, 0), |jt| (jt.nodes.len(), jt.controllers.len()))
+    }
+
+    pub fn journal_stop(&mut self) {
+        self.journal = None;
+    }
+
+    // Appends delta, or writes new base if there is none to append to or there are already max_deltas of them
+    pub fn save_journaled(&mut self, filepath: &str, compress: bool, max_deltas: usize) -> Result<(), String> {
+        let base_due = match self.journal {
+            Some(ref jt) => (jt.filepath != filepath) || (jt.deltas >= max_deltas),
+            None => true
+        };
+        if base_due {
+            self.journal_compact(filepath, compress)
+        } else {
+            self.journal_append()
+        }
+    }
+
+    pub fn journal_compact(&mut self, filepath: &str, compress: bool) -> Result<(), String> {
+        let base_checksum = self.save_base(filepath, compress)?;
+        // Empty rather than absent, so that tracking resumes after load
+        let journal_filepath = journal_filepath(filepath);
+        let file = File::create(&journal_filepath).map_err(|err| format!("Cannot create '{}': {}", &journal_filepath, &err))?;
+        file.sync_all().map_err(|err| format!("Cannot sync '{}': {}", &journal_filepath, &err))?;
+        self.journal = Some(JournalTracking::new(filepath, base_checksum, 0, self));
+        Ok(())
+    }
+
+    fn journal_append(&mut self) -> Result<(), String> {
+        if let Some(ref jt) = self.journal {
+            let journal_filepath = journal_filepath(&jt.filepath);
+
+            let mut writer = ChecksumWriter::new(Vec::new());
+            writer.write_bin(jt.base_checksum)?;
+            writer.write_bin(jt.deltas + 1)?;
+
+            // Absent ones which were never saved are skipped
+            let nodes: Vec<(Uid, Option<&Node>)> = jt.nodes.iter().map(|uid| (*uid, self.nodes.get(uid)))
+                .filter(|(uid, optnode)| optnode.is_some() || !uid_newer(*uid, jt.new_node_uid, self.new_node_uid)).collect();
+            writer.write_bin(nodes.len())?;
+            for (uid, optnode) in nodes {
+                writer.write_bin(uid)?;
+                writer.write_bin(optnode)?;
+            }
+            writer.write_bin(jt.nodes_historing.len())?;
+            for i in & jt.nodes_historing {
+                writer.write_bin(*i)?;
+                writer.write_bin(self.nodes_historing[*i])?;
+            }
+
+            let controllers: Vec<(Uid, Option<&Controller>)> = jt.controllers.iter().map(|uid| (*uid, self.controllers.get(uid)))
+                .filter(|(uid, optctrl)| optctrl.is_some() || !uid_newer(*uid, jt.new_controller_uid, self.new_controller_uid)).collect();
+            writer.write_bin(controllers.len())?;
+            for (uid, optctrl) in controllers {
+                writer.write_bin(uid)?;
+                writer.write_bin(optctrl)?;
+            }
+            writer.write_bin(jt.controllers_historing.len())?;
+            for i in & jt.controllers_historing {
+                writer.write_bin(*i)?;
+                writer.write_bin(self.controllers_historing[*i])?;
+            }
+
+            let changed: Vec<usize> = (0..self.ether_optuids.len()).filter(|&i| self.ether_optuids[i] != jt.ether_optuids[i]).collect();
+            writer.write_bin(changed.len())?;
+            for i in changed {
+                writer.write_bin(i)?;
+                writer.write_bin(self.ether_optuids[i])?;
+            }
+            let changed: Vec<usize> = (0..self.ether_integers.len()).filter(|&i| self.ether_integers[i] != jt.ether_integers[i]).collect();
+            writer.write_bin(changed.len())?;
+            for i in changed {
+                writer.write_bin(i)?;
+                writer.write_bin(self.ether_integers[i])?;
+            }
+
+            write_world(&mut ChecksumWriter::new(&mut writer), self, false)?;
+
+            let checksum = writer.checksum();
+            let payload = writer.into_inner();
+
+            let mut file = OpenOptions::new().create(true).append(true).open(&journal_filepath).map_err(|err| format!("Cannot open '{}': {}", &journal_filepath, &err))?;
+            file.write_all(DELTA_SIGNATURE)
+                .and_then(|_| file.write_all(& (payload.len() as u64).to_le_bytes()))
+                .and_then(|_| file.write_all(&payload))
+                .and_then(|_| file.write_all(& checksum.to_le_bytes()))
+                .map_err(|err| format!("Cannot write to '{}': {}", &journal_filepath, &err))?;
+            file.sync_data().map_err(|err| format!("Cannot sync '{}': {}", &journal_filepath, &err))?;
+        }
+
+        let (ether_optuids, ether_integers) = (self.ether_optuids.clone(), self.ether_integers.clone());
+        if let Some(ref mut jt) = self.journal {
+            jt.deltas += 1;
+            jt.new_node_uid = self.new_node_uid;
+            jt.new_controller_uid = self.new_controller_uid;
+            jt.nodes.clear();
+            jt.nodes_historing.clear();
+            jt.controllers.clear();
+            jt.controllers_historing.clear();
+            jt.ether_optuids = ether_optuids;
+            jt.ether_integers = ether_integers;
+        }
+        Ok(())
+    }
+
+    fn journal_apply(&mut self, delta: Delta) {
+        for (uid, optnode) in delta.nodes {
+            match optnode {
+                Some(node) => {
+                    self.nodes.insert(uid, node);
+                },
+                None => {
+                    self.nodes.remove(&uid);
+                }
+            }
+        }
+        for (i, ouid) in delta.nodes_historing {
+            if i < self.nodes_historing.len() {
+                self.nodes_historing[i] = ouid;
+            }
+        }
+
+        for (uid, optctrl) in delta.controllers {
+            match optctrl {
+                Some(ctrl) => {
+                    self.controllers.insert(uid, ctrl);
+                },
+                None => {
+                    self.controllers.remove(&uid);
+                }
+            }
+        }
+        for (i, ouid) in delta.controllers_historing {
+            if i < self.controllers_historing.len() {
+                self.controllers_historing[i] = ouid;
+            }
+        }
+
+        for (i, ouid) in delta.ether_optuids {
+            if i < self.ether_optuids.len() {
+                self.ether_optuids[i] = ouid;
+            }
+        }
+        for (i, integer) in delta.ether_integers {
+            if i < self.ether_integers.len() {
+                self.ether_integers[i] = integer;
+            }
+        }
+
+        // The rest is taken from the shell as a whole
+        let mut shell = delta.shell;
+        shell.nodes = mem::take(&mut self.nodes);
+        shell.nodes_historing = mem::take(&mut self.nodes_historing);
+        shell.controllers = mem::take(&mut self.controllers);
+        shell.controllers_historing = mem::take(&mut self.controllers_historing);
+        shell.ether_optuids = mem::take(&mut self.ether_optuids);
+        shell.ether_integers = mem::take(&mut self.ether_integers);
+        *self = shell;
+    }
+
+    // Called by load() before the peer, if any, is exposed
+    pub(super) fn journal_replay(&mut self, filepath: &str, base_checksum: u64) -> Result<(), String> {
+        let journal_filepath = journal_filepath(filepath);
+        let data = match fs::read(&journal_filepath) {
+            Ok(data) => data,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()), // not journaled
+            Err(err) => return Err(format!("Cannot read from '{}': {}", &journal_filepath, &err))
+        };
+
+        let mut pos = 0;
+        let mut deltas = 0;
+        while let Some((payload, next_pos)) = delta_at(&data, pos) {
+            let (delta_base_checksum, index, delta) = read_delta(payload).map_err(|err| format!("Cannot read from '{}': Corrupt delta {}, offset {}: {}", &journal_filepath, deltas + 1, pos, &err))?;
+            if (delta_base_checksum != base_checksum) || (index != deltas + 1) {
+                break; // left from previous base
+            }
+            self.journal_apply(delta);
+            deltas += 1;
+            pos = next_pos;
+        }
+
+        // Whatever follows the last applied delta would be in the way of the next ones
+        if pos < data.len() {
+            let file = OpenOptions::new().write(true).open(&journal_filepath).map_err(|err| format!("Cannot open '{}': {}", &journal_filepath, &err))?;
+            file.set_len(pos as u64).and_then(|_| file.sync_all()).map_err(|err| format!("Cannot truncate '{}': {}", &journal_filepath, &err))?;
+        }
+
+        self.journal = Some(JournalTracking::new(filepath, base_checksum, deltas, self));
+        Ok(())
+    }
+
+}
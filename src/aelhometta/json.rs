@@ -0,0 +1,834 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// JSON export and import: the same state as the binary save, as a document that external tools can read.
+// Nodes and controllers are sorted by uid, so that equal worlds give equal documents. Probabilities are
+// decimal strings, because JSON numbers do not always come back as the same f64
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use std::{
+    borrow::Cow,
+    collections::{
+        BTreeMap,
+        HashMap,
+        HashSet,
+        VecDeque
+    },
+    fs::File,
+    io::{
+        BufReader,
+        BufWriter,
+        Write
+    }
+};
+
+use rand::{
+    rngs::StdRng,
+    SeedableRng
+};
+
+use crate::serbin::{
+    OtBits,
+    ToBits
+};
+
+use super::{
+    serbin::FORMAT_VERSION,
+    Clock,
+    Command,
+    Construction,
+    Content,
+    Controller,
+    FileFormat,
+    Flags,
+    GeneratorMapping,
+    Integer,
+    IntegersFileMapping,
+    Node,
+    Optuid,
+    OtherPeer,
+    Registers,
+    Reward,
+    Sensor,
+    SensorMapping,
+    Task,
+    TaskFunction,
+    Uid,
+    Waveform,
+    Ælhometta,
+    CONTENTS,
+    NUM_CTRL_DATA_OPTUIDS,
+    NUM_CTRL_INTEGERS,
+    NUM_CTRL_INTEGER_CHANNELS,
+    NUM_CTRL_OPTUIDS,
+    NUM_CTRL_OPTUID_CHANNELS,
+    NUM_INTEGER_CHANNELS,
+    NUM_OPTUID_CHANNELS
+};
+
+const JSON_SIGNATURE: &str = "aelhometta";
+
+const MAX_JSON_CHAINS_BINLOG: u8 = 31; // uids are 31-bit
+
+#[derive(Serialize, Deserialize)]
+struct JsonWorld<'a> {
+    signature: String,
+    version: String,
+    max_num_chains_binlog: u8,
+    new_node_uid: Uid,
+    nodes: Vec<JsonNode<'a>>,
+    nodes_historing: Vec<Optuid>,
+    i_nodes_historing: usize,
+    new_controller_uid: Uid,
+    controllers: Vec<JsonController>,
+    controllers_historing: Vec<Optuid>,
+    i_controllers_historing: usize,
+    commandswitch: String, // hexadecimal
+    ether: JsonEther,
+    counts: JsonCounts,
+    glitches: JsonGlitches,
+    peer: JsonPeer,
+    mappings: JsonMappings,
+    tasks: JsonTasks
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonNode<'a> {
+    uid: Uid,
+    content: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    byte: Option<u8>, // only when content alone does not give it back
+    next: Optuid,
+    altnext: Optuid
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonController {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    uid: Optuid, // absent for new controller being constructed
+    chain_start: Optuid,
+    exec: Optuid,
+    data_optuids: Vec<Optuid>,
+    i_data_optuid: usize,
+    new_chain: Optuid,
+    new_controller: Option<Box<JsonController>>,
+    integer: Integer,
+    success: bool,
+    optuids: Vec<Optuid>,
+    i_optuid: usize,
+    integers: Vec<Integer>,
+    i_integer: usize,
+    optuid_channels: Vec<usize>,
+    i_optuid_channel: usize,
+    i_peer: usize,
+    integer_channels: Vec<usize>,
+    i_integer_channel: usize,
+    generation: u128,
+    ticks: u128
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonEther {
+    optuids: Vec<Optuid>,
+    integers: Vec<Integer>
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonCounts {
+    age: u128,
+    spaces: u128,
+    branches_main: u128,
+    branches_alt: u128,
+    commands: BTreeMap<String, u128>,
+    constructions: BTreeMap<String, u128>
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonGlitch {
+    prob: String, // decimal, exact
+    count: u128
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonGlitches {
+    background: JsonGlitch,
+    replicate: JsonGlitch,
+    construct: JsonGlitch
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonOtherPeer {
+    publickey: String,
+    onion: String,
+    port: u16,
+    ether_integers: Vec<Integer>,
+    ut_last_update: i64
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonPeer {
+    share_size: usize,
+    share_interval: i64,
+    ut_last_share: i64,
+    port: u16,
+    torproxy_port: u16,
+    torproxy_host: String,
+    exposed: bool,
+    other_peers: Vec<JsonOtherPeer>,
+    whitelist: Vec<String>,
+    in_permitted_num: u64,
+    in_attempted_num: u64
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonFileMapping {
+    start: usize,
+    length: usize,
+    interval: i64,
+    filepath: String,
+    ut_last_update: i64,
+    format: String,
+    format_param: usize
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSensorMapping {
+    sensor: String,
+    start: usize,
+    interval: i64,
+    ut_last_update: i64,
+    last_total: u128
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonGeneratorMapping {
+    waveform: String,
+    start: usize,
+    length: usize,
+    clock: String,
+    period: i64,
+    amplitude: Integer,
+    offset: Integer,
+    width: i64,
+    values: Vec<Integer>,
+    value: Integer,
+    last_step: i64
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonMappings {
+    output: Vec<JsonFileMapping>,
+    input: Vec<JsonFileMapping>,
+    sensor: Vec<JsonSensorMapping>,
+    generator: Vec<JsonGeneratorMapping>
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTask {
+    function: String,
+    output: usize,
+    inputs: Vec<usize>,
+    reward: String,
+    amount: u32,
+    attempts_count: u128,
+    completions_count: u128
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonRenewal {
+    controller: Uid,
+    count: u32
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTasks {
+    tasks: Vec<JsonTask>,
+    bonus_ticks: Vec<Uid>,
+    renewals: Vec<JsonRenewal>
+}
+
+fn content_name(b: u8) -> String {
+    format!("{:?}", Content::ot_bits(b))
+}
+
+fn content_from_name(name: &str) -> Option<Content> {
+    CONTENTS.iter().find(|content| format!("{:?}", content) == name).copied()
+}
+
+fn command_from_name(name: &str) -> Option<Command> {
+    CONTENTS.iter().find_map(|content| match *content {
+        Content::Command(command) if format!("{:?}", command) == name => Some(command),
+        _ => None
+    })
+}
+
+fn construction_from_name(name: &str) -> Option<Construction> {
+    CONTENTS.iter().find_map(|content| match *content {
+        Content::Construction(construction) if format!("{:?}", construction) == name => Some(construction),
+        _ => None
+    })
+}
+
+fn prob_from_str(s: &str, what: &str) -> Result<f64, String> {
+    s.parse::<f64>().map_err(|err| format!("Bad {} glitch probability '{}': {}", what, s, &err))
+}
+
+fn check_len(len: usize, expected: usize, what: &str) -> Result<(), String> {
+    if len == expected {
+        Ok(())
+    } else {
+        Err(format!("Wrong length of {}: {} instead of {}", what, len, expected))
+    }
+}
+
+fn check_index(i: usize, len: usize, what: &str) -> Result<(), String> {
+    if i < len {
+        Ok(())
+    } else {
+        Err(format!("Index {} out of range: {} not less than {}", what, i, len))
+    }
+}
+
+impl JsonController {
+    fn from_controller(uid: Optuid, ctrl: &Controller) -> Self {
+        Self {
+            uid,
+            chain_start: ctrl.chain_start_optuid,
+            exec: ctrl.exec_optuid,
+            data_optuids: ctrl.data_optuids.clone(),
+            i_data_optuid: ctrl.i_data_optuid,
+            new_chain: ctrl.new_chain_optuid,
+            new_controller: ctrl.new_controller.as_ref().map(|nc| Box::new(Self::from_controller(None, nc))),
+            integer: ctrl.registers.integer,
+            success: ctrl.flags.success,
+            optuids: ctrl.optuids.clone(),
+            i_optuid: ctrl.i_optuid,
+            integers: ctrl.integers.clone(),
+            i_integer: ctrl.i_integer,
+            optuid_channels: ctrl.optuid_channels.clone(),
+            i_optuid_channel: ctrl.i_optuid_channel,
+            i_peer: ctrl.i_peer,
+            integer_channels: ctrl.integer_channels.clone(),
+            i_integer_channel: ctrl.i_integer_channel,
+            generation: ctrl.generation,
+            ticks: ctrl.ticks
+        }
+    }
+
+    fn into_controller(self) -> Result<Controller, String> {
+        check_len(self.data_optuids.len(), NUM_CTRL_DATA_OPTUIDS, "data optuids")?;
+        check_index(self.i_data_optuid, NUM_CTRL_DATA_OPTUIDS, "of data optuid")?;
+        check_len(self.optuids.len(), NUM_CTRL_OPTUIDS, "optuids")?;
+        check_index(self.i_optuid, NUM_CTRL_OPTUIDS, "of optuid")?;
+        check_len(self.integers.len(), NUM_CTRL_INTEGERS, "integers")?;
+        check_index(self.i_integer, NUM_CTRL_INTEGERS, "of integer")?;
+        check_len(self.optuid_channels.len(), NUM_CTRL_OPTUID_CHANNELS, "optuid channels")?;
+        check_index(self.i_optuid_channel, NUM_CTRL_OPTUID_CHANNELS, "of optuid channel")?;
+        check_len(self.integer_channels.len(), NUM_CTRL_INTEGER_CHANNELS, "integer channels")?;
+        check_index(self.i_integer_channel, NUM_CTRL_INTEGER_CHANNELS, "of integer channel")?;
+        let new_controller = match self.new_controller {
+            Some(nc) => Some(Box::new(nc.into_controller()?)),
+            None => None
+        };
+        Ok(Controller {
+            chain_start_optuid: self.chain_start,
+            exec_optuid: self.exec,
+            data_optuids: self.data_optuids,
+            i_data_optuid: self.i_data_optuid,
+            new_chain_optuid: self.new_chain,
+            new_controller,
+            registers: Registers {
+                integer: self.integer
+            },
+            flags: Flags {
+                success: self.success
+            },
+            optuids: self.optuids,
+            i_optuid: self.i_optuid,
+            integers: self.integers,
+            i_integer: self.i_integer,
+            optuid_channels: self.optuid_channels,
+            i_optuid_channel: self.i_optuid_channel,
+            i_peer: self.i_peer,
+            integer_channels: self.integer_channels,
+            i_integer_channel: self.i_integer_channel,
+            generation: self.generation,
+            ticks: self.ticks
+        })
+    }
+
+}
+
+impl JsonFileMapping {
+    fn from_mapping(ifm: &IntegersFileMapping) -> Self {
+        Self {
+            start: ifm.start,
+            length: ifm.length,
+            interval: ifm.interval,
+            filepath: ifm.filepath.clone(),
+            ut_last_update: ifm.ut_last_update,
+            format: ifm.format.name().to_string(),
+            format_param: ifm.format_param
+        }
+    }
+
+    fn into_mapping(self) -> Result<IntegersFileMapping, String> {
+        Ok(IntegersFileMapping {
+            start: self.start,
+            length: self.length,
+            interval: self.interval,
+            filepath: self.filepath,
+            ut_last_update: self.ut_last_update,
+            format: FileFormat::from_name(&self.format).ok_or(format!("Unknown file format '{}'", &self.format))?,
+            format_param: self.format_param
+        })
+    }
+
+}
+
+impl Ælhometta {
+    pub fn export_json(&self, filepath: &str) -> Result<(), String> {
+        let content_names: Vec<String> = (0..=u8::MAX).map(content_name).collect();
+
+        let mut nodes: Vec<JsonNode> = self.nodes.iter().map(|(uid, node)| {
+            let content = Content::ot_bits(node.b_content);
+            JsonNode {
+                uid: *uid,
+                content: Cow::Borrowed(content_names[node.b_content as usize].as_str()),
+                byte: if content.to_bits() == node.b_content { None } else { Some(node.b_content) },
+                next: Optuid::ot_bits(node.b_next),
+                altnext: Optuid::ot_bits(node.b_altnext)
+            }
+        }).collect();
+        nodes.sort_unstable_by_key(|node| node.uid);
+
+        let mut controllers: Vec<JsonController> = self.controllers.iter().map(|(uid, ctrl)| JsonController::from_controller(Some(*uid), ctrl)).collect();
+        controllers.sort_unstable_by_key(|ctrl| ctrl.uid);
+
+        let mut whitelist: Vec<String> = self.whitelist.iter().cloned().collect();
+        whitelist.sort_unstable();
+
+        let mut renewals: Vec<JsonRenewal> = self.renewals.iter().map(|(cuid, n)| JsonRenewal { controller: *cuid, count: *n }).collect();
+        renewals.sort_unstable_by_key(|renewal| renewal.controller);
+
+        // "before" + "now", as in binary save
+        let (mut in_permitted_num, mut in_attempted_num) = (self.in_permitted_before_num, self.in_attempted_before_num);
+        if let Some(ref efunguz) = self.efunguz {
+            in_permitted_num += efunguz.in_permitted_num();
+            in_attempted_num += efunguz.in_attempted_num();
+        }
+
+        let world = JsonWorld {
+            signature: JSON_SIGNATURE.to_string(),
+            version: FORMAT_VERSION.to_string(),
+            max_num_chains_binlog: self.max_num_chains_binlog,
+            new_node_uid: self.new_node_uid,
+            nodes,
+            nodes_historing: self.nodes_historing.clone(),
+            i_nodes_historing: self.i_nodes_historing,
+            new_controller_uid: self.new_controller_uid,
+            controllers,
+            controllers_historing: self.controllers_historing.clone(),
+            i_controllers_historing: self.i_controllers_historing,
+            commandswitch: format!("{:032X}", self.commandswitch),
+            ether: JsonEther {
+                optuids: self.ether_optuids.clone(),
+                integers: self.ether_integers.clone()
+            },
+            counts: JsonCounts {
+                age: self.age,
+                spaces: self.spaces_count,
+                branches_main: self.branches_main_count,
+                branches_alt: self.branches_alt_count,
+                commands: self.commands_count.iter().map(|(command, count)| (format!("{:?}", command), *count)).collect(),
+                constructions: self.constructions_count.iter().map(|(construction, count)| (format!("{:?}", construction), *count)).collect()
+            },
+            glitches: JsonGlitches {
+                background: JsonGlitch { prob: format!("{:e}", self.glitch_background_prob), count: self.glitch_background_count },
+                replicate: JsonGlitch { prob: format!("{:e}", self.glitch_replicate_prob), count: self.glitch_replicate_count },
+                construct: JsonGlitch { prob: format!("{:e}", self.glitch_construct_prob), count: self.glitch_construct_count }
+            },
+            peer: JsonPeer {
+                share_size: self.share_size,
+                share_interval: self.share_interval,
+                ut_last_share: self.ut_last_share,
+                port: self.port,
+                torproxy_port: self.torproxy_port,
+                torproxy_host: self.torproxy_host.clone(),
+                exposed: self.exposed,
+                other_peers: self.other_peers.iter().map(|op| JsonOtherPeer {
+                    publickey: op.publickey.clone(),
+                    onion: op.onion.clone(),
+                    port: op.port,
+                    ether_integers: op.ether_integers.clone(),
+                    ut_last_update: op.ut_last_update
+                }).collect(),
+                whitelist,
+                in_permitted_num,
+                in_attempted_num
+            },
+            mappings: JsonMappings {
+                output: self.output_mappings.iter().map(JsonFileMapping::from_mapping).collect(),
+                input: self.input_mappings.iter().map(JsonFileMapping::from_mapping).collect(),
+                sensor: self.sensor_mappings.iter().map(|sm| JsonSensorMapping {
+                    sensor: sm.sensor.name().to_string(),
+                    start: sm.start,
+                    interval: sm.interval,
+                    ut_last_update: sm.ut_last_update,
+                    last_total: sm.last_total
+                }).collect(),
+                generator: self.generator_mappings.iter().map(|gm| JsonGeneratorMapping {
+                    waveform: gm.waveform.name().to_string(),
+                    start: gm.start,
+                    length: gm.length,
+                    clock: gm.clock.name().to_string(),
+                    period: gm.period,
+                    amplitude: gm.amplitude,
+                    offset: gm.offset,
+                    width: gm.width,
+                    values: gm.values.clone(),
+                    value: gm.value,
+                    last_step: gm.last_step
+                }).collect()
+            },
+            tasks: JsonTasks {
+                tasks: self.tasks.iter().map(|task| JsonTask {
+                    function: task.function.name().to_string(),
+                    output: task.output,
+                    inputs: task.inputs.clone(),
+                    reward: task.reward.name().to_string(),
+                    amount: task.amount,
+                    attempts_count: task.attempts_count,
+                    completions_count: task.completions_count
+                }).collect(),
+                bonus_ticks: self.bonus_ticks.iter().copied().collect(),
+                renewals
+            }
+        };
+
+        let file = File::create(filepath).map_err(|err| format!("Cannot create '{}': {}", filepath, &err))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, &world).map_err(|err| format!("Cannot write to '{}': {}", filepath, &err))?;
+        writer.write_all(b"\n").map_err(|err| format!("Cannot write to '{}': {}", filepath, &err))?;
+        writer.flush().map_err(|err| format!("Cannot write to '{}': {}", filepath, &err))
+    }
+
+    // The secret key is not in the document, so it comes separately
+    pub fn import_json(filepath: &str, secretkey: &str) -> Result<Self, String> {
+        let file = File::open(filepath).map_err(|err| format!("Cannot open '{}': {}", filepath, &err))?;
+        let world: JsonWorld<'static> = serde_json::from_reader(BufReader::new(file)).map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))?;
+        let mut æh = Self::from_json(world).map_err(|err| format!("Cannot import from '{}': {}", filepath, &err))?;
+        æh.secretkey = secretkey.to_string();
+        if æh.exposed {
+            æh.peer_expose().map_err(|err| format!("Cannot import from '{}': {}", filepath, &err))?;
+        }
+        Ok(æh)
+    }
+
+    fn from_json(world: JsonWorld) -> Result<Self, String> {
+        if world.signature != JSON_SIGNATURE {
+            return Err(format!("Wrong signature: expected '{}', found '{}'", JSON_SIGNATURE, &world.signature));
+        }
+        if world.version != FORMAT_VERSION {
+            return Err(format!("Wrong version: expected '{}', found '{}'", FORMAT_VERSION, &world.version));
+        }
+        if world.max_num_chains_binlog > MAX_JSON_CHAINS_BINLOG {
+            return Err(format!("Maximum number binlog too large: {}", world.max_num_chains_binlog));
+        }
+        let max_num_chains: usize = 1 << world.max_num_chains_binlog;
+        let max_num_chains_binmask: usize = max_num_chains - 1;
+
+        let mut nodes = HashMap::with_capacity(world.nodes.len());
+        for jn in world.nodes {
+            let b_content = match jn.byte {
+                Some(b) => b,
+                None => content_from_name(&jn.content).ok_or(format!("Unknown content '{}' of node {:X}", &jn.content, jn.uid))?.to_bits()
+            };
+            nodes.insert(jn.uid, Node::new(b_content, jn.next.to_bits(), jn.altnext.to_bits()));
+        }
+        check_len(world.nodes_historing.len(), max_num_chains, "nodes historing")?;
+        check_index(world.i_nodes_historing, max_num_chains, "of nodes historing")?;
+
+        let mut controllers = HashMap::with_capacity(world.controllers.len());
+        for jc in world.controllers {
+            let uid = jc.uid.ok_or("Controller without uid")?;
+            controllers.insert(uid, jc.into_controller().map_err(|err| format!("Controller {:X}: {}", uid, &err))?);
+        }
+        check_len(world.controllers_historing.len(), max_num_chains, "controllers historing")?;
+        check_index(world.i_controllers_historing, max_num_chains, "of controllers historing")?;
+
+        let commandswitch = u128::from_str_radix(&world.commandswitch, 16).map_err(|err| format!("Bad commandswitch '{}': {}", &world.commandswitch, &err))?;
+
+        check_len(world.ether.optuids.len(), NUM_OPTUID_CHANNELS, "optuid ether")?;
+        check_len(world.ether.integers.len(), NUM_INTEGER_CHANNELS, "integer ether")?;
+
+        let mut commands_count = HashMap::with_capacity(world.counts.commands.len());
+        for (name, count) in & world.counts.commands {
+            commands_count.insert(command_from_name(name).ok_or(format!("Unknown command '{}'", name))?, *count);
+        }
+        let mut constructions_count = HashMap::with_capacity(world.counts.constructions.len());
+        for (name, count) in & world.counts.constructions {
+            constructions_count.insert(construction_from_name(name).ok_or(format!("Unknown construction '{}'", name))?, *count);
+        }
+
+        let glitch_background_prob = prob_from_str(&world.glitches.background.prob, "background")?;
+        let glitch_replicate_prob = prob_from_str(&world.glitches.replicate.prob, "replicate")?;
+        let glitch_construct_prob = prob_from_str(&world.glitches.construct.prob, "construct")?;
+
+        let peer = world.peer;
+        let other_peers = peer.other_peers.into_iter().map(|jop| OtherPeer {
+            publickey: jop.publickey,
+            onion: jop.onion,
+            port: jop.port,
+            ether_integers: jop.ether_integers,
+            ut_last_update: jop.ut_last_update
+        }).collect();
+
+        let mappings = world.mappings;
+        let output_mappings = mappings.output.into_iter().map(JsonFileMapping::into_mapping).collect::<Result<Vec<_>, _>>()?;
+        let input_mappings = mappings.input.into_iter().map(JsonFileMapping::into_mapping).collect::<Result<Vec<_>, _>>()?;
+        let mut sensor_mappings = Vec::with_capacity(mappings.sensor.len());
+        for jsm in mappings.sensor {
+            sensor_mappings.push(SensorMapping {
+                sensor: Sensor::from_name(&jsm.sensor).ok_or(format!("Unknown sensor '{}'", &jsm.sensor))?,
+                start: jsm.start,
+                interval: jsm.interval,
+                ut_last_update: jsm.ut_last_update,
+                last_total: jsm.last_total
+            });
+        }
+        let mut generator_mappings = Vec::with_capacity(mappings.generator.len());
+        for jgm in mappings.generator {
+            generator_mappings.push(GeneratorMapping {
+                waveform: Waveform::from_name(&jgm.waveform).ok_or(format!("Unknown waveform '{}'", &jgm.waveform))?,
+                start: jgm.start,
+                length: jgm.length,
+                clock: Clock::from_name(&jgm.clock).ok_or(format!("Unknown clock '{}'", &jgm.clock))?,
+                period: jgm.period,
+                amplitude: jgm.amplitude,
+                offset: jgm.offset,
+                width: jgm.width,
+                values: jgm.values,
+                value: jgm.value,
+                last_step: jgm.last_step
+            });
+        }
+
+        let mut tasks = Vec::with_capacity(world.tasks.tasks.len());
+        for jt in world.tasks.tasks {
+            tasks.push(Task {
+                function: TaskFunction::from_name(&jt.function).ok_or(format!("Unknown task function '{}'", &jt.function))?,
+                output: jt.output,
+                inputs: jt.inputs,
+                reward: Reward::from_name(&jt.reward).ok_or(format!("Unknown reward '{}'", &jt.reward))?,
+                amount: jt.amount,
+                attempts_count: jt.attempts_count,
+                completions_count: jt.completions_count
+            });
+        }
+        let bonus_ticks: VecDeque<Uid> = world.tasks.bonus_ticks.into_iter().collect();
+        let renewals: HashMap<Uid, u32> = world.tasks.renewals.into_iter().map(|jr| (jr.controller, jr.count)).collect();
+
+        let æh = Self {
+            max_num_chains_binlog: world.max_num_chains_binlog,
+            new_node_uid: world.new_node_uid,
+            nodes,
+            nodes_historing: world.nodes_historing,
+            i_nodes_historing: world.i_nodes_historing,
+            new_controller_uid: world.new_controller_uid,
+            controllers,
+            controllers_historing: world.controllers_historing,
+            i_controllers_historing: world.i_controllers_historing,
+            commandswitch,
+            ether_optuids: world.ether.optuids,
+            ether_integers: world.ether.integers,
+            age: world.counts.age,
+            spaces_count: world.counts.spaces,
+            branches_main_count: world.counts.branches_main,
+            branches_alt_count: world.counts.branches_alt,
+            commands_count,
+            constructions_count,
+            glitch_background_prob,
+            glitch_background_count: world.glitches.background.count,
+            glitch_replicate_prob,
+            glitch_replicate_count: world.glitches.replicate.count,
+            glitch_construct_prob,
+            glitch_construct_count: world.glitches.construct.count,
+            share_size: peer.share_size,
+            share_interval: peer.share_interval,
+            ut_last_share: peer.ut_last_share,
+            secretkey: String::new(),
+            port: peer.port,
+            torproxy_port: peer.torproxy_port,
+            torproxy_host: peer.torproxy_host,
+            exposed: peer.exposed,
+            other_peers,
+            whitelist: peer.whitelist.into_iter().collect::<HashSet<String>>(),
+            in_permitted_before_num: peer.in_permitted_num,
+            in_attempted_before_num: peer.in_attempted_num,
+            output_mappings,
+            input_mappings,
+            sensor_mappings,
+            generator_mappings,
+            tasks,
+            bonus_ticks,
+            renewals,
+
+            max_num_chains,
+            max_num_chains_binmask,
+            rng: StdRng::from_entropy(),
+            efunguz: None,
+            io_samples: VecDeque::new(),
+            journal: None
+        };
+        // Channels and counts that ticking relies upon
+        æh.iomap_check()?;
+        æh.tasks_check()?;
+        Ok(æh)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        fs,
+        path::PathBuf,
+        process
+    };
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("aelhometta-test-json-{}-{}", process::id(), name))
+    }
+
+    fn saved(æh: &Ælhometta, name: &str) -> Vec<u8> {
+        let path = temp_path(name);
+        æh.save(&path.display().to_string(), false).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap_or(());
+        bytes
+    }
+
+    fn exported(æh: &Ælhometta, name: &str) -> (PathBuf, String) {
+        let path = temp_path(name);
+        æh.export_json(&path.display().to_string()).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        (path, json)
+    }
+
+    fn imported(path: &PathBuf, secretkey: &str) -> Result<Ælhometta, String> {
+        let result = Ælhometta::import_json(&path.display().to_string(), secretkey);
+        fs::remove_file(path).unwrap_or(());
+        result
+    }
+
+    // Everything but the bulk, which is written in the order of hash maps, so there is one node and one controller
+    fn sparse_world() -> Ælhometta {
+        let mut æh = Ælhometta::new(4);
+        æh.nodes.insert(0x11, Node::new(0x2A, Some(0x11).to_bits(), None.to_bits()));
+        æh.controllers.insert(0x22, Controller::new());
+        æh.ether_integers[5] = -7;
+        æh.age = 123456;
+        æh.set_glitch_background_prob(0.1);
+        æh.set_glitch_construct_prob(1e-7);
+        æh.whitelist.insert(String::from("b"));
+        æh.whitelist.insert(String::from("a"));
+        æh.iomap_out_add(0, 4, 10, "out.wav", FileFormat::Wav, 8000).unwrap();
+        æh.iomap_in_add(4, 2, 20, "in.bin", FileFormat::Raw, 0).unwrap();
+        æh.iomap_sens_add(Sensor::Glitches, 6, 30).unwrap();
+        æh.iomap_gen_add(Waveform::Sequence, 9, 1, Clock::Ticks, 5, &[1, -2, 3]).unwrap();
+        æh.task_add(TaskFunction::Sum, 10, vec![11, 12], Reward::Renew, 3).unwrap();
+        æh.renewals.insert(0x22, 2);
+        æh.renewals.insert(0x33, 1);
+        æh.bonus_ticks.push_back(0x22);
+        æh
+    }
+
+    #[test]
+    fn round_trip_is_binary_equal() {
+        let æh = sparse_world();
+        let (path, _) = exported(&æh, "sparse.json");
+        let æh_imported = imported(&path, &æh.secretkey()).unwrap();
+        assert!(saved(&æh, "a.bin") == saved(&æh_imported, "b.bin"));
+    }
+
+    #[test]
+    fn round_trip_of_populated_world() {
+        let mut æh = Ælhometta::new(12);
+        æh.introduce_ancestor_b(1);
+        for _ in 0..5000 {
+            æh.tick(&None);
+        }
+        assert!(æh.nodes.len() > 1);
+        let (path, json) = exported(&æh, "populated.json");
+        let æh_imported = imported(&path, &æh.secretkey()).unwrap();
+        assert_eq!(æh_imported.nodes.len(), æh.nodes.len());
+        assert_eq!(æh_imported.controllers.len(), æh.controllers.len());
+        // Nodes and controllers are sorted in the document, so it is the same again
+        assert_eq!(exported(&æh_imported, "again.json").1, json);
+        fs::remove_file(temp_path("again.json")).unwrap_or(());
+        // Then the binary file can differ only in order of nodes and controllers
+        assert_eq!(saved(&æh, "a.bin").len(), saved(&æh_imported, "b.bin").len());
+    }
+
+    type Edit = fn(&mut serde_json::Value);
+
+    #[test]
+    fn hand_edits_are_checked() {
+        let cases: [(&str, Edit, &str); 7] = [
+            ("binlog", |v| v["max_num_chains_binlog"] = 64.into(), "binlog too large"),
+            ("output", |v| v["mappings"]["output"][0]["start"] = NUM_INTEGER_CHANNELS.into(), "Output mapping 0: Mapping ends at"),
+            ("input", |v| v["mappings"]["input"][0]["length"] = usize::MAX.into(), "Input mapping 0: Mapping ends at"),
+            ("generator", |v| v["mappings"]["generator"][0]["period"] = 0.into(), "Generator mapping 0: Period must be greater than 0"),
+            ("channel", |v| v["tasks"]["tasks"][0]["inputs"][1] = NUM_INTEGER_CHANNELS.into(), "Task 0: Channel"),
+            ("amount", |v| v["tasks"]["tasks"][0]["amount"] = 0.into(), "Task 0: Reward amount must be greater than 0"),
+            ("renewal", |v| v["tasks"]["renewals"][0]["count"] = 0.into(), "Renewal count of controller 22 must be greater than 0")
+        ];
+        let (path, json) = exported(&sparse_world(), "edited.json");
+        let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for (name, edit, expected) in cases {
+            let mut value = document.clone();
+            edit(&mut value);
+            fs::write(&path, serde_json::to_string(&value).unwrap()).unwrap();
+            match imported(&path, "") {
+                Ok(_) => panic!("'{}' imported", name),
+                Err(err) => assert!(err.contains(expected), "'{}': {}", name, &err)
+            }
+        }
+    }
+
+}
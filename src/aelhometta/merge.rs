@@ -0,0 +1,173 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Island model: populations evolved apart are brought together, copied with new uids
+
+use std::collections::{
+    HashMap,
+    HashSet,
+    VecDeque
+};
+
+use rand::Rng;
+
+use crate::serbin::{
+    OtBits,
+    ToBits
+};
+
+use super::{
+    Content,
+    Controller,
+    Optuid,
+    Uid,
+    Ælhometta
+};
+
+pub enum MergeSelection {
+    All, // all nodes and controllers
+    Fraction(f64), // each controller with this probability, with nodes reachable from it
+    Controllers(Vec<Uid>) // these controllers, with nodes reachable from them
+}
+
+pub struct MergeOutcome {
+    pub nodes_copied: usize,
+    pub controllers_copied: usize,
+    pub nodes_skipped: usize, // the oldest of selected, that would be pushed out by the newer ones anyway
+    pub controllers_skipped: usize
+}
+
+// Oldest first, as they would be pushed out
+fn historing_ordered(historing: &[Optuid], i_historing: usize) -> impl Iterator<Item = Uid> + '_ {
+    historing[i_historing..].iter().chain(historing[..i_historing].iter()).filter_map(|ouid| *ouid)
+}
+
+fn controller_optuids(ctrl: &Controller, ouids: &mut Vec<Optuid>) {
+    ouids.push(ctrl.chain_start_optuid);
+    ouids.push(ctrl.exec_optuid);
+    ouids.extend_from_slice(&ctrl.data_optuids);
+    ouids.push(ctrl.new_chain_optuid);
+    ouids.extend_from_slice(&ctrl.optuids);
+    if let Some(ref new_ctrl) = ctrl.new_controller {
+        controller_optuids(new_ctrl, ouids);
+    }
+}
+
+fn controller_remapped(ctrl: &Controller, remap: &impl Fn(Optuid) -> Optuid) -> Controller {
+    let mut ctrl = ctrl.clone();
+    ctrl.chain_start_optuid = remap(ctrl.chain_start_optuid);
+    ctrl.exec_optuid = remap(ctrl.exec_optuid);
+    for ouid in ctrl.data_optuids.iter_mut() {
+        *ouid = remap(*ouid);
+    }
+    ctrl.new_chain_optuid = remap(ctrl.new_chain_optuid);
+    for ouid in ctrl.optuids.iter_mut() {
+        *ouid = remap(*ouid);
+    }
+    ctrl.new_controller = ctrl.new_controller.map(|new_ctrl| Box::new(controller_remapped(&new_ctrl, remap)));
+    ctrl
+}
+
+impl Ælhometta {
+    // Copies go through the usual historings, so they become the newest and push out the oldest of this state.
+    // Ether, counters, and configuration of this state stay as they are
+    pub fn merge(&mut self, other: &Self, selection: &MergeSelection) -> MergeOutcome {
+        let mut ctrl_uids: Vec<Uid> = historing_ordered(&other.controllers_historing, other.i_controllers_historing)
+            .filter(|cuid| other.controllers.contains_key(cuid))
+            .collect();
+        let mut node_uids: Vec<Uid> = historing_ordered(&other.nodes_historing, other.i_nodes_historing)
+            .filter(|nuid| other.nodes.contains_key(nuid))
+            .collect();
+
+        let selected_ctrl_uids = match selection {
+            MergeSelection::All => None,
+            MergeSelection::Fraction(p) => {
+                let p = p.clamp(0.0, 1.0);
+                Some(ctrl_uids.iter().filter(|_| self.rng.gen_bool(p)).copied().collect::<HashSet<Uid>>())
+            },
+            MergeSelection::Controllers(cuids) => Some(cuids.iter().copied().collect::<HashSet<Uid>>())
+        };
+        if let Some(selected_ctrl_uids) = selected_ctrl_uids {
+            ctrl_uids.retain(|cuid| selected_ctrl_uids.contains(cuid));
+            // Chains the selected controllers can ever get to
+            let mut reachable: HashSet<Uid> = HashSet::new();
+            let mut queue: VecDeque<Uid> = VecDeque::new();
+            let mut ouids: Vec<Optuid> = Vec::new();
+            for cuid in &ctrl_uids {
+                controller_optuids(&other.controllers[cuid], &mut ouids);
+            }
+            queue.extend(ouids.into_iter().flatten());
+            while let Some(nuid) = queue.pop_front() {
+                if let Some(node) = other.nodes.get(&nuid) {
+                    if reachable.insert(nuid) {
+                        queue.extend(Optuid::ot_bits(node.b_next));
+                        queue.extend(Optuid::ot_bits(node.b_altnext));
+                    }
+                }
+            }
+            node_uids.retain(|nuid| reachable.contains(nuid));
+        }
+
+        let nodes_skipped = node_uids.len().saturating_sub(self.max_num_chains);
+        let controllers_skipped = ctrl_uids.len().saturating_sub(self.max_num_chains);
+        node_uids.drain(..nodes_skipped);
+        ctrl_uids.drain(..controllers_skipped);
+
+        // First allot new uids, then copy with links remapped, both forward and backward ones
+        let mut uids_map: HashMap<Uid, Uid> = HashMap::with_capacity(node_uids.len());
+        for ouid in &node_uids {
+            if let Some(nuid) = self.add_new_node(Content::Space) {
+                uids_map.insert(*ouid, nuid);
+            }
+        }
+        let remap = |ouid: Optuid| ouid.and_then(|uid| uids_map.get(&uid).copied());
+        for (ouid, nuid) in &uids_map {
+            let onode = &other.nodes[ouid];
+            if let Some(node) = self.nodes.get_mut(nuid) {
+                node.b_content = onode.b_content;
+                node.b_next = remap(Optuid::ot_bits(onode.b_next)).to_bits();
+                node.b_altnext = remap(Optuid::ot_bits(onode.b_altnext)).to_bits();
+            }
+        }
+
+        for cuid in &ctrl_uids {
+            let ctrl = controller_remapped(&other.controllers[cuid], &remap);
+            self.add_controller(ctrl);
+        }
+
+        MergeOutcome {
+            nodes_copied: node_uids.len(),
+            controllers_copied: ctrl_uids.len(),
+            nodes_skipped,
+            controllers_skipped
+        }
+    }
+
+}
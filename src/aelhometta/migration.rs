@@ -0,0 +1,399 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Format migrations. read_world() knows the current layout only; where a file of older format is laid out otherwise,
+// the section is read by Legacy, which keeps the fields the file has since lost and leaves those it lacks empty. Then,
+// from the file's format up to the current one, each step turns the state of the previous format into that of its own,
+// defaults included. Node contents are migrated separately, as they are read, so that nodes need not be kept.
+// New format: append its step here, and move the reading of the sections it changes into Legacy
+
+use num_traits::ToPrimitive;
+
+use std::{
+    collections::{
+        HashMap,
+        VecDeque
+    },
+    io::Read
+};
+
+use crate::serbin::ReadBin;
+
+use super::{
+    new_constructions_count,
+    serbin::{
+        read_constructions_count,
+        read_file,
+        Mappings,
+        Tasks,
+        FORMAT_VERSION
+    },
+    Command,
+    Construction,
+    FileFormat,
+    IntegersFileMapping,
+    Ælhometta
+};
+
+pub const OLDEST_FORMAT_VERSION: &str = "000016";
+
+pub(super) struct Legacy {
+    version: String, // of the file
+    pub(super) introspection: bool // "000017" to "00001A", replaced by commandswitch in "00001B"
+}
+
+struct Migration {
+    version: &'static str, // which the step migrates to, from the previous one
+    content: fn(u8) -> u8,
+    world: fn(&mut Ælhometta, &Legacy)
+}
+
+const MIGRATIONS: [Migration; 6] = [
+    // Commands keep their numbers in every step but "000019", hence content_unchanged()
+    Migration { version: "000017", content: content_unchanged, world: to_000017 },
+    Migration { version: "000018", content: content_unchanged, world: to_000018 },
+    Migration { version: "000019", content: content_to_000019, world: world_unchanged },
+    Migration { version: "00001A", content: content_unchanged, world: to_00001a },
+    Migration { version: "00001B", content: content_unchanged, world: to_00001b },
+    Migration { version: FORMAT_VERSION, content: content_unchanged, world: to_00001c } // "00001C"
+];
+
+// Sections as laid out before the current format, in the order read_world() comes to them
+
+impl Legacy {
+    pub(super) fn new(file_version: &str) -> Self {
+        Self {
+            version: file_version.to_string(),
+            introspection: true
+        }
+    }
+
+    fn since(&self, version: &str) -> bool {
+        since(&self.version, version)
+    }
+
+    pub(super) fn read_commandswitch<R: Read>(&mut self, r: &mut R) -> Result<u128, String> {
+        if self.since("00001B") {
+            return r.read_bin();
+        }
+        if self.since("000017") {
+            self.introspection = r.read_bin()?;
+        }
+        Ok(0)
+    }
+
+    // Spaces, main branches, alternative branches
+    pub(super) fn read_branches_counts<R: Read>(&self, r: &mut R) -> Result<(u128, u128, u128), String> {
+        if self.since("000018") {
+            Ok((r.read_bin()?, r.read_bin()?, r.read_bin()?))
+        } else {
+            Ok((0, 0, 0))
+        }
+    }
+
+    pub(super) fn read_constructions_count<R: Read>(&self, r: &mut R) -> Result<HashMap<Construction, u128>, String> {
+        if self.since("000018") {
+            read_constructions_count(r)
+        } else {
+            Ok(HashMap::new())
+        }
+    }
+
+    // Incoming connections permitted and attempted before the current session
+    pub(super) fn read_in_before_nums<R: Read>(&self, r: &mut R) -> Result<(u64, u64), String> {
+        if self.since("00001A") {
+            Ok((r.read_bin()?, r.read_bin()?))
+        } else {
+            Ok((0, 0))
+        }
+    }
+
+    // Output and input mappings, without format fields, and neither sensor nor generator ones
+    pub(super) fn read_mappings<R: Read>(&self, r: &mut R) -> Result<Mappings, String> {
+        let mut read_raw = || -> Result<Vec<IntegersFileMapping>, String> {
+            let l: usize = r.read_bin()?;
+            let mut mappings = Vec::with_capacity(l);
+            for _ in 0..l {
+                mappings.push(IntegersFileMapping {
+                    start: r.read_bin()?,
+                    length: r.read_bin()?,
+                    interval: r.read_bin()?,
+                    filepath: r.read_bin()?,
+                    ut_last_update: r.read_bin()?,
+                    format: FileFormat::Raw,
+                    format_param: 0
+                });
+            }
+            Ok(mappings)
+        };
+        let output_mappings = read_raw()?;
+        let input_mappings = read_raw()?;
+        Ok((output_mappings, input_mappings, Vec::new(), Vec::new()))
+    }
+
+    // Tasks, extra ticks, renewals: none, nothing to read
+    pub(super) fn tasks(&self) -> Tasks {
+        (Vec::new(), VecDeque::new(), HashMap::new())
+    }
+
+}
+
+// Versions are of the same length, in uppercase hexadecimal, so they compare as strings
+
+pub(super) fn since(file_version: &str, version: &str) -> bool {
+    file_version >= version
+}
+
+pub(super) fn loadable(file_version: &str) -> bool {
+    (file_version == OLDEST_FORMAT_VERSION) || MIGRATIONS.iter().any(|m| m.version == file_version)
+}
+
+fn pending(file_version: &str) -> impl Iterator<Item = &'static Migration> + '_ {
+    MIGRATIONS.iter().filter(move |m| m.version > file_version)
+}
+
+// Node content of file's format → current, for every byte
+
+pub(super) fn content_table(file_version: &str) -> [u8; 0x100] {
+    let mut table = [0u8; 0x100];
+    for (i, b) in table.iter_mut().enumerate() {
+        *b = pending(file_version).fold(i as u8, |b, m| (m.content)(b));
+    }
+    table
+}
+
+pub(super) fn migrate(æh: &mut Ælhometta, file_version: &str, legacy: &Legacy) {
+    for m in pending(file_version) {
+        (m.world)(æh, legacy);
+    }
+}
+
+fn content_unchanged(b: u8) -> u8 {
+    b
+}
+
+fn world_unchanged(_æh: &mut Ælhometta, _legacy: &Legacy) {}
+
+// Introspection switch appears, on by default
+
+fn to_000017(_æh: &mut Ælhometta, _legacy: &Legacy) {
+    // Nothing to turn: the switch is kept in Legacy only, which has it on unless the file says otherwise
+}
+
+// Counts of spaces and branches appear, starting from 0, and those of constructions
+
+fn to_000018(æh: &mut Ælhometta, _legacy: &Legacy) {
+    æh.spaces_count = 0;
+    æh.branches_main_count = 0;
+    æh.branches_alt_count = 0;
+    æh.constructions_count = new_constructions_count();
+}
+
+// Commands reordered alphabetically
+
+fn content_to_000019(b: u8) -> u8 {
+    match b {
+        18 => 19, // IntegerToIntegerIndex was 18, since "000019" is 19
+        19 => 18, // IntegerToIntegerChannel was 19, since "000019" is 18
+        61 => 62, // ShiftUp was 61, since "000019" is 62
+        62 => 61, // ShiftDown was 62, since "000019" is 61
+        _ => b
+    }
+}
+
+// Numbers of incoming connections permitted and attempted before the current session appear, starting from 0
+
+fn to_00001a(æh: &mut Ælhometta, _legacy: &Legacy) {
+    æh.in_permitted_before_num = 0;
+    æh.in_attempted_before_num = 0;
+}
+
+// Per-command switches replace introspection switch, which covered two commands
+
+fn to_00001b(æh: &mut Ælhometta, legacy: &Legacy) {
+    æh.commandswitch = u128::MAX;
+    if !legacy.introspection {
+        æh.commandswitch ^= 1 << Command::GetExecFromOptuid.to_u8().unwrap_or(0);
+        æh.commandswitch ^= 1 << Command::SetOptuidFromExec.to_u8().unwrap_or(0);
+    }
+}
+
+// Sensor and generator mappings, tasks, extra ticks, and renewals appear, none; input and output mappings get format,
+// all were raw; trailing checksum appears, which is verified on reading and not kept
+
+fn to_00001c(æh: &mut Ælhometta, _legacy: &Legacy) {
+    æh.sensor_mappings.clear();
+    æh.generator_mappings.clear();
+    æh.tasks.clear();
+    æh.bonus_ticks.clear();
+    æh.renewals.clear();
+    for ifm in æh.output_mappings.iter_mut().chain(æh.input_mappings.iter_mut()) {
+        ifm.format = FileFormat::Raw;
+        ifm.format_param = 0;
+    }
+}
+
+impl Ælhometta {
+    // File of any loadable format is rewritten in the current one, compressed if it was. Journal is not applied
+    pub fn upgrade(from_filepath: &str, to_filepath: &str) -> Result<String, String> {
+        let (æh, info) = read_file(from_filepath, None)?;
+        æh.loaded_check(from_filepath)?;
+        æh.save(to_filepath, info.compressed)?;
+        Ok(info.format_version)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(table: &[u8; 0x100]) -> bool {
+        table.iter().enumerate().all(|(i, &b)| b as usize == i)
+    }
+
+    #[test]
+    fn commands_reordered_to_000019() {
+        let table = content_table("000018");
+        for (old, command) in [
+            (18, Command::IntegerToIntegerIndex),
+            (19, Command::IntegerToIntegerChannel),
+            (61, Command::ShiftUp),
+            (62, Command::ShiftDown)
+        ] {
+            assert_eq!(table[old], command.to_u8().unwrap());
+        }
+        for (i, &b) in table.iter().enumerate() {
+            if ![18, 19, 61, 62].contains(&i) {
+                assert_eq!(b as usize, i);
+            }
+        }
+        assert!(identity(&content_table("000019")));
+    }
+
+    fn mapping(format: FileFormat, format_param: usize) -> IntegersFileMapping {
+        IntegersFileMapping {
+            start: 0x10,
+            length: 4,
+            interval: 1000,
+            filepath: String::from("in.bin"),
+            ut_last_update: -1,
+            format,
+            format_param
+        }
+    }
+
+    #[test]
+    fn steps_set_only_what_appeared() {
+        assert!(identity(&content_table("00001B")));
+        for (version, since_00001b) in [("00001B", true), ("000019", false)] {
+            let mut æh = Ælhometta::new(4);
+            let commandswitch = æh.commandswitch;
+            æh.constructions_count.clear();
+            æh.spaces_count = 7;
+            æh.in_permitted_before_num = 3;
+            æh.output_mappings.push(mapping(FileFormat::Wav, 8000));
+            æh.renewals.insert(0x22, 2);
+            migrate(&mut æh, version, &Legacy::new(version));
+            assert_eq!(æh.commandswitch, if since_00001b { commandswitch } else { u128::MAX });
+            assert!(æh.constructions_count.is_empty());
+            assert_eq!(æh.spaces_count, 7);
+            assert_eq!(æh.in_permitted_before_num, if since_00001b { 3 } else { 0 });
+            assert!((æh.output_mappings[0].format == FileFormat::Raw) && (æh.output_mappings[0].format_param == 0));
+            assert!(æh.renewals.is_empty());
+        }
+    }
+
+    #[test]
+    fn legacy_sections() {
+        use crate::serbin::WriteBin;
+
+        let mut data = Vec::<u8>::new();
+        data.write_bin(false).unwrap(); // introspection
+        data.write_bin(1usize).unwrap();
+        let ifm = mapping(FileFormat::Raw, 0);
+        data.write_bin(ifm.start).unwrap();
+        data.write_bin(ifm.length).unwrap();
+        data.write_bin(ifm.interval).unwrap();
+        data.write_bin(ifm.filepath.as_str()).unwrap();
+        data.write_bin(ifm.ut_last_update).unwrap();
+        data.write_bin(0usize).unwrap();
+
+        let mut legacy = Legacy::new("00001A");
+        let mut r = data.as_slice();
+        assert_eq!(legacy.read_commandswitch(&mut r).unwrap(), 0);
+        assert!(!legacy.introspection);
+        let mut r = &data[1..];
+        let legacy = Legacy::new("000017");
+        assert_eq!(legacy.read_branches_counts(&mut r).unwrap(), (0, 0, 0));
+        assert!(legacy.read_constructions_count(&mut r).unwrap().is_empty());
+        assert_eq!(legacy.read_in_before_nums(&mut r).unwrap(), (0, 0));
+        let (output_mappings, input_mappings, sensor_mappings, generator_mappings) = legacy.read_mappings(&mut r).unwrap();
+        assert_eq!(output_mappings.len(), 1);
+        assert_eq!((output_mappings[0].start, output_mappings[0].length, output_mappings[0].interval), (ifm.start, ifm.length, ifm.interval));
+        assert_eq!(output_mappings[0].filepath, ifm.filepath);
+        assert!(output_mappings[0].format == FileFormat::Raw);
+        assert!(input_mappings.is_empty() && sensor_mappings.is_empty() && generator_mappings.is_empty());
+        assert!(r.is_empty());
+
+        let mut data = Vec::<u8>::new();
+        data.write_bin(0x1234u128).unwrap();
+        let mut legacy = Legacy::new("00001B");
+        assert_eq!(legacy.read_commandswitch(&mut data.as_slice()).unwrap(), 0x1234);
+        assert!(legacy.introspection);
+    }
+
+    #[test]
+    fn chain_from_oldest_to_current() {
+        let versions = pending(OLDEST_FORMAT_VERSION).map(|m| m.version).collect::<Vec<&str>>();
+        assert_eq!(versions.len(), MIGRATIONS.len());
+        assert_eq!(versions.last(), Some(&FORMAT_VERSION));
+        let mut previous = OLDEST_FORMAT_VERSION;
+        for version in versions {
+            assert_eq!(version.len(), FORMAT_VERSION.len());
+            assert!(version > previous);
+            assert!(loadable(version));
+            previous = version;
+        }
+        assert!(loadable(OLDEST_FORMAT_VERSION));
+        assert!(!loadable("000015"));
+        assert_eq!(pending(FORMAT_VERSION).count(), 0);
+
+        for introspection in [false, true] {
+            let mut æh = Ælhometta::new(4);
+            æh.constructions_count.clear();
+            migrate(&mut æh, OLDEST_FORMAT_VERSION, &Legacy { version: OLDEST_FORMAT_VERSION.to_string(), introspection });
+            assert_eq!(æh.constructions_count, new_constructions_count());
+            let off = (1u128 << Command::GetExecFromOptuid.to_u8().unwrap()) | (1u128 << Command::SetOptuidFromExec.to_u8().unwrap());
+            assert_eq!(æh.commandswitch, if introspection { u128::MAX } else { u128::MAX ^ off });
+        }
+    }
+
+}
@@ -223,11 +223,16 @@ impl Ælhometta {
                     found = true;
                     self.other_peers.remove(i);
                     // Fix peer indices of all controllers
-                    for (_, ctrl) in self.controllers.iter_mut() {
+                    for (cuid, ctrl) in self.controllers.iter_mut() {
                         if ctrl.i_peer > 1 + i {
                             ctrl.i_peer -= 1;
                         } else if ctrl.i_peer == 1 + i {
                             ctrl.i_peer = 0; // "reset"
+                        } else {
+                            continue;
+                        }
+                        if let Some(ref mut jt) = self.journal {
+                            jt.controllers.insert(*cuid);
                         }
                     }
                     if let Some(ref mut efunguz) = self.efunguz { // may be None when this peer is not exposed
@@ -38,46 +38,81 @@ use rand::prelude::*;
 use std::{
     collections::{
         HashMap,
-        HashSet
+        HashSet,
+        VecDeque
+    },
+    fs::{
+        self,
+        File
     },
-    fs::File,
     io::{
+        BufRead,
         BufReader,
         BufWriter,
         Read,
         Write
+    },
+    path::Path,
+    sync::{
+        atomic::{
+            self,
+            AtomicUsize
+        },
+        Arc
+    },
+    thread::{
+        self,
+        JoinHandle
+    },
+    time::{
+        Duration,
+        Instant
     }
 };
 
 use crate::serbin::{
+    ChecksumReader,
+    ChecksumWriter,
+    LzReader,
+    LzWriter,
     ReadBin,
-    WriteBin
+    WriteBin,
+    LZ_SIGNATURE
 };
 
 use super::{
-    DEFAULT_ÆLHOMETTA_FILENAME,
+    inspect::Tally,
+    journal::journal_remove,
+    migration::{
+        self,
+        Legacy,
+        OLDEST_FORMAT_VERSION
+    },
     Command,
     Content,
+    Clock,
     Construction,
     Controller,
+    FileFormat,
     Flags,
+    GeneratorMapping,
     IntegersFileMapping,
     Node,
     OtherPeer,
     Registers,
+    Reward,
+    Sensor,
+    SensorMapping,
+    Task,
+    TaskFunction,
+    Uid,
+    Waveform,
     Ælhometta
 };
 
 const SIGNATURE: &str = "aelhometta";
-pub const FORMAT_VERSION: &str = "00001B";
-const LOADABLE_FORMATS: [&str; 6] = [
-    FORMAT_VERSION,
-    "00001A",
-    "000019",
-    "000018",
-    "000017",
-    "000016"
-];
+const BACKGROUND_SAVE_CHUNK_SIZE: usize = 0x100000;
+pub const FORMAT_VERSION: &str = "00001C";
 
 impl<W: Write> WriteBin<Command> for W {
     fn write_bin(&mut self, command: Command) -> Result<(), String> {
@@ -380,7 +415,9 @@ impl<W: Write> WriteBin<&IntegersFileMapping> for W {
         self.write_bin(ifm.length)?;
         self.write_bin(ifm.interval)?;
         self.write_bin(ifm.filepath.as_str())?;
-        self.write_bin(ifm.ut_last_update)?;    
+        self.write_bin(ifm.ut_last_update)?;
+        self.write_bin(ifm.format)?;
+        self.write_bin(ifm.format_param)?;
         Ok(())
     }
 }
@@ -392,420 +429,952 @@ impl<R: Read> ReadBin<IntegersFileMapping> for R {
         let interval = self.read_bin()?;
         let filepath: String = self.read_bin()?;
         let ut_last_update = self.read_bin()?;
+        let format = self.read_bin()?;
+        let format_param = self.read_bin()?;
 
         Ok(IntegersFileMapping {
             start,
             length,
             interval,
             filepath,
-            ut_last_update
+            ut_last_update,
+            format,
+            format_param
         })
     }
 }
 
-impl<W: Write> WriteBin<&Ælhometta> for W {
-    fn write_bin(&mut self, æh: &Ælhometta) -> Result<(), String> {
-        // Signature & format version
-        for b in SIGNATURE.as_bytes() {
-            self.write_bin(*b)?;
+impl<W: Write> WriteBin<FileFormat> for W {
+    fn write_bin(&mut self, format: FileFormat) -> Result<(), String> {
+        self.write_bin(format.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> ReadBin<FileFormat> for R {
+    fn read_bin(&mut self) -> Result<FileFormat, String> {
+        let vi: u8 = self.read_bin()?;
+        match FileFormat::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of FileFormat", vi))
         }
-        for b in FORMAT_VERSION.as_bytes() {
-            self.write_bin(*b)?;
+    }
+}
+
+impl<W: Write> WriteBin<Sensor> for W {
+    fn write_bin(&mut self, sensor: Sensor) -> Result<(), String> {
+        self.write_bin(sensor.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> ReadBin<Sensor> for R {
+    fn read_bin(&mut self) -> Result<Sensor, String> {
+        let vi: u8 = self.read_bin()?;
+        match Sensor::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of Sensor", vi))
         }
+    }
+}
 
-        // Serialisable part
-        self.write_bin(æh.max_num_chains_binlog)?;
+impl<W: Write> WriteBin<&SensorMapping> for W {
+    fn write_bin(&mut self, sm: &SensorMapping) -> Result<(), String> {
+        self.write_bin(sm.sensor)?;
+        self.write_bin(sm.start)?;
+        self.write_bin(sm.interval)?;
+        self.write_bin(sm.ut_last_update)?;
+        self.write_bin(sm.last_total)?;
+        Ok(())
+    }
+}
 
-        self.write_bin(æh.new_node_uid)?;
+impl<R: Read> ReadBin<SensorMapping> for R {
+    fn read_bin(&mut self) -> Result<SensorMapping, String> {
+        let sensor = self.read_bin()?;
+        let start = self.read_bin()?;
+        let interval = self.read_bin()?;
+        let ut_last_update = self.read_bin()?;
+        let last_total = self.read_bin()?;
 
-        self.write_bin(æh.nodes.len())?;
-        for (uid, node) in & æh.nodes {
-            self.write_bin(*uid)?;
-            self.write_bin(node)?;
+        Ok(SensorMapping {
+            sensor,
+            start,
+            interval,
+            ut_last_update,
+            last_total
+        })
+    }
+}
+
+impl<W: Write> WriteBin<Waveform> for W {
+    fn write_bin(&mut self, waveform: Waveform) -> Result<(), String> {
+        self.write_bin(waveform.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> ReadBin<Waveform> for R {
+    fn read_bin(&mut self) -> Result<Waveform, String> {
+        let vi: u8 = self.read_bin()?;
+        match Waveform::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of Waveform", vi))
         }
+    }
+}
 
-        self.write_bin(æh.nodes_historing.len())?;
-        for opt in & æh.nodes_historing {
-            self.write_bin(*opt)?;
+impl<W: Write> WriteBin<Clock> for W {
+    fn write_bin(&mut self, clock: Clock) -> Result<(), String> {
+        self.write_bin(clock.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
+
+impl<R: Read> ReadBin<Clock> for R {
+    fn read_bin(&mut self) -> Result<Clock, String> {
+        let vi: u8 = self.read_bin()?;
+        match Clock::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of Clock", vi))
         }
-        self.write_bin(æh.i_nodes_historing)?;
+    }
+}
 
-        self.write_bin(æh.new_controller_uid)?;
-        
-        self.write_bin(æh.controllers.len())?;
-        for (uid, ctrl) in & æh.controllers {
-            self.write_bin(*uid)?;
-            self.write_bin(ctrl)?;
+impl<W: Write> WriteBin<&GeneratorMapping> for W {
+    fn write_bin(&mut self, gm: &GeneratorMapping) -> Result<(), String> {
+        self.write_bin(gm.waveform)?;
+        self.write_bin(gm.start)?;
+        self.write_bin(gm.length)?;
+        self.write_bin(gm.clock)?;
+        self.write_bin(gm.period)?;
+        self.write_bin(gm.amplitude)?;
+        self.write_bin(gm.offset)?;
+        self.write_bin(gm.width)?;
+        self.write_bin(gm.values.len())?;
+        for v in & gm.values {
+            self.write_bin(*v)?;
         }
+        self.write_bin(gm.value)?;
+        self.write_bin(gm.last_step)?;
+        Ok(())
+    }
+}
 
-        self.write_bin(æh.controllers_historing.len())?;
-        for opt in & æh.controllers_historing {
-            self.write_bin(*opt)?;
+impl<R: Read> ReadBin<GeneratorMapping> for R {
+    fn read_bin(&mut self) -> Result<GeneratorMapping, String> {
+        let waveform = self.read_bin()?;
+        let start = self.read_bin()?;
+        let length = self.read_bin()?;
+        let clock = self.read_bin()?;
+        let period = self.read_bin()?;
+        let amplitude = self.read_bin()?;
+        let offset = self.read_bin()?;
+        let width = self.read_bin()?;
+        let l: usize = self.read_bin()?;
+        let mut values = Vec::with_capacity(l);
+        for _ in 0..l {
+            values.push(self.read_bin()?);
         }
-        self.write_bin(æh.i_controllers_historing)?;
+        let value = self.read_bin()?;
+        let last_step = self.read_bin()?;
 
-        self.write_bin(æh.commandswitch)?;
+        Ok(GeneratorMapping {
+            waveform,
+            start,
+            length,
+            clock,
+            period,
+            amplitude,
+            offset,
+            width,
+            values,
+            value,
+            last_step
+        })
+    }
+}
 
-        self.write_bin(æh.ether_optuids.len())?;
-        for opt in & æh.ether_optuids {
-            self.write_bin(*opt)?;
-        }
+impl<W: Write> WriteBin<TaskFunction> for W {
+    fn write_bin(&mut self, function: TaskFunction) -> Result<(), String> {
+        self.write_bin(function.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
 
-        self.write_bin(æh.ether_integers.len())?;
-        for opt in & æh.ether_integers {
-            self.write_bin(*opt)?;
+impl<R: Read> ReadBin<TaskFunction> for R {
+    fn read_bin(&mut self) -> Result<TaskFunction, String> {
+        let vi: u8 = self.read_bin()?;
+        match TaskFunction::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of TaskFunction", vi))
         }
+    }
+}
 
-        self.write_bin(æh.age)?;
-
-        self.write_bin(æh.spaces_count)?;
-        self.write_bin(æh.branches_main_count)?;
-        self.write_bin(æh.branches_alt_count)?;
+impl<W: Write> WriteBin<Reward> for W {
+    fn write_bin(&mut self, reward: Reward) -> Result<(), String> {
+        self.write_bin(reward.to_u8().unwrap_or(0))?;
+        Ok(())
+    }
+}
 
-        self.write_bin(æh.commands_count.len())?;
-        for (command, count) in & æh.commands_count {
-            self.write_bin(*command)?;
-            self.write_bin(*count)?;
+impl<R: Read> ReadBin<Reward> for R {
+    fn read_bin(&mut self) -> Result<Reward, String> {
+        let vi: u8 = self.read_bin()?;
+        match Reward::from_u8(vi) {
+            Some(x) => Ok(x),
+            _ => Err(format!("Unknown variant '{}' of Reward", vi))
         }
+    }
+}
 
-        self.write_bin(æh.constructions_count.len())?;
-        for (construction, count) in & æh.constructions_count {
-            self.write_bin(*construction)?;
-            self.write_bin(*count)?;
+impl<W: Write> WriteBin<&Task> for W {
+    fn write_bin(&mut self, task: &Task) -> Result<(), String> {
+        self.write_bin(task.function)?;
+        self.write_bin(task.output)?;
+        self.write_bin(task.inputs.len())?;
+        for chan in & task.inputs {
+            self.write_bin(*chan)?;
         }
+        self.write_bin(task.reward)?;
+        self.write_bin(task.amount)?;
+        self.write_bin(task.attempts_count)?;
+        self.write_bin(task.completions_count)?;
+        Ok(())
+    }
+}
 
-        self.write_bin(æh.glitch_background_prob)?;
-        self.write_bin(æh.glitch_background_count)?;
+impl<R: Read> ReadBin<Task> for R {
+    fn read_bin(&mut self) -> Result<Task, String> {
+        let function = self.read_bin()?;
+        let output = self.read_bin()?;
+        let l: usize = self.read_bin()?;
+        let mut inputs = Vec::with_capacity(l);
+        for _ in 0..l {
+            inputs.push(self.read_bin()?);
+        }
+        let reward = self.read_bin()?;
+        let amount = self.read_bin()?;
+        let attempts_count = self.read_bin()?;
+        let completions_count = self.read_bin()?;
+
+        Ok(Task {
+            function,
+            output,
+            inputs,
+            reward,
+            amount,
+            attempts_count,
+            completions_count
+        })
+    }
+}
 
-        self.write_bin(æh.glitch_replicate_prob)?;
-        self.write_bin(æh.glitch_replicate_count)?;
+impl<W: Write> WriteBin<&Ælhometta> for ChecksumWriter<W> {
+    fn write_bin(&mut self, æh: &Ælhometta) -> Result<(), String> {
+        write_world(self, æh, true)
+    }
+}
 
-        self.write_bin(æh.glitch_construct_prob)?;
-        self.write_bin(æh.glitch_construct_count)?;
+// Without bulk, nodes, controllers, their historings, and ether are written as empty,
+// which is what journal deltas carry besides their own records of changes to the bulk
 
-        self.write_bin(æh.share_size)?;
-        self.write_bin(æh.share_interval)?;
+// Small maps are written in order of keys, so that their order in memory does not change the file; nodes and controllers
+// are not, since sorting them would take memory proportional to the state
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    entries
+}
 
-        self.write_bin(æh.ut_last_share)?;
+pub(super) fn write_world<W: Write>(w: &mut ChecksumWriter<W>, æh: &Ælhometta, bulk: bool) -> Result<(), String> {
+    // Signature & format version
+    for b in SIGNATURE.as_bytes() {
+        w.write_bin(*b)?;
+    }
+    for b in FORMAT_VERSION.as_bytes() {
+        w.write_bin(*b)?;
+    }
 
-        self.write_bin(æh.secretkey.as_str())?;
-        self.write_bin(æh.port)?;
-        self.write_bin(æh.torproxy_port)?;
-        self.write_bin(æh.torproxy_host.as_str())?;
+    // Serialisable part
+    w.write_bin(æh.max_num_chains_binlog)?;
 
-        self.write_bin(æh.exposed)?;
+    w.write_bin(æh.new_node_uid)?;
 
-        self.write_bin(æh.other_peers.len())?;
-        for op in & æh.other_peers {
-            self.write_bin(op)?;
+    if bulk {
+        w.write_bin(æh.nodes.len())?;
+        for (uid, node) in & æh.nodes {
+            w.write_bin(*uid)?;
+            w.write_bin(node)?;
         }
 
-        self.write_bin(æh.whitelist.len())?;
-        for pk in & æh.whitelist {
-            self.write_bin(pk.as_str())?;
+        w.write_bin(æh.nodes_historing.len())?;
+        for opt in & æh.nodes_historing {
+            w.write_bin(*opt)?;
         }
+    } else {
+        w.write_bin(0usize)?;
+        w.write_bin(0usize)?;
+    }
+    w.write_bin(æh.i_nodes_historing)?;
 
-        // "before" + "now"
-        let mut n = æh.in_permitted_before_num;
-        if let Some(ref efunguz) = æh.efunguz {
-            n += efunguz.in_permitted_num();
+    w.write_bin(æh.new_controller_uid)?;
+    
+    if bulk {
+        w.write_bin(æh.controllers.len())?;
+        for (uid, ctrl) in & æh.controllers {
+            w.write_bin(*uid)?;
+            w.write_bin(ctrl)?;
         }
-        self.write_bin(n)?;
 
-        // "before" + "now"
-        let mut n = æh.in_attempted_before_num;
-        if let Some(ref efunguz) = æh.efunguz {
-            n += efunguz.in_attempted_num();
+        w.write_bin(æh.controllers_historing.len())?;
+        for opt in & æh.controllers_historing {
+            w.write_bin(*opt)?;
         }
-        self.write_bin(n)?;
+    } else {
+        w.write_bin(0usize)?;
+        w.write_bin(0usize)?;
+    }
+    w.write_bin(æh.i_controllers_historing)?;
 
-        self.write_bin(æh.output_mappings.len())?;
-        for ifm in & æh.output_mappings {
-            self.write_bin(ifm)?;
+    w.write_bin(æh.commandswitch)?;
+
+    if bulk {
+        w.write_bin(æh.ether_optuids.len())?;
+        for opt in & æh.ether_optuids {
+            w.write_bin(*opt)?;
         }
 
-        self.write_bin(æh.input_mappings.len())?;
-        for ifm in & æh.input_mappings {
-            self.write_bin(ifm)?;
+        w.write_bin(æh.ether_integers.len())?;
+        for opt in & æh.ether_integers {
+            w.write_bin(*opt)?;
         }
+    } else {
+        w.write_bin(0usize)?;
+        w.write_bin(0usize)?;
+    }
 
-        Ok(())
+    w.write_bin(æh.age)?;
+
+    w.write_bin(æh.spaces_count)?;
+    w.write_bin(æh.branches_main_count)?;
+    w.write_bin(æh.branches_alt_count)?;
+
+    w.write_bin(æh.commands_count.len())?;
+    for (command, count) in sorted(& æh.commands_count) {
+        w.write_bin(*command)?;
+        w.write_bin(*count)?;
+    }
+
+    w.write_bin(æh.constructions_count.len())?;
+    for (construction, count) in sorted(& æh.constructions_count) {
+        w.write_bin(*construction)?;
+        w.write_bin(*count)?;
     }
+
+    w.write_bin(æh.glitch_background_prob)?;
+    w.write_bin(æh.glitch_background_count)?;
+
+    w.write_bin(æh.glitch_replicate_prob)?;
+    w.write_bin(æh.glitch_replicate_count)?;
+
+    w.write_bin(æh.glitch_construct_prob)?;
+    w.write_bin(æh.glitch_construct_count)?;
+
+    w.write_bin(æh.share_size)?;
+    w.write_bin(æh.share_interval)?;
+
+    w.write_bin(æh.ut_last_share)?;
+
+    w.write_bin(æh.secretkey.as_str())?;
+    w.write_bin(æh.port)?;
+    w.write_bin(æh.torproxy_port)?;
+    w.write_bin(æh.torproxy_host.as_str())?;
+
+    w.write_bin(æh.exposed)?;
+
+    w.write_bin(æh.other_peers.len())?;
+    for op in & æh.other_peers {
+        w.write_bin(op)?;
+    }
+
+    w.write_bin(æh.whitelist.len())?;
+    let mut whitelist: Vec<&String> = æh.whitelist.iter().collect();
+    whitelist.sort_unstable();
+    for pk in whitelist {
+        w.write_bin(pk.as_str())?;
+    }
+
+    // "before" + "now"
+    let mut n = æh.in_permitted_before_num;
+    if let Some(ref efunguz) = æh.efunguz {
+        n += efunguz.in_permitted_num();
+    }
+    w.write_bin(n)?;
+
+    // "before" + "now"
+    let mut n = æh.in_attempted_before_num;
+    if let Some(ref efunguz) = æh.efunguz {
+        n += efunguz.in_attempted_num();
+    }
+    w.write_bin(n)?;
+
+    w.write_bin(æh.output_mappings.len())?;
+    for ifm in & æh.output_mappings {
+        w.write_bin(ifm)?;
+    }
+
+    w.write_bin(æh.input_mappings.len())?;
+    for ifm in & æh.input_mappings {
+        w.write_bin(ifm)?;
+    }
+
+    w.write_bin(æh.sensor_mappings.len())?;
+    for sm in & æh.sensor_mappings {
+        w.write_bin(sm)?;
+    }
+
+    w.write_bin(æh.generator_mappings.len())?;
+    for gm in & æh.generator_mappings {
+        w.write_bin(gm)?;
+    }
+
+    w.write_bin(æh.tasks.len())?;
+    for task in & æh.tasks {
+        w.write_bin(task)?;
+    }
+
+    w.write_bin(æh.bonus_ticks.len())?;
+    for cuid in & æh.bonus_ticks {
+        w.write_bin(*cuid)?;
+    }
+
+    w.write_bin(æh.renewals.len())?;
+    for (cuid, n) in sorted(& æh.renewals) {
+        w.write_bin(*cuid)?;
+        w.write_bin(*n)?;
+    }
+
+    // Checksum of all the above
+    let checksum = w.checksum();
+    w.write_all(& checksum.to_le_bytes()).map_err(|e| e.to_string())?;
+
+    Ok(())
 }
 
-impl<R: Read> ReadBin<Ælhometta> for R {
+impl<R: Read> ReadBin<Ælhometta> for ChecksumReader<R> {
     fn read_bin(&mut self) -> Result<Ælhometta, String> {
-        // Check signature & format version
-        let mut strbuf = [0u8; SIGNATURE.len()];
-        self.read_exact(&mut strbuf).map_err(|e| e.to_string())?;
-        let signature = String::from_utf8_lossy(&strbuf);
-        if signature != SIGNATURE {
-            return Err(format!("Wrong signature: expected '{}', found '{}'", SIGNATURE, signature));
-        }
-
-        let mut strbuf = [0u8; FORMAT_VERSION.len()];
-        self.read_exact(&mut strbuf).map_err(|e| e.to_string())?;
-        let file_format_version = String::from_utf8_lossy(&strbuf);
-        let format_loadable = LOADABLE_FORMATS.contains(& file_format_version.as_ref());
-        if !format_loadable {
-            return Err(format!("Format not loadable: '{}' (oldest loadable is '{}', current is '{}')", &file_format_version, LOADABLE_FORMATS[0], FORMAT_VERSION));
-        } 
-
-        // Serialisable part
-        let max_num_chains_binlog: u8 = self.read_bin()?;
-
-        let new_node_uid = self.read_bin()?;
-        let l: usize = self.read_bin()?;
-        let mut nodes = HashMap::with_capacity(l);
-        match file_format_version.as_ref() { // example of conversion between Command encoding in different formats
-            FORMAT_VERSION | "00001A" | "000019" => {
-                for _ in 0..l {
-                    let uid = self.read_bin()?;
-                    let node = self.read_bin()?;
-                    nodes.insert(uid, node);
-                }
-            },
-            _ => {
-                for _ in 0..l {
-                    let uid = self.read_bin()?;
-                    let mut node: Node = self.read_bin()?;
-                    node.b_content = match node.b_content {
-                        18 => 19, // IntegerToIntegerIndex was 18, since "000019" is 19
-                        19 => 18, // IntegerToIntegerChannel was 19, since "000019" is 18
-                        61 => 62, // ShiftUp was 61, since "000019" is 62
-                        62 => 61, // ShiftDown was 62, since "000019" is 61
-                        _ => node.b_content
-                    };
-                    nodes.insert(uid, node);
-                }
+        read_world(self, None).map(|(æh, _)| æh)
+    }
+}
+
+// With tally, nodes, controllers, historings, and ether are only counted as they are read, not kept, so the world comes out without them
+
+pub(super) fn read_constructions_count<R: Read>(r: &mut R) -> Result<HashMap<Construction, u128>, String> {
+    let l: usize = r.read_bin()?;
+    let mut constructions_count = HashMap::with_capacity(l);
+    for _ in 0..l {
+        let cons = r.read_bin()?;
+        let count = r.read_bin()?;
+        constructions_count.insert(cons, count);
+    }
+    Ok(constructions_count)
+}
+
+// Output, input, sensor, generator
+pub(super) type Mappings = (Vec<IntegersFileMapping>, Vec<IntegersFileMapping>, Vec<SensorMapping>, Vec<GeneratorMapping>);
+
+fn read_mappings<R: Read>(r: &mut R) -> Result<Mappings, String> {
+    let l: usize = r.read_bin()?;
+    let mut output_mappings = Vec::<IntegersFileMapping>::with_capacity(l);
+    for _ in 0..l {
+        output_mappings.push(r.read_bin()?);
+    }
+
+    let l: usize = r.read_bin()?;
+    let mut input_mappings = Vec::<IntegersFileMapping>::with_capacity(l);
+    for _ in 0..l {
+        input_mappings.push(r.read_bin()?);
+    }
+
+    let l: usize = r.read_bin()?;
+    let mut sensor_mappings = Vec::<SensorMapping>::with_capacity(l);
+    for _ in 0..l {
+        sensor_mappings.push(r.read_bin()?);
+    }
+
+    let l: usize = r.read_bin()?;
+    let mut generator_mappings = Vec::<GeneratorMapping>::with_capacity(l);
+    for _ in 0..l {
+        generator_mappings.push(r.read_bin()?);
+    }
+
+    Ok((output_mappings, input_mappings, sensor_mappings, generator_mappings))
+}
+
+// Tasks, extra ticks, renewals
+pub(super) type Tasks = (Vec<Task>, VecDeque<Uid>, HashMap<Uid, u32>);
+
+fn read_tasks<R: Read>(r: &mut R) -> Result<Tasks, String> {
+    let l: usize = r.read_bin()?;
+    let mut tasks = Vec::<Task>::with_capacity(l);
+    for _ in 0..l {
+        tasks.push(r.read_bin()?);
+    }
+
+    let l: usize = r.read_bin()?;
+    let mut bonus_ticks = VecDeque::with_capacity(l);
+    for _ in 0..l {
+        bonus_ticks.push_back(r.read_bin()?);
+    }
+
+    let l: usize = r.read_bin()?;
+    let mut renewals = HashMap::with_capacity(l);
+    for _ in 0..l {
+        let cuid = r.read_bin()?;
+        let n = r.read_bin()?;
+        renewals.insert(cuid, n);
+    }
+
+    Ok((tasks, bonus_ticks, renewals))
+}
+
+pub(super) fn read_world<R: Read>(r: &mut ChecksumReader<R>, mut tally: Option<&mut Tally>) -> Result<(Ælhometta, String), String> {
+    // Check signature & format version
+    r.begin_section("header");
+    let mut strbuf = [0u8; SIGNATURE.len()];
+    r.read_exact(&mut strbuf).map_err(|e| e.to_string())?;
+    let signature = String::from_utf8_lossy(&strbuf);
+    if signature != SIGNATURE {
+        return Err(format!("Wrong signature: expected '{}', found '{}'", SIGNATURE, signature));
+    }
+
+    let mut strbuf = [0u8; FORMAT_VERSION.len()];
+    r.read_exact(&mut strbuf).map_err(|e| e.to_string())?;
+    let file_format_version = String::from_utf8_lossy(&strbuf).to_string();
+    if !migration::loadable(&file_format_version) {
+        return Err(format!("Format not loadable: '{}' (oldest loadable is '{}', current is '{}')", &file_format_version, OLDEST_FORMAT_VERSION, FORMAT_VERSION));
+    }
+    let fv = file_format_version.as_str();
+    let current = fv == FORMAT_VERSION;
+    let mut legacy = Legacy::new(fv);
+
+    // Serialisable part
+    let max_num_chains_binlog: u8 = r.read_bin()?;
+
+    r.begin_section("nodes");
+    let new_node_uid = r.read_bin()?;
+    let l: usize = r.read_bin()?;
+    let mut nodes = HashMap::with_capacity(l);
+    let content_table = migration::content_table(fv);
+    for _ in 0..l {
+        let uid = r.read_bin()?;
+        let mut node: Node = r.read_bin()?;
+        node.b_content = content_table[node.b_content as usize];
+        match tally.as_deref_mut() {
+            Some(t) => t.node(&node),
+            None => {
+                nodes.insert(uid, node);
             }
         }
+    }
 
-        let l: usize = self.read_bin()?;
-        let mut nodes_historing = Vec::with_capacity(l);
-        for _ in 0..l {
-            nodes_historing.push(self.read_bin()?);
+    let l: usize = r.read_bin()?;
+    let mut nodes_historing = Vec::with_capacity(l);
+    for _ in 0..l {
+        let opt = r.read_bin()?;
+        match tally.as_deref_mut() {
+            Some(t) => t.node_historing(opt),
+            None => nodes_historing.push(opt)
         }
-        let i_nodes_historing = self.read_bin()?;
-
-        let new_controller_uid = self.read_bin()?;
-        let l: usize = self.read_bin()?;
-        let mut controllers = HashMap::with_capacity(l);
-        for _ in 0..l {
-            let uid = self.read_bin()?;
-            let ctrl = self.read_bin()?;
-            controllers.insert(uid, ctrl);
+    }
+    let i_nodes_historing = r.read_bin()?;
+
+    r.begin_section("controllers");
+    let new_controller_uid = r.read_bin()?;
+    let l: usize = r.read_bin()?;
+    let mut controllers = HashMap::with_capacity(l);
+    for _ in 0..l {
+        let uid = r.read_bin()?;
+        let ctrl = r.read_bin()?;
+        match tally.as_deref_mut() {
+            Some(t) => t.controller(&ctrl),
+            None => {
+                controllers.insert(uid, ctrl);
+            }
         }
-        let l: usize = self.read_bin()?;
-        let mut controllers_historing = Vec::with_capacity(l);
-        for _ in 0..l {
-            controllers_historing.push(self.read_bin()?);
+    }
+    let l: usize = r.read_bin()?;
+    let mut controllers_historing = Vec::with_capacity(l);
+    for _ in 0..l {
+        let opt = r.read_bin()?;
+        match tally.as_deref_mut() {
+            Some(t) => t.controller_historing(opt),
+            None => controllers_historing.push(opt)
         }
-        let i_controllers_historing = self.read_bin()?;
-
-        let mut commandswitch = u128::MAX;
-        match file_format_version.as_ref() {
-            FORMAT_VERSION => {
-                commandswitch = self.read_bin()?;
-            },
-            "00001A" | "000019" | "000018" | "000017" => {
-                let introspection: bool = self.read_bin()?;
-                if !introspection {
-                    commandswitch ^= 1 << Command::GetExecFromOptuid.to_u8().unwrap_or(0);
-                    commandswitch ^= 1 << Command::SetOptuidFromExec.to_u8().unwrap_or(0);
-                }
-            },
-            _ => {}
-        };
-
-        let l: usize = self.read_bin()?;
-        let mut ether_optuids = Vec::with_capacity(l);
-        for _ in 0..l {
-            ether_optuids.push(self.read_bin()?);
+    }
+    let i_controllers_historing = r.read_bin()?;
+
+    r.begin_section("commandswitch");
+    let commandswitch = if current { r.read_bin()? } else { legacy.read_commandswitch(r)? };
+
+    r.begin_section("ether");
+    let l: usize = r.read_bin()?;
+    let mut ether_optuids = Vec::with_capacity(l);
+    for _ in 0..l {
+        let opt = r.read_bin()?;
+        match tally.as_deref_mut() {
+            Some(t) => t.ether_optuid(opt),
+            None => ether_optuids.push(opt)
         }
+    }
 
-        let l: usize = self.read_bin()?;
-        let mut ether_integers = Vec::with_capacity(l);
-        for _ in 0..l {
-            ether_integers.push(self.read_bin()?);
+    let l: usize = r.read_bin()?;
+    let mut ether_integers = Vec::with_capacity(l);
+    for _ in 0..l {
+        let integer = r.read_bin()?;
+        match tally.as_deref_mut() {
+            Some(t) => t.ether_integer(integer),
+            None => ether_integers.push(integer)
         }
+    }
 
-        let age = self.read_bin()?;
+    r.begin_section("counts");
+    let age = r.read_bin()?;
+
+    let (spaces_count, branches_main_count, branches_alt_count) = if current {
+        (r.read_bin()?, r.read_bin()?, r.read_bin()?)
+    } else {
+        legacy.read_branches_counts(r)?
+    };
+
+    let l: usize = r.read_bin()?;
+    let mut commands_count = HashMap::with_capacity(l);
+    for _ in 0..l {
+        let command = r.read_bin()?;
+        let count = r.read_bin()?;
+        commands_count.insert(command, count);
+    }
 
-        let spaces_count = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" | "000019" | "000018" => self.read_bin()?,
-            _ => 0
-        };
+    let constructions_count = if current { read_constructions_count(r)? } else { legacy.read_constructions_count(r)? };
 
-        let branches_main_count = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" | "000019" | "000018" => self.read_bin()?,
-            _ => 0
-        };
+    r.begin_section("glitches");
+    let glitch_background_prob = r.read_bin()?;
+    let glitch_background_count = r.read_bin()?;
 
-        let branches_alt_count = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" | "000019" | "000018" => self.read_bin()?,
-            _ => 0
-        };
-
-        let l: usize = self.read_bin()?;
-        let mut commands_count = HashMap::with_capacity(l);
-        for _ in 0..l {
-            let command = self.read_bin()?;
-            let count = self.read_bin()?;
-            commands_count.insert(command, count);
-        }
-
-        let constructions_count = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" | "000019" | "000018" => {
-                let l: usize = self.read_bin()?;
-                let mut cons_count = HashMap::with_capacity(l);
-                for _ in 0..l {
-                    let cons = self.read_bin()?;
-                    let count = self.read_bin()?;
-                    cons_count.insert(cons, count);
-                }
-                cons_count
-            },
-            _ => super::new_constructions_count()
-        };
+    let glitch_replicate_prob = r.read_bin()?;
+    let glitch_replicate_count = r.read_bin()?;
 
-        let glitch_background_prob = self.read_bin()?;
-        let glitch_background_count = self.read_bin()?;
+    let glitch_construct_prob = r.read_bin()?;
+    let glitch_construct_count = r.read_bin()?;
+    
+    r.begin_section("peer");
+    let share_size = r.read_bin()?;
+    let share_interval = r.read_bin()?;
 
-        let glitch_replicate_prob = self.read_bin()?;
-        let glitch_replicate_count = self.read_bin()?;
+    let ut_last_share = r.read_bin()?;
 
-        let glitch_construct_prob = self.read_bin()?;
-        let glitch_construct_count = self.read_bin()?;
-        
-        let share_size = self.read_bin()?;
-        let share_interval = self.read_bin()?;
+    let secretkey: String = r.read_bin()?;
+    let port = r.read_bin()?;
+    let torproxy_port = r.read_bin()?;
+    let torproxy_host: String = r.read_bin()?;
 
-        let ut_last_share = self.read_bin()?;
+    let exposed = r.read_bin()?;
 
-        let secretkey: String = self.read_bin()?;
-        let port = self.read_bin()?;
-        let torproxy_port = self.read_bin()?;
-        let torproxy_host: String = self.read_bin()?;
+    let l: usize = r.read_bin()?;
+    let mut other_peers = Vec::<OtherPeer>::with_capacity(l);
+    for _ in 0..l {
+        other_peers.push(r.read_bin()?);
+    }
 
-        let exposed = self.read_bin()?;
+    let l: usize = r.read_bin()?;
+    let mut whitelist = HashSet::new();
+    for _ in 0..l {
+        whitelist.insert(r.read_bin()?);
+    }
 
-        let l: usize = self.read_bin()?;
-        let mut other_peers = Vec::<OtherPeer>::with_capacity(l);
-        for _ in 0..l {
-            other_peers.push(self.read_bin()?);
+    let (in_permitted_before_num, in_attempted_before_num) = if current {
+        (r.read_bin()?, r.read_bin()?)
+    } else {
+        legacy.read_in_before_nums(r)?
+    };
+
+    r.begin_section("mappings");
+    let (output_mappings, input_mappings, sensor_mappings, generator_mappings) = if current {
+        read_mappings(r)?
+    } else {
+        legacy.read_mappings(r)?
+    };
+
+    r.begin_section("tasks");
+    let (tasks, bonus_ticks, renewals) = if current { read_tasks(r)? } else { legacy.tasks() };
+
+    r.begin_section("checksum");
+    if current { // older formats have no checksum
+        let checksum = r.checksum();
+        let mut buf = [0u8; 8];
+        r.read_exact(&mut buf).map_err(|e| e.to_string())?;
+        let stored_checksum = u64::from_le_bytes(buf);
+        if stored_checksum != checksum {
+            return Err(format!("Checksum mismatch: stored {:016X}, computed {:016X}", stored_checksum, checksum));
         }
+    }
 
-        let l: usize = self.read_bin()?;
-        let mut whitelist = HashSet::new();
-        for _ in 0..l {
-            whitelist.insert(self.read_bin()?);
-        }
+    // Non-serialisable part
+
+    let max_num_chains: usize = 1 <<  max_num_chains_binlog;
+    let max_num_chains_binmask: usize = max_num_chains - 1;
+    let rng = StdRng::from_entropy();
+    let efunguz = None;
+    let io_samples = VecDeque::new();
+    let journal = None;
+
+    let mut æh = Ælhometta {
+        max_num_chains_binlog,
+        new_node_uid,
+        nodes,
+        nodes_historing,
+        i_nodes_historing,
+        new_controller_uid,
+        controllers,
+        controllers_historing,
+        i_controllers_historing,
+        commandswitch,
+        ether_optuids,
+        ether_integers,
+        age,
+        spaces_count,
+        branches_main_count,
+        branches_alt_count,
+        commands_count,
+        constructions_count,
+        glitch_background_prob,
+        glitch_background_count,
+        glitch_replicate_prob,
+        glitch_replicate_count,
+        glitch_construct_prob,
+        glitch_construct_count,
+        share_size,
+        share_interval,
+        ut_last_share,
+        secretkey,
+        port,
+        torproxy_port,
+        torproxy_host,
+        exposed,
+        other_peers,
+        whitelist,
+        in_permitted_before_num,
+        in_attempted_before_num,
+        output_mappings,
+        input_mappings,
+        sensor_mappings,
+        generator_mappings,
+        tasks,
+        bonus_ticks,
+        renewals,
+
+        max_num_chains,
+        max_num_chains_binmask,
+        rng,
+        efunguz,
+        io_samples,
+        journal
+    };
+
+    migration::migrate(&mut æh, fv, &legacy);
+
+    Ok((æh, file_format_version))
+}
 
-        let in_permitted_before_num = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" => self.read_bin()?,
-            _ => 0
-        };
+pub(super) struct FileInfo {
+    pub(super) format_version: String,
+    pub(super) compressed: bool,
+    pub(super) checksum: u64 // of payload, meaningful only since "00001C"
+}
 
-        let in_attempted_before_num = match file_format_version.as_ref() {
-            FORMAT_VERSION | "00001A" => self.read_bin()?,
-            _ => 0
-        };
+pub struct BackgroundSave {
+    filepaths: Vec<String>,
+    written: Arc<AtomicUsize>, // bytes, over all filepaths
+    total: usize,
+    payload_size: usize, // held in memory until the thread finishes
+    pause: Duration, // of the caller, serialising
+    handle: JoinHandle<Result<(), String>>
+}
 
-        let l: usize = self.read_bin()?;
-        let mut output_mappings = Vec::<IntegersFileMapping>::with_capacity(l);
-        for _ in 0..l {
-            output_mappings.push(self.read_bin()?);
-        }
+// Plain or compressed, the payload goes through ChecksumWriter/ChecksumReader, so offsets in errors are those of plain format.
+// Final checksum identifies the base which journal deltas are applied to
 
-        let l: usize = self.read_bin()?;
-        let mut input_mappings = Vec::<IntegersFileMapping>::with_capacity(l);
-        for _ in 0..l {
-            input_mappings.push(self.read_bin()?);
-        }
-
-        // Non-serialisable part
-
-        let max_num_chains: usize = 1 <<  max_num_chains_binlog;
-        let max_num_chains_binmask: usize = max_num_chains - 1;
-        let rng = thread_rng();
-        let efunguz = None;
-
-        let mut æh = Ælhometta {
-            max_num_chains_binlog,
-            new_node_uid,
-            nodes,
-            nodes_historing,
-            i_nodes_historing,
-            new_controller_uid,
-            controllers,
-            controllers_historing,
-            i_controllers_historing,
-            commandswitch,
-            ether_optuids,
-            ether_integers,
-            age,
-            spaces_count,
-            branches_main_count,
-            branches_alt_count,
-            commands_count,
-            constructions_count,
-            glitch_background_prob,
-            glitch_background_count,
-            glitch_replicate_prob,
-            glitch_replicate_count,
-            glitch_construct_prob,
-            glitch_construct_count,
-            share_size,
-            share_interval,
-            ut_last_share,
-            secretkey,
-            port,
-            torproxy_port,
-            torproxy_host,
-            exposed,
-            other_peers,
-            whitelist,
-            in_permitted_before_num,
-            in_attempted_before_num,
-            output_mappings,
-            input_mappings,
-
-            max_num_chains,
-            max_num_chains_binmask,
-            rng,
-            efunguz
-        };
+fn write_payload<W: Write>(æh: &Ælhometta, writer: W, filepath: &str) -> Result<(W, u64), String> {
+    let mut writer = ChecksumWriter::new(writer);
+    writer.write_bin(æh).map_err(|err| format!("Cannot write to '{}': {}", filepath, &err))?;
+    let checksum = writer.checksum();
+    Ok((writer.into_inner(), checksum))
+}
 
-        if exposed {
-            æh.peer_expose()?;
+fn read_payload<R: Read>(reader: R, filepath: &str, tally: Option<&mut Tally>) -> Result<(Ælhometta, String, u64), String> {
+    let mut reader = ChecksumReader::new(reader);
+    let (æh, format_version) = read_world(&mut reader, tally).map_err(|err| if reader.section().is_empty() {
+        format!("Cannot read from '{}': {}", filepath, &err)
+    } else {
+        format!("Cannot read from '{}': Corrupt file: section '{}', offset {}: {}", filepath, reader.section(), reader.offset(), &err)
+    })?;
+    Ok((æh, format_version, reader.checksum()))
+}
+
+// Compressed or not is detected by the beginning of file
+
+pub(super) fn read_file(filepath: &str, tally: Option<&mut Tally>) -> Result<(Ælhometta, FileInfo), String> {
+    let mut reader = BufReader::new(File::open(filepath).map_err(|err| format!("Cannot open '{}': {}", filepath, &err))?);
+    let compressed = reader.fill_buf().map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))?.starts_with(LZ_SIGNATURE);
+    let (æh, format_version, checksum) = if compressed {
+        reader.consume(LZ_SIGNATURE.len());
+        read_payload(LzReader::new(reader), filepath, tally)?
+    } else {
+        read_payload(reader, filepath, tally)?
+    };
+    Ok((æh, FileInfo {
+        format_version,
+        compressed,
+        checksum
+    }))
+}
+
+// Written to temporary file first, which then replaces the target, so that the previous state survives interrupted save.
+// write() gets plain or compressing writer and the temporary filepath, for errors
+
+fn save_file<F: FnOnce(&mut dyn Write, &str) -> Result<u64, String>>(filepath: &str, compress: bool, write: F) -> Result<u64, String> {
+    let tmp_filepath = format!("{}.tmp", filepath);
+    let mut writer = BufWriter::new(File::create(&tmp_filepath).map_err(|err| format!("Cannot create '{}': {}", &tmp_filepath, &err))?);
+    let checksum = if compress {
+        let mut lz_writer = LzWriter::new(&mut writer).map_err(|err| format!("Cannot write to '{}': {}", &tmp_filepath, &err))?;
+        let checksum = write(&mut lz_writer, &tmp_filepath)?;
+        lz_writer.finish().map_err(|err| format!("Cannot write to '{}': {}", &tmp_filepath, &err))?;
+        checksum
+    } else {
+        write(&mut writer, &tmp_filepath)?
+    };
+    let file = writer.into_inner().map_err(|err| format!("Cannot flush: {}", &err))?;
+    file.sync_all().map_err(|err| format!("Cannot sync '{}': {}", &tmp_filepath, &err))?;
+    drop(file);
+    fs::rename(&tmp_filepath, filepath).map_err(|err| format!("Cannot rename '{}' to '{}': {}", &tmp_filepath, filepath, &err))?;
+    // Make the rename itself durable, where directories can be synced
+    let dirpath = match Path::new(filepath).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new(".")
+    };
+    if let Ok(dir) = File::open(dirpath) {
+        dir.sync_all().unwrap_or(());
+    }
+    Ok(checksum)
+}
+
+impl BackgroundSave {
+    pub fn filepaths(&self) -> &[String] {
+        & self.filepaths
+    }
+
+    // Fraction of bytes written, before compression if any
+    pub fn progress(&self) -> f64 {
+        if self.total > 0 {
+            (self.written.load(atomic::Ordering::Relaxed) as f64) / (self.total as f64)
+        } else {
+            1.0
         }
+    }
 
-        Ok(æh)
+    pub fn payload_size(&self) -> usize {
+        self.payload_size
+    }
+
+    pub fn pause(&self) -> Duration {
+        self.pause
     }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    // Blocks until finished
+    pub fn join(self) -> Result<(), String> {
+        self.handle.join().map_err(|_| String::from("Saving thread panicked"))?
+    }
+
 }
 
 impl Ælhometta {
-    pub fn save(&self, filepath: &str) -> Result<(), String> {
+    // Returns checksum of the written state, cf. write_payload()
+    pub(super) fn save_base(&self, filepath: &str, compress: bool) -> Result<u64, String> {
         // "Binary serialization", cf. load()
-        let mut writer = BufWriter::new(File::create(filepath).map_err(|err| format!("Cannot create '{}': {}", filepath, &err))?);
-        writer.write_bin(self).map_err(|err| format!("Cannot write to '{}': {}", filepath, &err))?;
-        writer.flush().map_err(|err| format!("Cannot flush: {}", &err))?;
-        Ok(())
+        save_file(filepath, compress, |writer, tmp_filepath| {
+            write_payload(self, writer, tmp_filepath).map(|(_, checksum)| checksum)
+        })
+    }
+
+    // Same as save() to each of filepaths, but only serialisation into memory happens here, at once, so that the state saved is
+    // consistent; compression and writing, which take much longer, are left to another thread, and ticking may go on meanwhile.
+    // The price: the caller still pauses to serialise, and the whole payload is held in memory besides the state until written
+    pub fn save_background(&self, filepaths: &[String], compress: bool) -> Result<BackgroundSave, String> {
+        let t_start = Instant::now();
+        let (payload, _) = write_payload(self, Vec::new(), filepaths.first().map_or("", |filepath| filepath.as_str()))?;
+        let pause = t_start.elapsed();
+        let payload_size = payload.len();
+        let total = payload_size * filepaths.len();
+        let written = Arc::new(AtomicUsize::new(0));
+        let thread_filepaths = filepaths.to_vec();
+        let thread_written = Arc::clone(&written);
+        let handle = thread::Builder::new().name(String::from("save")).spawn(move || {
+            for filepath in & thread_filepaths {
+                save_file(filepath, compress, |writer, tmp_filepath| {
+                    for chunk in payload.chunks(BACKGROUND_SAVE_CHUNK_SIZE) {
+                        writer.write_all(chunk).map_err(|err| format!("Cannot write to '{}': {}", tmp_filepath, &err))?;
+                        thread_written.fetch_add(chunk.len(), atomic::Ordering::Relaxed);
+                    }
+                    Ok(0)
+                })?;
+                journal_remove(filepath)?;
+            }
+            Ok(())
+        }).map_err(|err| format!("Cannot start saving thread: {}", &err))?;
+        Ok(BackgroundSave {
+            filepaths: filepaths.to_vec(),
+            written,
+            total,
+            payload_size,
+            pause,
+            handle
+        })
     }
 
-    pub fn save_default(&self) -> Result<(), String> {
-        self.save(DEFAULT_ÆLHOMETTA_FILENAME)
+    pub fn save(&self, filepath: &str, compress: bool) -> Result<(), String> {
+        self.save_base(filepath, compress)?;
+        // Deltas, if any, refer to the replaced state
+        journal_remove(filepath)
     }
 
     pub fn load(filepath: &str) -> Result<Self, String> {
         // "Binary deserialization", cf. save()
-        let mut reader = BufReader::new(File::open(filepath).map_err(|err| format!("Cannot open '{}': {}", filepath, &err))?);
-        reader.read_bin().map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))
+        let (mut æh, info) = read_file(filepath, None)?;
+        æh.journal_replay(filepath, info.checksum)?;
+        æh.loaded_check(filepath)?;
+        if æh.exposed {
+            æh.peer_expose().map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))?;
+        }
+        Ok(æh)
+    }
+
+    // Journal applied, peer not exposed: another state to take from, as by merge, or this one run offline
+    pub fn load_unexposed(filepath: &str) -> Result<Self, String> {
+        let (mut æh, info) = read_file(filepath, None)?;
+        æh.journal_replay(filepath, info.checksum)?;
+        æh.loaded_check(filepath)?;
+        Ok(æh)
     }
 
-    pub fn load_default() -> Result<Self, String> {
-        Self::load(DEFAULT_ÆLHOMETTA_FILENAME)
+    // Same checks as import does, once the ether has its final size, so that tasks and mappings never index past it
+    pub(super) fn loaded_check(&self, filepath: &str) -> Result<(), String> {
+        self.iomap_check().and_then(|_| self.tasks_check()).map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))
     }
 
 }
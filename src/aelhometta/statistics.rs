@@ -28,26 +28,99 @@
  * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use serde::Serialize;
+
 use std::collections::HashMap;
 
 use crate::serbin::OtBits;
 
 use super::{
+    iomap::io_sampled_channels,
     Content,
+    Integer,
     Ælhometta
 };
 
+#[derive(Serialize)]
 pub struct ControllerGenerationStatistics {
     pub minimum: u128,
     pub average: u128,
     pub maximum: u128
 }
 
+#[derive(Serialize)]
 pub struct ChannelsStatistics {
     pub optuids_some: usize,
     pub integers_nonzero: usize
 }
 
+#[derive(Serialize)]
+pub struct IoPairStatistics {
+    pub input: usize, // channel
+    pub output: usize, // channel
+    pub correlation: f64, // Pearson, 0 if either channel is constant
+    pub mutual_information: f64 // bits
+}
+
+// Equal-width bins between minimum and maximum of the series
+
+fn bin_indices(series: &[Integer], bins: usize) -> Vec<usize> {
+    let minimum = series.iter().copied().min().unwrap_or(0) as f64;
+    let maximum = series.iter().copied().max().unwrap_or(0) as f64;
+    let width = (maximum - minimum) / (bins as f64);
+    series.iter().map(|&v| {
+        if width > 0.0 {
+            (((v as f64 - minimum) / width) as usize).min(bins - 1)
+        } else {
+            0
+        }
+    }).collect()
+}
+
+fn correlation(xs: &[Integer], ys: &[Integer]) -> f64 {
+    let n = xs.len() as f64;
+    let mx = xs.iter().map(|&x| x as f64).sum::<f64>() / n;
+    let my = ys.iter().map(|&y| y as f64).sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut vx = 0.0;
+    let mut vy = 0.0;
+    for (&x, &y) in xs.iter().zip(ys.iter()) {
+        let dx = x as f64 - mx;
+        let dy = y as f64 - my;
+        cov += dx * dy;
+        vx += dx * dx;
+        vy += dy * dy;
+    }
+    if (vx > 0.0) && (vy > 0.0) {
+        cov / (vx * vy).sqrt()
+    } else {
+        0.0
+    }
+}
+
+fn mutual_information(xbins: &[usize], ybins: &[usize], bins: usize) -> f64 {
+    let n = xbins.len() as f64;
+    let mut joint = vec![0usize; bins * bins];
+    let mut px = vec![0usize; bins];
+    let mut py = vec![0usize; bins];
+    for (&bx, &by) in xbins.iter().zip(ybins.iter()) {
+        joint[bx * bins + by] += 1;
+        px[bx] += 1;
+        py[by] += 1;
+    }
+    let mut mi = 0.0;
+    for bx in 0..bins {
+        for by in 0..bins {
+            let c = joint[bx * bins + by];
+            if c > 0 {
+                let pxy = (c as f64) / n;
+                mi += pxy * (pxy * n * n / ((px[bx] * py[by]) as f64)).log2();
+            }
+        }
+    }
+    mi.max(0.0)
+}
+
 impl Ælhometta {
     pub fn content_statistics(&self) -> HashMap<Content, usize> {
         let mut hm = HashMap::new();
@@ -93,4 +166,41 @@ impl Ælhometta {
         }
     }
 
+    pub fn io_samples_num(&self) -> usize {
+        self.io_samples.len()
+    }
+
+    pub fn io_statistics(&self, bins: usize) -> Vec<IoPairStatistics> {
+        let mut stats = Vec::new();
+        if (bins < 2) || (self.io_samples.len() < 2) {
+            return stats;
+        }
+
+        let in_chans = io_sampled_channels(& self.input_mappings);
+        let out_chans = io_sampled_channels(& self.output_mappings);
+
+        let in_series: Vec<Vec<Integer>> = (0..in_chans.len()).map(|i|
+            self.io_samples.iter().map(|(ins, _)| ins[i]).collect()
+        ).collect();
+        let out_series: Vec<Vec<Integer>> = (0..out_chans.len()).map(|j|
+            self.io_samples.iter().map(|(_, outs)| outs[j]).collect()
+        ).collect();
+
+        let in_bins: Vec<Vec<usize>> = in_series.iter().map(|s| bin_indices(s, bins)).collect();
+        let out_bins: Vec<Vec<usize>> = out_series.iter().map(|s| bin_indices(s, bins)).collect();
+
+        for (i, &input) in in_chans.iter().enumerate() {
+            for (j, &output) in out_chans.iter().enumerate() {
+                stats.push(IoPairStatistics {
+                    input,
+                    output,
+                    correlation: correlation(& in_series[i], & out_series[j]),
+                    mutual_information: mutual_information(& in_bins[i], & out_bins[j], bins)
+                });
+            }
+        }
+
+        stats
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use super::{
+    Integer,
+    Reward,
+    Task,
+    TaskFunction,
+    Uid,
+    Ælhometta,
+    MAX_BONUS_TICKS
+};
+
+// The checks are the same when a task is added and when it comes from a file
+
+impl Task {
+    pub(super) fn check(&self, num_channels: usize) -> Result<(), String> {
+        if self.inputs.len() == self.function.arity() {
+            if let Some(chan) = self.inputs.iter().chain([self.output].iter()).find(|&&chan| chan >= num_channels) {
+                Err(format!("Channel {} is out of bounds, there are only {} integer channels", chan, num_channels))
+            } else if self.amount > 0 {
+                Ok(())
+            } else {
+                Err(String::from("Reward amount must be greater than 0"))
+            }
+        } else {
+            Err(format!("Function '{}' requires {} input channel(s), {} given", self.function.name(), self.function.arity(), self.inputs.len()))
+        }
+    }
+
+}
+
+impl Ælhometta {
+    // Of tasks and their rewards read from a file, which may have been edited by hand
+    pub(super) fn tasks_check(&self) -> Result<(), String> {
+        for (i, task) in self.tasks.iter().enumerate() {
+            task.check(self.ether_integers.len()).map_err(|err| format!("Task {}: {}", i, &err))?;
+        }
+        if let Some(cuid) = self.renewals.iter().find_map(|(cuid, &n)| if n == 0 { Some(cuid) } else { None }) {
+            return Err(format!("Renewal count of controller {:X} must be greater than 0", cuid));
+        }
+        Ok(())
+    }
+
+    pub fn task_add(&mut self, function: TaskFunction, output: usize, inputs: Vec<usize>, reward: Reward, amount: u32) -> Result<(), String> {
+        let task = Task::new(function, output, inputs, reward, amount);
+        task.check(self.ether_integers.len())?;
+        self.tasks.push(task);
+        Ok(())
+    }
+
+    pub fn task_del(&mut self, index: usize) -> Result<(), String> {
+        if index < self.tasks.len() {
+            self.tasks.remove(index);
+            Ok(())
+        } else {
+            Err(format!("There are only {} tasks", self.tasks.len()))
+        }
+    }
+
+    pub fn task_reset_counts(&mut self) {
+        for task in &mut self.tasks {
+            task.attempts_count = 0;
+            task.completions_count = 0;
+        }
+    }
+
+    pub fn tasks(&self) -> & Vec<Task> {
+        & self.tasks
+    }
+
+    pub fn bonus_ticks_num(&self) -> usize {
+        self.bonus_ticks.len()
+    }
+
+    pub fn renewals_num(&self) -> usize {
+        self.renewals.len()
+    }
+
+    // Called after cuid-controller has transmitted value to chan-nel
+    pub(super) fn task_check(&mut self, cuid: Uid, chan: usize, value: Integer) {
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].output == chan {
+                let args = self.tasks[i].inputs.iter().map(|&ichan| self.ether_integers.get(ichan).copied().unwrap_or(0)).collect::<Vec<Integer>>();
+                let task = &mut self.tasks[i];
+                task.attempts_count += 1;
+                if value == task.function.apply(&args) {
+                    task.completions_count += 1;
+                    match task.reward {
+                        Reward::Ticks => {
+                            for _ in 0..task.amount {
+                                if self.bonus_ticks.len() >= MAX_BONUS_TICKS {
+                                    break;
+                                }
+                                self.bonus_ticks.push_back(cuid);
+                            }
+                        },
+                        Reward::Renew => {
+                            let amount = task.amount;
+                            self.renewals.entry(cuid).and_modify(|n| { *n = n.saturating_add(amount) }).or_insert(amount);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Next controller that has earned extra tick and still exists
+    pub(super) fn bonus_tick_pop(&mut self) -> Option<Uid> {
+        while let Some(cuid) = self.bonus_ticks.pop_front() {
+            if self.controllers.contains_key(&cuid) {
+                return Some(cuid);
+            }
+        }
+        None
+    }
+
+}
@@ -65,9 +65,12 @@ impl Ælhometta {
             // Choose given controller, or random one if none has been given, and work with its copy
             let cuid = match ctrl_optuid {
                 &Some(ctrl_uid) => ctrl_uid,
-                &None => {
-                    let (cuid, _) = self.controllers.iter().choose(&mut self.rng).unwrap(); // should not panic, because self.controllers isn't empty
-                    *cuid
+                &None => match self.bonus_tick_pop() {
+                    Some(cuid) => cuid,
+                    None => {
+                        let (cuid, _) = self.controllers.iter().choose(&mut self.rng).unwrap(); // should not panic, because self.controllers isn't empty
+                        *cuid
+                    }
                 }
             };
 
@@ -163,6 +166,7 @@ impl Ælhometta {
                                                                                     if let Some(nuid) = nouid {
                                                                                         newuids_set.insert(nuid);
                                                                                     }
+                                                                                    self.journal_node(wnuid);
                                                                                     if let Some(wnode) = self.nodes.get_mut(&wnuid) {
                                                                                         match wcontent {
                                                                                             Content::Branch => {
@@ -191,6 +195,7 @@ impl Ælhometta {
                                                                                         NextToStored => {
                                                                                             if constr_uids_stack.len() != 0 {
                                                                                                 let st_ouid = constr_uids_stack.last().cloned();
+                                                                                                self.journal_node(wnuid);
                                                                                                 if let Some(wnode) = self.nodes.get_mut(&wnuid) {
                                                                                                     match wcontent {
                                                                                                         Content::Branch => {
@@ -280,6 +285,7 @@ impl Ælhometta {
                                                     let wn_next_optuid = Optuid::ot_bits(self.nodes.get(&wnuid).unwrap().b_next);
                                                     let nnouid = self.add_new_node_to_existing(Content::ot_bits(ctrl.registers.integer as u8), & ctrl.data_optuids[ctrl.i_data_optuid]);
                                                     if let Some(nnuid) = nnouid {                                                
+                                                        self.journal_node(nnuid);
                                                         if let Some(nnode) = self.nodes.get_mut(&nnuid) {
                                                             nnode.b_next = wn_next_optuid.to_bits();
                                                             ctrl.data_optuids[ctrl.i_data_optuid] = nnouid;
@@ -625,6 +631,7 @@ impl Ælhometta {
                                                                             if let Some(nuid) = nouid {
                                                                                 newuids_set.insert(nuid);
                                                                             }
+                                                                            self.journal_node(wnuid);
                                                                             if let Some(wnode) = self.nodes.get_mut(&wnuid) {
                                                                                 wnode.b_next = nouid.to_bits();
                                                                                 ctrl.new_chain_optuid = nouid;
@@ -738,7 +745,8 @@ impl Ælhometta {
                                                 let chan = ctrl.integer_channels[ctrl.i_integer_channel];
                                                 if chan < self.ether_integers.len() {
                                                     self.ether_integers[chan] = ctrl.registers.integer;
-                                                    ctrl.flags.success = true;    
+                                                    ctrl.flags.success = true;
+                                                    self.task_check(cuid, chan, ctrl.registers.integer);
                                                 }
                                             }
                                         },
@@ -750,6 +758,7 @@ impl Ælhometta {
 
                                         Write => {
                                             if let Some(wnuid) = ctrl.data_optuids[ctrl.i_data_optuid] {
+                                                self.journal_node(wnuid);
                                                 self.nodes.entry(wnuid).and_modify(|wnode| {
                                                     wnode.b_content = ctrl.registers.integer as u8;
                                                     ctrl.data_optuids[ctrl.i_data_optuid] = Optuid::ot_bits(wnode.b_next);
@@ -816,6 +825,7 @@ impl Ælhometta {
                         if self.controllers.contains_key(&cuid) { // ...if it has NOT been replaced by new controller created at this very execution
                             self.controllers.insert(cuid, ctrl);
                         }
+                        self.journal_controller(cuid);
                     } else {
                         self.remove_controller(& Some(cuid));
                     }
@@ -829,8 +839,11 @@ impl Ælhometta {
 
         // Background glitch
         if self.rng.gen_bool(self.glitch_background_prob) {
-            if let Some((_, node)) = self.nodes.iter_mut().choose(&mut self.rng) {
+            if let Some((uid, node)) = self.nodes.iter_mut().choose(&mut self.rng) {
                 node.b_content = CONTENTS[self.rng.gen_range(0..CONTENTS.len())].to_bits();
+                if let Some(ref mut jt) = self.journal {
+                    jt.nodes.insert(*uid);
+                }
                 self.glitch_background_count += 1;
             }
         }        
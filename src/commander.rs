@@ -45,19 +45,34 @@ use std::{
 };
 
 mod ancestors;
+#[cfg(unix)]
+mod attach;
 mod backtrace;
 mod changelim;
 mod cleanse;
 mod commandswitch;
+#[cfg(unix)]
+mod daemon;
+mod diff;
 mod ether;
+mod export;
+mod extinction;
 mod glitch;
 mod help;
 mod history;
+mod import;
+mod inspect;
 mod iomap;
+mod journal;
+mod merge;
+mod monitor;
+mod output;
+mod pace;
 mod peer;
 mod prevnodes;
 mod random;
 mod run;
+mod schedule;
 mod set;
 mod settings;
 mod shell;
@@ -65,15 +80,33 @@ mod showctrl;
 mod shownode;
 mod showseq;
 mod showsizes;
+mod snapshot;
+mod source;
 mod statistics;
+mod task;
 mod tick;
+mod until;
+mod upgrade;
+
+pub use run::{
+    RunLimit,
+    Stop
+};
+pub use until::Condition;
+
+use extinction::Extinctions;
+use monitor::Monitor;
+use schedule::ScheduledCommand;
 
 use crate::aelhometta::{
+    BackgroundSave,
     Uid,
-    Ælhometta
+    Ælhometta,
+    DEFAULT_ÆLHOMETTA_FILENAME
 };
 
 pub const DEFAULT_COMMANDER_FILENAME: &str = "commander.json";
+pub const DEFAULT_SOCKET_FILENAME: &str = "aelhometta.sock";
 pub const HISTORY_MAX_LEN: usize = 0x10000;
 
 #[derive(Serialize, Deserialize)]
@@ -86,6 +119,28 @@ struct Settings {
     #[serde(default = "def_freqs_window_margin")] freqs_window_margin: usize,
     #[serde(default = "def_freqs_comm_str_len")] freqs_comm_str_len: usize,
     #[serde(default = "def_freqs_cons_str_len")] freqs_cons_str_len: usize,
+    #[serde(default = "def_io_bins")] io_bins: usize,
+    #[serde(default)] io_log: String, // empty if not logging
+    #[serde(default = "def_io_log_interval")] io_log_interval: usize, // seconds
+    #[serde(default)] stats_log: String, // empty if not logging
+    #[serde(default = "def_stats_log_format")] stats_log_format: String, // "csv" or "jsonl"
+    #[serde(default = "def_stats_log_interval")] stats_log_interval: usize, // seconds, 0 if not logging by time
+    #[serde(default)] stats_log_ticks: usize, // 0 if not logging by ticks
+    #[serde(default = "def_autosave_interval")] autosave_interval: usize, // seconds, 0 if not autosaving by time
+    #[serde(default)] autosave_ticks: usize, // 0 if not autosaving by ticks
+    #[serde(default = "def_autosave_background")] autosave_background: bool,
+    #[serde(default = "def_snapshots_dir")] snapshots_dir: String,
+    #[serde(default = "def_snapshots_max")] snapshots_max: usize, // 0 if no snapshots
+    #[serde(default = "def_compress_saves")] compress_saves: bool,
+    #[serde(default)] journal: bool,
+    #[serde(default = "def_journal_max_deltas")] journal_max_deltas: usize,
+    #[serde(default)] script_abort_on_error: bool,
+    #[serde(default)] output_json: bool, // inspection commands print JSON as if given --json
+    #[serde(default)] monitor_port: u16, // 0 if no HTTP monitor
+    #[serde(default = "def_monitor_interval")] monitor_interval: usize, // seconds
+    #[serde(default)] on_extinction: String, // empty if ticking on, see ExtinctionPolicy
+    #[serde(default)] pace_tps: f64, // target ticks per second, 0 if as fast as possible
+    #[serde(default)] pace_ticks_per_io: usize, // target ticks per the shortest I/O mapping interval, 0 if none
 }
 
 #[derive(Serialize, Deserialize)]
@@ -109,7 +164,21 @@ struct Selections {
 pub struct Commander {
     settings: Settings,
     history: History,
-    #[serde(default = "def_selections")] selections: Selections
+    #[serde(default = "def_selections")] selections: Selections,
+    #[serde(default)] extinctions: Extinctions,
+    #[serde(default)] schedule: Vec<ScheduledCommand>,
+    #[serde(skip)] background_save: Option<BackgroundSave>,
+    #[serde(skip)] background_save_done: bool, // to be shown once on status line
+    // Given by command line, not saved
+    #[serde(skip, default = "def_world_filepath")] world_filepath: String,
+    #[serde(skip)] no_save: bool, // neither autosaves nor save on quit
+    #[serde(skip)] no_peer: bool, // loaded states are not exposed
+    #[serde(skip)] script: VecDeque<String>, // lines to be executed by shell before those from stdin
+    #[serde(skip)] script_sourced: usize, // lines added by source since the last line from stdin
+    #[serde(skip)] script_abort_on_error: Option<bool>, // overrides the setting
+    #[serde(skip)] json_once: bool, // --json given to the command being executed
+    #[serde(skip)] monitor: Option<Monitor>,
+    #[serde(skip)] monitor_port: Option<u16> // overrides the setting
 }
 
 pub trait ParseHex {
@@ -144,10 +213,60 @@ fn def_freqs_cons_str_len() -> usize {
     12
 }
 
+fn def_io_bins() -> usize {
+    8
+}
+
+fn def_io_log_interval() -> usize {
+    60
+}
+
+fn def_stats_log_format() -> String {
+    String::from("csv")
+}
+
+fn def_stats_log_interval() -> usize {
+    60
+}
+
+fn def_autosave_interval() -> usize {
+    3600
+}
+
+// Off, since the payload held meanwhile raises peak memory by the size of uncompressed file
+fn def_autosave_background() -> bool {
+    false
+}
+
+fn def_snapshots_dir() -> String {
+    String::from("snapshots")
+}
+
+fn def_snapshots_max() -> usize {
+    8
+}
+
+// Off, so the file keeps its plain layout unless asked otherwise
+fn def_compress_saves() -> bool {
+    false
+}
+
+fn def_journal_max_deltas() -> usize {
+    24
+}
+
+fn def_monitor_interval() -> usize {
+    5
+}
+
 fn def_selections() -> Selections {
     Selections::new_default()
 }
 
+fn def_world_filepath() -> String {
+    String::from(DEFAULT_ÆLHOMETTA_FILENAME)
+}
+
 impl Settings {
     fn new_default() -> Self {
         Self {
@@ -158,7 +277,29 @@ impl Settings {
             freqs_interval: def_freqs_interval(),
             freqs_window_margin: def_freqs_window_margin(),
             freqs_comm_str_len: def_freqs_comm_str_len(),
-            freqs_cons_str_len: def_freqs_cons_str_len()
+            freqs_cons_str_len: def_freqs_cons_str_len(),
+            io_bins: def_io_bins(),
+            io_log: String::new(),
+            io_log_interval: def_io_log_interval(),
+            stats_log: String::new(),
+            stats_log_format: def_stats_log_format(),
+            stats_log_interval: def_stats_log_interval(),
+            stats_log_ticks: 0,
+            autosave_interval: def_autosave_interval(),
+            autosave_ticks: 0,
+            autosave_background: def_autosave_background(),
+            snapshots_dir: def_snapshots_dir(),
+            snapshots_max: def_snapshots_max(),
+            compress_saves: def_compress_saves(),
+            journal: false,
+            journal_max_deltas: def_journal_max_deltas(),
+            script_abort_on_error: false,
+            output_json: false,
+            monitor_port: 0,
+            monitor_interval: def_monitor_interval(),
+            on_extinction: String::new(),
+            pace_tps: 0.0,
+            pace_ticks_per_io: 0
         }
     }
 }
@@ -250,7 +391,20 @@ impl Commander {
         Self {
             settings,
             history,
-            selections
+            selections,
+            extinctions: Extinctions::default(),
+            schedule: Vec::new(),
+            background_save: None,
+            background_save_done: false,
+            world_filepath: def_world_filepath(),
+            no_save: false,
+            no_peer: false,
+            script: VecDeque::new(),
+            script_sourced: 0,
+            script_abort_on_error: None,
+            json_once: false,
+            monitor: None,
+            monitor_port: None
         }
     }
 
@@ -260,8 +414,16 @@ impl Commander {
         Ok(())
     }
 
-    pub fn save_default(&self) -> Result<(), String> {
-        self.save(DEFAULT_COMMANDER_FILENAME)
+    // Full save, or delta to the journal of the world file if journal is on.
+    // Background save, if any, is waited for, lest it overwrite this one
+    pub fn save_world(&mut self, æh: &mut Ælhometta) -> Result<(), String> {
+        self.background_save_wait()?;
+        if self.settings.journal {
+            æh.save_journaled(&self.world_filepath, self.settings.compress_saves, self.settings.journal_max_deltas)
+        } else {
+            æh.journal_stop();
+            æh.save(&self.world_filepath, self.settings.compress_saves)
+        }
     }
 
     pub fn load(filepath: &str) -> Result<Self, String> {
@@ -270,8 +432,24 @@ impl Commander {
         Ok(commander)
     }
 
-    pub fn load_default() -> Result<Self, String> {
-        Self::load(DEFAULT_COMMANDER_FILENAME)
+    pub fn set_world_filepath(&mut self, filepath: &str) {
+        self.world_filepath = String::from(filepath);
+    }
+
+    pub fn set_no_save(&mut self, no_save: bool) {
+        self.no_save = no_save;
+    }
+
+    pub fn set_no_peer(&mut self, no_peer: bool) {
+        self.no_peer = no_peer;
+    }
+
+    pub fn set_script_abort_on_error(&mut self, abort: bool) {
+        self.script_abort_on_error = Some(abort);
+    }
+
+    pub fn set_monitor_port(&mut self, port: u16) {
+        self.monitor_port = Some(port);
     }
 
 }
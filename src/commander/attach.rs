@@ -0,0 +1,112 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Client of daemon.rs: lines typed go to the daemon, its replies are shown
+
+use crossterm::style::Stylize;
+
+use std::{
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        ErrorKind,
+        Write
+    },
+    os::unix::net::UnixStream
+};
+
+use super::{
+    daemon::DAEMON_END_OF_REPLY,
+    Commander,
+    DEFAULT_SOCKET_FILENAME
+};
+
+// False when the daemon has gone
+fn show_reply(reader: &mut impl BufRead) -> Result<bool, String> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => return Ok(false),
+            Ok(_) => {
+                if line.trim_end_matches('\n') == DAEMON_END_OF_REPLY {
+                    return Ok(true);
+                }
+                print!("{}", &line);
+            },
+            Err(ref err) if err.kind() == ErrorKind::ConnectionReset => return Ok(false),
+            Err(err) => return Err(format!("Cannot read from daemon: {}", &err))
+        }
+    }
+}
+
+impl Commander {
+    // Non-interactive, before anything is loaded: "aelhometta attach [socket]"
+    pub fn attach(paramstr: &[&str]) -> Result<(), String> {
+        let socket_path = paramstr.first().copied().unwrap_or(DEFAULT_SOCKET_FILENAME);
+        let mut stream = UnixStream::connect(socket_path).map_err(|err| format!("Cannot connect to '{}': {}", socket_path, &err))?;
+        let mut reader = BufReader::new(stream.try_clone().map_err(|err| err.to_string())?);
+
+        if !show_reply(&mut reader)? {
+            return Err(String::from("Daemon has stopped"));
+        }
+        let mut attached = true;
+        while attached {
+            print!("@ ");
+            io::stdout().flush().unwrap_or(());
+            let mut input = String::new();
+            match io::stdin().read_line(&mut input) {
+                Ok(0) => { // end of stdin, as if detaching
+                    println!();
+                    input = String::from("detach");
+                },
+                Ok(_) => {},
+                Err(err) => return Err(format!("Cannot read input: {}", &err))
+            }
+            let input = input.trim();
+            if input.is_empty() {
+                continue;
+            }
+            let sent = match stream.write_all(format!("{}\n", input).as_bytes()) {
+                Ok(()) => true,
+                Err(ref err) if matches!(err.kind(), ErrorKind::BrokenPipe | ErrorKind::ConnectionReset) => false,
+                Err(err) => return Err(format!("Cannot write to daemon: {}", &err))
+            };
+            if !(sent && show_reply(&mut reader)?) {
+                println!("{}", "Daemon has stopped".dark_grey());
+                break;
+            }
+            attached = input != "detach";
+        }
+        Ok(())
+    }
+
+}
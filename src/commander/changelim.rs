@@ -43,6 +43,7 @@ impl Commander {
         if paramstr.len() >= 1 {
             match paramstr[0].parse::<u8>() {
                 Ok(max_num_binlog) => {
+                    self.snapshot_before(æh)?;
                     æh.change_limit(max_num_binlog);
                     println!("{}", "Changed".green());
                     Ok(())
@@ -37,6 +37,7 @@ use {
 
 impl Commander {
     pub fn cleanse(&self, æh: &mut Ælhometta) -> Result<(), String> {
+        self.snapshot_before(æh)?;
         æh.cleanse();
         println!("{}", "Cleansed".green());
         Ok(())
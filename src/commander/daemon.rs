@@ -0,0 +1,440 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Headless running: ticks go on without terminal, shell commands come over Unix domain socket, see attach.rs
+
+use chrono::prelude::*;
+
+use crossterm::style::Stylize;
+
+use rand::Rng;
+
+use std::{
+    env,
+    fs::{
+        self,
+        DirBuilder,
+        File,
+        Permissions
+    },
+    io::{
+        self,
+        ErrorKind,
+        Read,
+        Seek,
+        SeekFrom,
+        Write
+    },
+    os::{
+        raw::c_int,
+        unix::{
+            fs::{
+                DirBuilderExt,
+                OpenOptionsExt,
+                PermissionsExt
+            },
+            io::AsRawFd,
+            net::{
+                UnixListener,
+                UnixStream
+            }
+        }
+    },
+    path::{
+        Path,
+        PathBuf
+    },
+    process,
+    thread,
+    time::{
+        Duration,
+        Instant
+    }
+};
+
+use {
+    crate::aelhometta::Ælhometta,
+    super::{
+        pace::Pacer,
+        run::{
+            Chores,
+            Stop
+        },
+        shell::Executed,
+        until::Condition,
+        Commander,
+        RunLimit
+    }
+};
+
+pub const DAEMON_END_OF_REPLY: &str = "\u{4}"; // on its own line after each reply
+
+const DAEMON_TICKS_BATCH: u128 = 0x400; // between looks at the socket
+const DAEMON_PAUSED_SLEEP: Duration = Duration::from_millis(20);
+const DAEMON_WRITE_TIMEOUT: Duration = Duration::from_secs(10); // a client that does not read is dropped
+
+// Standard output is redirected to a file while a command executes, since commands print
+extern "C" {
+    fn dup(fd: c_int) -> c_int;
+    fn dup2(fd: c_int, fd2: c_int) -> c_int;
+    fn close(fd: c_int) -> c_int;
+}
+
+const STDOUT_FD: c_int = 1;
+const DAEMON_PRIVATE_MODE: u32 = 0o600; // of socket and capture, so that other users neither command nor read
+
+// File in a directory of its own, which nobody else can have prepared, both removed when dropped
+struct Capture {
+    dir: PathBuf,
+    path: PathBuf,
+    file: File
+}
+
+// Stdout goes back where it was even if what is captured panics
+struct Redirection {
+    saved_fd: c_int
+}
+
+struct Client {
+    stream: UnixStream,
+    incoming: Vec<u8> // until newline
+}
+
+fn log(message: &str) {
+    println!("{} {}", format!("[{} UTC]", Utc::now().format("%Y.%m.%d %a %H:%M:%S")).dark_green(), message);
+}
+
+impl Capture {
+    fn new() -> Result<Self, String> {
+        let dir = env::temp_dir().join(format!("aelhometta-{}-{:016x}", process::id(), rand::thread_rng().gen::<u64>()));
+        // Fails if it exists, be it a symlink or not
+        DirBuilder::new().mode(0o700).create(&dir).map_err(|err| format!("Cannot create '{}': {}", dir.display(), &err))?;
+        let path = dir.join("out");
+        match File::options().read(true).write(true).create_new(true).mode(DAEMON_PRIVATE_MODE).open(&path) {
+            Ok(file) => Ok(Self {
+                dir,
+                path,
+                file
+            }),
+            Err(err) => {
+                fs::remove_dir(&dir).unwrap_or(());
+                Err(format!("Cannot create '{}': {}", path.display(), &err))
+            }
+        }
+    }
+
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).unwrap_or(());
+        fs::remove_dir(&self.dir).unwrap_or(());
+    }
+
+}
+
+impl Redirection {
+    fn new(file: &File) -> Result<Self, String> {
+        io::stdout().flush().unwrap_or(());
+        let saved_fd = unsafe { dup(STDOUT_FD) };
+        if saved_fd < 0 {
+            return Err(String::from("Cannot duplicate stdout"));
+        }
+        if unsafe { dup2(file.as_raw_fd(), STDOUT_FD) } < 0 {
+            unsafe { close(saved_fd) };
+            return Err(String::from("Cannot redirect stdout"));
+        }
+        Ok(Self {
+            saved_fd
+        })
+    }
+
+}
+
+impl Drop for Redirection {
+    fn drop(&mut self) {
+        io::stdout().flush().unwrap_or(());
+        unsafe {
+            dup2(self.saved_fd, STDOUT_FD);
+            close(self.saved_fd);
+        }
+    }
+
+}
+
+// Whatever f prints, instead of being shown
+fn captured<T>(capture: &mut Capture, f: impl FnOnce() -> T) -> Result<(T, Vec<u8>), String> {
+    let file = &mut capture.file;
+    file.set_len(0).and_then(|_| file.seek(SeekFrom::Start(0))).map_err(|err| format!("Cannot reset capture: {}", &err))?;
+    let t = {
+        let _redirection = Redirection::new(file)?;
+        f()
+    };
+    let mut output = Vec::new();
+    file.seek(SeekFrom::Start(0)).and_then(|_| file.read_to_end(&mut output)).map_err(|err| format!("Cannot read capture: {}", &err))?;
+    Ok((t, output))
+}
+
+// Whoever has managed to connect before the socket became private is dropped
+fn bind_private(socket_path: &str) -> io::Result<UnixListener> {
+    let listener = UnixListener::bind(socket_path)?;
+    fs::set_permissions(socket_path, Permissions::from_mode(DAEMON_PRIVATE_MODE))?;
+    listener.set_nonblocking(true)?;
+    while listener.accept().is_ok() {}
+    Ok(listener)
+}
+
+fn reply(client: &mut Client, output: &[u8]) -> io::Result<()> {
+    client.stream.set_nonblocking(false)?;
+    client.stream.set_write_timeout(Some(DAEMON_WRITE_TIMEOUT))?;
+    client.stream.write_all(output)?;
+    if !output.is_empty() && (output[output.len() - 1] != b'\n') {
+        client.stream.write_all(b"\n")?;
+    }
+    client.stream.write_all(format!("{}\n", DAEMON_END_OF_REPLY).as_bytes())?;
+    client.stream.set_nonblocking(true)
+}
+
+impl Commander {
+    fn print_daemon_state(æh: &Ælhometta, paused: bool) {
+        Self::print_state(æh, true);
+        println!(" {} {}", "|".dark_grey(), if paused { "paused".dark_yellow().bold() } else { "ticking".dark_green().bold() });
+    }
+
+    // Some(do_save) when the daemon is to stop
+    fn daemon_execute(&mut self, æh: &mut Ælhometta, input: &str, paused: &mut bool) -> Option<bool> {
+        match input.split_whitespace().next().unwrap_or("") {
+            "pause" => {
+                *paused = true;
+                println!("{}", "Paused".green());
+            },
+            "resume" => {
+                *paused = false;
+                println!("{}", "Resumed".green());
+            },
+            "r" | "run" => {
+                println!("{}", "Already running, see pause and resume".red().bold());
+            },
+            _ => {
+                if let Executed::Quit(do_save) = self.execute(æh, input) {
+                    return Some(do_save);
+                }
+                // Sourced lines, if any
+                while let Some(line) = self.script.pop_front() {
+                    println!("@ {}", &line);
+                    match self.execute(æh, &line) {
+                        Executed::Quit(do_save) => return Some(do_save),
+                        Executed::Failed if self.script_abort_on_error() => {
+                            println!("{}", format!("Script aborted, {} lines skipped", self.script.len()).red().bold());
+                            self.script.clear();
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+        println!();
+        Self::print_daemon_state(æh, *paused);
+        None
+    }
+
+    // Returns whether to save, and why it has stopped by itself, None if a client has said so.
+    // Stops when limit or condition, if any, is reached, or by on_extinction policy
+    pub fn daemon(&mut self, æh: &mut Ælhometta, socket_path: &str, limit: Option<RunLimit>, until: Option<&Condition>) -> Result<(bool, Option<Stop>), String> {
+        if let Some(cond) = until {
+            cond.validate(æh)?;
+        }
+
+        // Socket file left by a daemon that has not stopped properly would prevent binding
+        if Path::new(socket_path).exists() {
+            if UnixStream::connect(socket_path).is_ok() {
+                return Err(format!("Another daemon listens at '{}'", socket_path));
+            }
+            fs::remove_file(socket_path).map_err(|err| format!("Cannot remove stale '{}': {}", socket_path, &err))?;
+        }
+        let listener = bind_private(socket_path).map_err(|err| format!("Cannot listen at '{}': {}", socket_path, &err))?;
+
+        let mut capture = Capture::new()?;
+
+        log(&format!("{} {}", "Listening at".dark_grey(), socket_path.bold()));
+
+        let t_start = Instant::now();
+        let start_age = æh.age();
+        let mut chores = Chores::new(æh);
+        let mut clients: Vec<Client> = Vec::new();
+        let mut paused = false;
+        let mut buf = [0u8; 0x1000];
+
+        // Cheap conditions are checked after each tick too, not only once a second (while paused, shell commands may change things)
+        let until_each_tick = until.is_none_or(|cond| cond.cheap());
+        let mut last_until_t_elapsed: u64 = 0;
+
+        let mut pacer = Pacer::new();
+        self.schedule_restart();
+
+        let (do_save, stop) = 'ext: loop {
+            if paused {
+                thread::sleep(DAEMON_PAUSED_SLEEP);
+            } else {
+                let n = match limit {
+                    Some(RunLimit::Ticks(ticks)) => DAEMON_TICKS_BATCH.min((start_age + ticks).saturating_sub(æh.age())),
+                    _ => DAEMON_TICKS_BATCH
+                };
+                pacer.retarget(self.pace_target(æh));
+                for _ in 0..n {
+                    // Ahead of target rate, clients are served meanwhile
+                    let delay = pacer.delay();
+                    if !delay.is_zero() {
+                        thread::sleep(delay.min(DAEMON_PAUSED_SLEEP));
+                        break;
+                    }
+                    æh.tick(&None);
+                    pacer.ticked();
+                    if !self.schedule.is_empty() {
+                        for command in self.schedule_due(æh, t_start.elapsed().as_secs()) {
+                            self.schedule_execute(æh, &command);
+                        }
+                    }
+                    if æh.num_controllers() == 0 {
+                        if let Some((stop, message)) = self.extinction(æh) {
+                            log(&message.dark_yellow().bold().to_string());
+                            if stop {
+                                break 'ext (true, Some(Stop::Extinct));
+                            }
+                        }
+                    }
+                    if let Some(cond) = until.filter(|_| until_each_tick) {
+                        if let Some(reason) = cond.reason(æh, t_start.elapsed().as_secs()) {
+                            break 'ext (true, Some(Stop::Condition(reason)));
+                        }
+                    }
+                }
+            }
+
+            let t_elapsed = t_start.elapsed().as_secs();
+            for err in self.do_chores(æh, &mut chores, t_elapsed) {
+                log(&err.red().bold().to_string());
+            }
+            if let Some(cond) = until.filter(|_| t_elapsed > last_until_t_elapsed) {
+                last_until_t_elapsed = t_elapsed;
+                if let Some(reason) = cond.reason(æh, t_elapsed) {
+                    break 'ext (true, Some(Stop::Condition(reason)));
+                }
+            }
+            match limit {
+                Some(RunLimit::Seconds(dur)) if t_elapsed >= dur => break 'ext (true, Some(Stop::Limit)),
+                Some(RunLimit::Ticks(ticks)) if æh.age() >= start_age + ticks => break 'ext (true, Some(Stop::Limit)),
+                _ => {}
+            }
+
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let mut client = Client {
+                            stream,
+                            incoming: Vec::new()
+                        };
+                        let greeting = captured(&mut capture, || {
+                            println!("{} {}", "Attached to daemon, process".green(), format!("{}", process::id()).green().bold());
+                            println!("{}", "Shell commands as usual, plus pause, resume, detach; quit stops daemon".dark_grey());
+                            println!();
+                            Self::print_daemon_state(æh, paused);
+                        })?.1;
+                        if reply(&mut client, &greeting).is_ok() {
+                            log(&"Client attached".dark_grey().to_string());
+                            clients.push(client);
+                        }
+                    },
+                    Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(err) => {
+                        log(&format!("Cannot accept client: {}", &err).red().bold().to_string());
+                        break;
+                    }
+                }
+            }
+
+            let mut i = 0;
+            while i < clients.len() {
+                let mut attached = true;
+                loop {
+                    match clients[i].stream.read(&mut buf) {
+                        Ok(0) => {
+                            attached = false;
+                            break;
+                        },
+                        Ok(n) => clients[i].incoming.extend_from_slice(&buf[..n]),
+                        Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                        Err(_) => {
+                            attached = false;
+                            break;
+                        }
+                    }
+                }
+                while attached {
+                    let Some(pos) = clients[i].incoming.iter().position(|&b| b == b'\n') else {
+                        break;
+                    };
+                    let input = String::from_utf8_lossy(&clients[i].incoming[..pos]).trim().to_string();
+                    clients[i].incoming.drain(..=pos);
+                    if input == "detach" {
+                        reply(&mut clients[i], "Detached".as_bytes()).unwrap_or(());
+                        attached = false;
+                        break;
+                    }
+                    log(&format!("{} {}", "@".dark_grey(), &input));
+                    let (quit, output) = captured(&mut capture, || self.daemon_execute(æh, &input, &mut paused))?;
+                    if let Some(do_save) = quit {
+                        let farewell = if do_save { "Stopping, state will be saved" } else { "Stopping, state will not be saved" };
+                        reply(&mut clients[i], farewell.as_bytes()).unwrap_or(());
+                        log(farewell);
+                        break 'ext (do_save, None);
+                    }
+                    if reply(&mut clients[i], &output).is_err() {
+                        attached = false;
+                    }
+                }
+                if attached {
+                    i += 1;
+                } else {
+                    clients.remove(i);
+                    log(&"Client detached".dark_grey().to_string());
+                }
+            }
+        };
+
+        if let Some(Stop::Condition(ref reason)) = stop {
+            log(&format!("Stopped: {}", reason).dark_yellow().bold().to_string());
+        }
+
+        fs::remove_file(socket_path).unwrap_or(());
+        Ok((do_save, stop))
+    }
+
+}
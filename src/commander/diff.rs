@@ -0,0 +1,138 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use {
+    crate::{
+        aelhometta::{
+            Uid,
+            Ælhometta
+        },
+        serbin::ToBits
+    },
+    super::Commander
+};
+
+const DIFF_UIDS_SHOWN: usize = 16;
+
+fn print_pair(label: &str, a: String, b: String) {
+    if a == b {
+        println!("{}{}", format!("{:24}", label).dark_grey(), a.dark_grey());
+    } else {
+        println!("{}{} {} {}", format!("{:24}", label).dark_grey(), a.blue(), "→".dark_grey(), b.yellow());
+    }
+}
+
+fn print_uids_only(label: &str, uids: &[Uid]) {
+    let mut shown = uids.iter().take(DIFF_UIDS_SHOWN).map(|uid| format!("{:X}", uid)).collect::<Vec<String>>().join(" ");
+    if uids.len() > DIFF_UIDS_SHOWN {
+        shown += &format!(" ... ({} more)", uids.len() - DIFF_UIDS_SHOWN);
+    }
+    println!("{}{}{}", format!("{:24}", label).dark_grey(), format!("{:<10}", uids.len()).cyan(), shown.dark_cyan());
+}
+
+fn print_only(label_a: &str, label_b: &str, only: &(Vec<String>, Vec<String>)) {
+    for item in & only.0 {
+        println!("{}{}", format!("{:24}", label_a).dark_grey(), item.as_str().blue());
+    }
+    for item in & only.1 {
+        println!("{}{}", format!("{:24}", label_b).dark_grey(), item.as_str().yellow());
+    }
+}
+
+impl Commander {
+    // Non-interactive, before anything is loaded: "aelhometta diff <a> <b>"
+    pub fn diff(paramstr: &[&str]) -> Result<(), String> {
+        if paramstr.len() < 2 {
+            return Err(String::from("Files to compare not specified"));
+        }
+        let d = Ælhometta::diff_files(paramstr[0], paramstr[1])?;
+
+        println!("{}{} {} {}", format!("{:24}", "Files").dark_grey(), paramstr[0].blue(), "→".dark_grey(), paramstr[1].yellow());
+        print_pair("Age", d.ages.0.to_string(), d.ages.1.to_string());
+        print_pair("Limit", format!("2^{}", d.limits.0), format!("2^{}", d.limits.1));
+
+        println!("{:4}{}", " ", "Nodes".dark_grey());
+        print_pair("Number", d.nums_nodes.0.to_string(), d.nums_nodes.1.to_string());
+        print_uids_only("Only in 1st", & d.nodes_only.0);
+        print_uids_only("Only in 2nd", & d.nodes_only.1);
+        println!("{}{}", format!("{:24}", "Changed").dark_grey(), d.nodes_changed.to_string().cyan());
+
+        println!("{:4}{}", " ", "Controllers".dark_grey());
+        print_pair("Number", d.nums_controllers.0.to_string(), d.nums_controllers.1.to_string());
+        print_uids_only("Only in 1st", & d.controllers_only.0);
+        print_uids_only("Only in 2nd", & d.controllers_only.1);
+        println!("{}{}", format!("{:24}", "Changed").dark_grey(), d.controllers_changed.to_string().cyan());
+
+        println!("{:4}{}", " ", "Contents".dark_grey());
+        for (content, na, nb) in & d.contents {
+            println!("{}{}{}{}{}",
+                format!("{:<4}", format!("{:02X}", content.to_bits())).blue(),
+                format!("{:<40}", format!("{:?}", content)).yellow(),
+                format!("{:>12}", na).blue(),
+                format!("{:>12}", nb).yellow(),
+                format!("{:>+12}", (*nb as i128) - (*na as i128)).dark_grey()
+            );
+        }
+
+        println!("{:4}{}", " ", "Counts".dark_grey());
+        for (name, na, nb) in & d.counts {
+            println!("{}{}{}{}",
+                format!("{:<44}", name).yellow(),
+                format!("{:>12}", na).blue(),
+                format!("{:>12}", nb).yellow(),
+                format!("{:>+12}", (*nb as i128) - (*na as i128)).dark_grey()
+            );
+        }
+
+        println!("{:4}{}", " ", "Glitches".dark_grey());
+        for (kind, pa, pb, na, nb) in & d.glitches {
+            print_pair(kind, format!("{:e}, {} occurred", pa, na), format!("{:e}, {} occurred", pb, nb));
+        }
+
+        println!("{:4}{}", " ", "Ether".dark_grey());
+        println!("{}{}", format!("{:24}", "Optuid changed").dark_grey(), d.optuid_channels_changed.to_string().magenta());
+        println!("{}{}", format!("{:24}", "Integer changed").dark_grey(), d.integer_channels_changed.to_string().blue());
+
+        println!("{:4}{}", " ", "Configuration".dark_grey());
+        print_pair("Commandswitch", format!("{:032X}", d.commandswitches.0), format!("{:032X}", d.commandswitches.1));
+        for (label, va, vb) in & d.peer {
+            print_pair(label, va.clone(), vb.clone());
+        }
+        print_only("Other peer only in 1st", "Other peer only in 2nd", & d.other_peers_only);
+        print_only("Whitelisted in 1st", "Whitelisted in 2nd", & d.whitelist_only);
+        print_only("Mapping only in 1st", "Mapping only in 2nd", & d.mappings_only);
+        print_only("Task only in 1st", "Task only in 2nd", & d.tasks_only);
+
+        Ok(())
+    }
+
+}
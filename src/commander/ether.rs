@@ -30,10 +30,14 @@
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use {
     crate::aelhometta::{
         Ælhometta,
-        Hexly
+        Hexly,
+        Integer,
+        Optuid
     },
     super::{
         Commander,
@@ -41,6 +45,43 @@ use {
     }
 };
 
+// Range of channels as requested; those out of bounds are absent from values
+#[derive(Serialize)]
+pub(super) struct ChannelsView<T> {
+    start: usize,
+    length: usize,
+    size: usize,
+    values: Vec<T>
+}
+
+pub(super) fn channels_view<T: Copy>(channels: &[T], start: usize, length: usize) -> ChannelsView<T> {
+    ChannelsView {
+        start,
+        length,
+        size: channels.len(),
+        values: channels.iter().skip(start).take(length).copied().collect()
+    }
+}
+
+fn print_optuids_view(view: &ChannelsView<Optuid>) {
+    for chan in view.start..(view.start + view.length) {
+        let chan_content_str_styl = match view.values.get(chan - view.start) {
+            Some(optuid) => optuid.hexly().magenta(),
+            None => String::from("OUT OF BOUNDS").red()
+        };
+        println!("{}{:8}{}", format!("{:>12}", chan).dark_magenta(), " ", chan_content_str_styl);
+    }
+}
+
+pub(super) fn print_integers_view(view: &ChannelsView<Integer>) {
+    for chan in view.start..(view.start + view.length) {
+        match view.values.get(chan - view.start) {
+            Some(integer) => println!("{}{:8}{}", format!("{:>12}", chan).dark_blue(), " ", format!("{0}={0:X}h", integer).blue()),
+            None => println!("{}{:8}{}", format!("{:>12}", chan).dark_red(), " ", "OUT OF BOUNDS".red())
+        }
+    }
+}
+
 impl Commander {
     pub fn ether(&self, æh: &Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() >= 1 {
@@ -66,29 +107,8 @@ impl Commander {
                         };
                         
                         match chantype.as_str() {
-                            "ouid" | "optuid" => {
-                                let ether_optuids = æh.ether_optuids();
-                                for chan in start..(start + length) {
-                                    let chan_content_str_styl = if chan < ether_optuids.len() {
-                                        ether_optuids[chan].hexly().magenta()
-                                    } else {
-                                        String::from("OUT OF BOUNDS").red()
-                                    };
-                                    println!("{}{:8}{}", format!("{:>12}", chan).dark_magenta(), " ", chan_content_str_styl);
-                                }
-                                Ok(())
-                            },
-                            "int" | "integer" => {
-                                let ether_integers = æh.ether_integers();
-                                for chan in start..(start + length) {
-                                    if chan < ether_integers.len() {
-                                        println!("{}{:8}{}", format!("{:>12}", chan).dark_blue(), " ", format!("{0}={0:X}h", ether_integers[chan]).blue());
-                                    } else {
-                                        println!("{}{:8}{}", format!("{:>12}", chan).dark_blue(), " ", "OUT OF BOUNDS".red());
-                                    };                                    
-                                }
-                                Ok(())
-                            },
+                            "ouid" | "optuid" => self.present(&channels_view(æh.ether_optuids(), start, length), print_optuids_view),
+                            "int" | "integer" => self.present(&channels_view(æh.ether_integers(), start, length), print_integers_view),
                             _ => {
                                 Err(format!("Unknown channels type '{}'", chantype))
                             }
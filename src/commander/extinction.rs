@@ -0,0 +1,235 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// What to do when no controllers are left, see on_extinction setting
+
+use chrono::prelude::*;
+
+use crossterm::style::Stylize;
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use std::{
+    collections::VecDeque,
+    time::{
+        SystemTime,
+        UNIX_EPOCH
+    }
+};
+
+use {
+    crate::aelhometta::{
+        MergeSelection,
+        Ælhometta
+    },
+    super::{
+        Commander,
+        ParseErrorPrefixise
+    }
+};
+
+const EXTINCTIONS_MAX_LEN: usize = 0x100;
+
+pub(super) enum ExtinctionPolicy {
+    Ignore, // tick on, as if nothing has happened
+    Stop,
+    AncestorA(u8, u8, usize),
+    AncestorB(usize),
+    Merge(String), // all controllers of another state, e.g. of the ancestor saved earlier
+    Restore // the latest snapshot that has controllers
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExtinctionRecord {
+    age: u128,
+    timestamp: i64, // microseconds since Unix epoch
+    action: String
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub(super) struct Extinctions {
+    reseeds: u128,
+    records: VecDeque<ExtinctionRecord> // the latest ones
+}
+
+#[derive(Serialize)]
+struct ExtinctionsView<'a> {
+    policy: &'a str,
+    reseeds: u128,
+    records: &'a VecDeque<ExtinctionRecord>
+}
+
+impl ExtinctionPolicy {
+    // Same words as "anc" and "merge" commands
+    pub(super) fn parse(policy: &str) -> Result<Self, String> {
+        let tokens: Vec<&str> = policy.split_whitespace().collect();
+        match tokens.first().map(|token| token.to_lowercase()).as_deref() {
+            None | Some("none") => Ok(Self::Ignore),
+            Some("stop") => Ok(Self::Stop),
+            Some("anc") | Some("ancestor") => match tokens.get(1).map(|kin| kin.to_lowercase()).as_deref() {
+                Some("a") => {
+                    if tokens.len() < 5 {
+                        return Err(String::from("Sterile-power, skip-power, spacity not specified"));
+                    }
+                    let sterile_power = match tokens[2].parse::<u8>() {
+                        Ok(p) => p,
+                        Err(err) => return err.prefixised("sterile-power")
+                    };
+                    let skip_power = match tokens[3].parse::<u8>() {
+                        Ok(p) => p,
+                        Err(err) => return err.prefixised("skip-power")
+                    };
+                    match tokens[4].parse::<usize>() {
+                        Ok(spacity) => Ok(Self::AncestorA(sterile_power, skip_power, spacity)),
+                        Err(err) => err.prefixised("spacity")
+                    }
+                },
+                Some("b") => match tokens.get(2).map(|s| s.parse::<usize>()) {
+                    Some(Ok(spacity)) => Ok(Self::AncestorB(spacity)),
+                    Some(Err(err)) => err.prefixised("spacity"),
+                    None => Err(String::from("Spacity not specified"))
+                },
+                Some(kin) => Err(format!("Unknown kin '{}'", kin)),
+                None => Err(String::from("Kin not specified"))
+            },
+            Some("merge") => match tokens.get(1) {
+                Some(filepath) => Ok(Self::Merge(String::from(*filepath))),
+                None => Err(String::from("Path not specified"))
+            },
+            Some("restore") => Ok(Self::Restore),
+            Some(other) => Err(format!("Unknown policy '{}', must be none, stop, anc, merge, or restore", other))
+        }
+    }
+
+}
+
+fn utc_str(timestamp: i64) -> String {
+    format!("{} UTC", NaiveDateTime::from_timestamp_micros(timestamp).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"))
+}
+
+impl Commander {
+    fn reseed(&self, æh: &mut Ælhometta, policy: &ExtinctionPolicy) -> Result<String, String> {
+        match policy {
+            ExtinctionPolicy::AncestorA(sterile_power, skip_power, spacity) => {
+                æh.introduce_ancestor_a(*sterile_power, *skip_power, *spacity);
+                Ok(format!("ancestor A {} {} {} introduced", sterile_power, skip_power, spacity))
+            },
+            ExtinctionPolicy::AncestorB(spacity) => {
+                æh.introduce_ancestor_b(*spacity);
+                Ok(format!("ancestor B {} introduced", spacity))
+            },
+            ExtinctionPolicy::Merge(filepath) => {
+                let other = Ælhometta::load_unexposed(filepath)?;
+                let outcome = æh.merge(&other, &MergeSelection::All);
+                Ok(format!("{} controllers merged from '{}'", outcome.controllers_copied, filepath))
+            },
+            ExtinctionPolicy::Restore => {
+                // Snapshots taken after the extinction, if any, are of no use
+                for path in self.snapshot_files().iter().rev() {
+                    let filepath = path.display().to_string();
+                    let Ok(mut restored) = Ælhometta::load_unexposed(&filepath) else {
+                        continue;
+                    };
+                    if restored.num_controllers() == 0 {
+                        continue;
+                    }
+                    // Peer must release its port before the restored one can expose itself
+                    æh.peer_repose().unwrap_or(());
+                    let expose_err = if restored.exposed() && !self.no_peer {
+                        restored.peer_expose().err()
+                    } else {
+                        None
+                    };
+                    *æh = restored;
+                    return Ok(match expose_err {
+                        Some(err) => format!("restored from '{}', peer not exposed: {}", &filepath, &err),
+                        None => format!("restored from '{}'", &filepath)
+                    });
+                }
+                Err(String::from("no snapshot with controllers"))
+            },
+            ExtinctionPolicy::Ignore | ExtinctionPolicy::Stop => Ok(String::new())
+        }
+    }
+
+    // To be called when no controllers are left. Returns whether to stop, and what has happened, for the caller to show;
+    // None if the policy is to ignore. If reseeding fails or does not help, it stops, rather than tick an empty world
+    pub(super) fn extinction(&mut self, æh: &mut Ælhometta) -> Option<(bool, String)> {
+        let policy = ExtinctionPolicy::parse(& self.settings.on_extinction).unwrap_or(ExtinctionPolicy::Stop);
+        let age = æh.age();
+        let (stop, action) = match policy {
+            ExtinctionPolicy::Ignore => return None,
+            ExtinctionPolicy::Stop => (true, String::from("stopped")),
+            _ => match self.reseed(æh, &policy) {
+                Ok(action) if æh.num_controllers() > 0 => {
+                    self.extinctions.reseeds += 1;
+                    (false, action)
+                },
+                Ok(action) => (true, format!("{}, still no controllers, stopped", action)),
+                Err(err) => (true, format!("cannot reseed: {}, stopped", &err))
+            }
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |dur| dur.as_micros() as i64);
+        let message = format!("Extinct at age {}: {}", age, &action);
+        self.extinctions.records.push_back(ExtinctionRecord {
+            age,
+            timestamp,
+            action
+        });
+        while self.extinctions.records.len() > EXTINCTIONS_MAX_LEN {
+            self.extinctions.records.pop_front();
+        }
+        Some((stop, message))
+    }
+
+    // "stat ext"
+    pub(super) fn extinctions_show(&self) -> Result<(), String> {
+        let view = ExtinctionsView {
+            policy: if self.settings.on_extinction.is_empty() { "none" } else { & self.settings.on_extinction },
+            reseeds: self.extinctions.reseeds,
+            records: & self.extinctions.records
+        };
+        self.present(&view, |view| {
+            println!("{}{}", format!("{:24}", "Policy").dark_blue(), view.policy.blue());
+            println!("{}{}", format!("{:24}", "Reseeds").dark_blue(), format!("{}", view.reseeds).blue());
+            for record in view.records {
+                println!("{}{}{}",
+                    format!("{:36}", utc_str(record.timestamp)).dark_green(),
+                    format!("{:>16}  ", record.age).blue(),
+                    record.action.clone().yellow()
+                );
+            }
+        })
+    }
+
+}
@@ -59,8 +59,8 @@ impl Commander {
                         "Repeat last command"),
                     ("anc | ancestor",
                         "Introduce ancestor of given kin with given parameters"),
-                    ("r | run",
-                        "Run until keypress"),
+                    ("r | run [until <condition>]",
+                        "Run until keypress or condition"),
                     ("t | tick",
                         "Run given number of ticks of given controller"),
                     ("glitch",
@@ -91,6 +91,18 @@ impl Commander {
                         "Configure peer"),
                     ("iomap",
                         "Configure input/output mappings of integer channels"),
+                    ("task",
+                        "Configure tasks that reward controllers for outputs related to inputs"),
+                    ("snap | snapshot",
+                        "List, take, restore snapshots of state"),
+                    ("journal",
+                        "Show journal of saves or compact it"),
+                    ("export",
+                        "Write state to file in readable format"),
+                    ("import",
+                        "Replace state with one read from file in readable format"),
+                    ("merge",
+                        "Copy nodes and controllers from saved state into current one"),
                     ("showsizes",
                         "Show constant sizes of some arrays"),
                     ("sets | settings",
@@ -98,8 +110,14 @@ impl Commander {
                     ("set",
                         "Set commander setting to value"),
                     ("hist | history",
-                        "Show history of commands")
+                        "Show history of commands"),
+                    ("source",
+                        "Execute commands from file"),
+                    ("sched | schedule",
+                        "List, add, remove commands to be executed during run at given age or time")
                 ]);
+                println!();
+                println!("{}", "shownode, showctrl, showseq, ether, statistics, peer (without subcommand or with ether), and iomap ... list print one line of JSON if given --json, or always if output_json setting is true".dark_grey());
             },
 
             "q" | "quit" | "exit" | "end" | "bye" => {
@@ -128,6 +146,18 @@ impl Commander {
 
             "r" | "run" => {
                 println!("{}{}", format!("{:32}", "run").dark_grey().bold(), "Run ticks until keypress".dark_grey());
+                println!("{}{}{}", format!("{:32}", "run until <condition>").dark_grey().bold(), "Run ticks until keypress or until ".dark_grey(), "condition".dark_grey().italic());
+                println!("{:32}{}{}", "", "condition".dark_grey().italic(), " : terms joined by 'and', 'or' ('and' first), each term being one of".dark_grey());
+                println!("{:38}{}{}", "", "age [op] N".dark_grey().italic(), " — ticks since beginning".dark_grey());
+                println!("{:38}{}{}", "", "ctrls [op] N".dark_grey().italic(), " — number of controllers".dark_grey());
+                println!("{:38}{}{}", "", "nodes [op] N".dark_grey().italic(), " — number of nodes".dark_grey());
+                println!("{:38}{}{}", "", "gen [op] N".dark_grey().italic(), " — maximum generation, checked once a second".dark_grey());
+                println!("{:38}{}{}", "", "comm <command> [op] N".dark_grey().italic(), " — times command has been executed, op defaults to >".dark_grey());
+                println!("{:38}{}{}", "", "eth int <channel> [op] N".dark_grey().italic(), " — integer in ether channel, op defaults to =".dark_grey());
+                println!("{:38}{}{}", "", "time [op] N".dark_grey().italic(), " — seconds since run began".dark_grey());
+                println!("{:38}{}{}", "", "extinct".dark_grey().italic(), " — no controllers left".dark_grey());
+                println!("{:32}{}{}", "", "op".dark_grey().italic(), " : < <= > >= = !=, defaults to >=".dark_grey());
+                println!("{:32}{}", "", "e.g. run until extinct or gen >= 100 and ctrls < 1000".dark_grey());
             },
 
             "t" | "tick" => {
@@ -188,16 +218,19 @@ impl Commander {
             },
 
             "stat" | "statistics" => {
-                println!("{}{}{}", format!("{:32}", "statistics <topic>").dark_grey().bold(), "Show statistics related to ".dark_grey(), "topic".dark_grey().italic());
+                println!("{}{}{}", format!("{:32}", "statistics <topic> [<parameters>]").dark_grey().bold(), "Show statistics related to ".dark_grey(), "topic".dark_grey().italic());
                 println!("{:32}{}{}", "", "topic".dark_grey().italic(), " : one of the following (case-insensitive):".dark_grey());
                 println!("{:39}{}{}", "", "cgen".dark_grey().italic(), " — generation of controllers".dark_grey());
                 println!("{:39}{}{}", "", "chan".dark_grey().italic(), " — channels usage (optuid, integer)".dark_grey());
                 println!("{:39}{}{}", "", "cont".dark_grey().italic(), " — content of nodes".dark_grey());
+                println!("{:39}{}{}", "", "ext".dark_grey().italic(), " — extinctions met by on_extinction policy, reseeds".dark_grey());
+                println!("{:39}{}{}", "", "io [bins]".dark_grey().italic(), " — correlation and mutual information between input and output channels, over last input updates".dark_grey());
+                println!("{:39}{}{}", "", "task".dark_grey().italic(), " — attempts and completions of tasks, pending rewards".dark_grey());
                 println!("{:39}{}{}", "", "tick".dark_grey().italic(), " — execution count (spaces, branches, commands), construction instructions count".dark_grey());
             },
 
             "cleanse" => {
-                println!("{}{}", format!("{:32}", "cleanse").dark_grey().bold(), "Reset by removing all nodes and controllers".dark_grey());
+                println!("{}{}", format!("{:32}", "cleanse").dark_grey().bold(), "Reset by removing all nodes and controllers, after taking snapshot".dark_grey());
             },
 
             "commsw" | "commandswitch" => {
@@ -207,7 +240,7 @@ impl Commander {
             },
 
             "changelim" => {
-                println!("{}{}{}", format!("{:32}", "changelim <max_num_log2>").dark_grey().bold(), "Change maximum number of nodes and controllers to 2^".dark_grey(), "max_num_log2".dark_grey().italic());
+                println!("{}{}{}{}", format!("{:32}", "changelim <max_num_log2>").dark_grey().bold(), "Change maximum number of nodes and controllers to 2^".dark_grey(), "max_num_log2".dark_grey().italic(), ", after taking snapshot".dark_grey());
                 println!("{:32}{}{}", "", "max_num_log2".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
             },
 
@@ -249,21 +282,112 @@ impl Commander {
             "iomap" => {
                 println!("{}{}{}{}", format!("{:62}", "iomap <subcommand> [<parameters>]").dark_grey().bold(), "Execute ".dark_grey(), "subcommand".dark_grey().italic(), " related to IO mapping of integer channels".dark_grey());
                 println!("{}", "Available subcommands:".dark_grey());
-                println!("{:6}{}{}", "", format!("{:56}", "<in|out> add <start> <length> <interval> <filepath>").dark_grey().bold(), "Add input or output mapping".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "<in|out> add <start> <length> <interval> <filepath> [format [param]]").dark_grey().bold(), "Add input or output mapping".dark_grey());
                 println!("{:62}{}{}", "", "start".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
                 println!("{:62}{}{}", "", "length".dark_grey().italic(), " : positive integer in decimal".dark_grey());
                 println!("{:62}{}{}", "", "interval".dark_grey().italic(), " : positive integer in decimal (μs)".dark_grey());
                 println!("{:62}{}{}", "", "filepath".dark_grey().italic(), " : string without spaces".dark_grey());
+                println!("{:62}{}{}", "", "format".dark_grey().italic(), " : one of the following (case-insensitive), default is raw:".dark_grey());
+                println!("{:68}{}{}", "", "raw".dark_grey().italic(), " — 8-byte little-endian integers".dark_grey());
+                println!("{:68}{}{}", "", "pgm".dark_grey().italic(), " — binary greyscale image, pixel per channel".dark_grey());
+                println!("{:68}{}{}", "", "ppm".dark_grey().italic(), " — binary colour image, 3 channels (R, G, B) per pixel".dark_grey());
+                println!("{:68}{}{}", "", "wav".dark_grey().italic(), " — PCM audio, sample (of 1st audio channel) per channel".dark_grey());
+                println!("{:62}{}{}", "", "param".dark_grey().italic(), " : unsigned integer in decimal, 0 (default) means automatic:".dark_grey());
+                println!("{:68}{}", "", "in pgm|ppm|wav — downsampling step".dark_grey());
+                println!("{:68}{}", "", "out pgm|ppm — image width, otherwise square-ish".dark_grey());
+                println!("{:68}{}", "", "out wav — sample rate, otherwise 8000".dark_grey());
                 println!("{:6}{}{}", "", format!("{:56}", "<in|out> del <index>").dark_grey().bold(), "Remove input or output mapping".dark_grey());
                 println!("{:62}{}{}", "", "index".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
                 println!("{:6}{}{}", "", format!("{:56}", "<in|out> list").dark_grey().bold(), "Show all input or output mappings".dark_grey());
+                println!("{:6}{}{}{}{}", "", format!("{:56}", "sens add <sensor> <start> <interval>").dark_grey().bold(), "Add internal sensor mapping, filling channels from ".dark_grey(), "start".dark_grey().italic(), " on".dark_grey());
+                println!("{:62}{}{}", "", "sensor".dark_grey().italic(), " : one of the following (case-insensitive):".dark_grey());
+                println!("{:68}{}{}", "", "time".dark_grey().italic(), " — 1 channel, μs since Unix epoch".dark_grey());
+                println!("{:68}{}{}", "", "age".dark_grey().italic(), " — 1 channel, ticks performed".dark_grey());
+                println!("{:68}{}{}", "", "nodes".dark_grey().italic(), " — 1 channel, number of nodes".dark_grey());
+                println!("{:68}{}{}", "", "ctrls".dark_grey().italic(), " — 1 channel, number of controllers".dark_grey());
+                println!("{:68}{}{}", "", "comms".dark_grey().italic(), " — 1 channel, commands executed since previous update".dark_grey());
+                println!("{:68}{}{}", "", "glitches".dark_grey().italic(), " — 3 channels, background, replicate, construct counts".dark_grey());
+                println!("{:68}{}{}", "", "loadavg".dark_grey().italic(), " — 3 channels, host load average over 1, 5, 15 minutes, ×1000".dark_grey());
+                println!("{:68}{}{}", "", "mem".dark_grey().italic(), " — 2 channels, host total and available memory, KiB".dark_grey());
+                println!("{:68}{}{}", "", "uptime".dark_grey().italic(), " — 1 channel, host uptime, seconds".dark_grey());
+                println!("{:62}{}{}", "", "start".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:62}{}{}", "", "interval".dark_grey().italic(), " : positive integer in decimal (μs)".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "sens del <index>").dark_grey().bold(), "Remove sensor mapping".dark_grey());
+                println!("{:62}{}{}", "", "index".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "sens list").dark_grey().bold(), "Show all sensor mappings".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "gen add <start> <length> <clock> <period> <waveform> [<params>]").dark_grey().bold(), "Add signal generator mapping".dark_grey());
+                println!("{:62}{}{}", "", "start".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:62}{}{}", "", "length".dark_grey().italic(), " : positive integer in decimal, all channels get the same value".dark_grey());
+                println!("{:62}{}{}", "", "clock".dark_grey().italic(), " : tick (age) OR time (μs since Unix epoch)".dark_grey());
+                println!("{:62}{}{}", "", "period".dark_grey().italic(), " : positive integer in decimal, ticks or μs".dark_grey());
+                println!("{:62}{}{}", "", "waveform".dark_grey().italic(), " : one of the following (case-insensitive), with params:".dark_grey());
+                println!("{:68}{}{}", "", "const value".dark_grey().italic(), " — value all the time".dark_grey());
+                println!("{:68}{}{}", "", "sine amplitude [offset]".dark_grey().italic(), " — offset ± amplitude, sinusoidally".dark_grey());
+                println!("{:68}{}{}", "", "square amplitude [offset]".dark_grey().italic(), " — offset + amplitude, then offset - amplitude".dark_grey());
+                println!("{:68}{}{}", "", "saw amplitude [offset]".dark_grey().italic(), " — rising from offset - amplitude to offset + amplitude".dark_grey());
+                println!("{:68}{}{}", "", "walk step [offset]".dark_grey().italic(), " — from offset, randomly ± step once per period".dark_grey());
+                println!("{:68}{}{}", "", "pulse amplitude width [offset]".dark_grey().italic(), " — offset + amplitude during width, offset otherwise".dark_grey());
+                println!("{:68}{}{}", "", "seq value1 value2 ...".dark_grey().italic(), " — next value each period, cyclically".dark_grey());
+                println!("{:62}{}{}", "", "params".dark_grey().italic(), " : integers in decimal, offset defaults to 0".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "gen del <index>").dark_grey().bold(), "Remove generator mapping".dark_grey());
+                println!("{:62}{}{}", "", "index".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:6}{}{}", "", format!("{:56}", "gen list").dark_grey().bold(), "Show all generator mappings".dark_grey());
                 println!("{:6}{}{}", "", format!("{:56}", "update").dark_grey().bold(), "Update, i.e. synchronise, input/output integer channels and files".dark_grey());
             },
 
+            "task" => {
+                println!("{}{}{}{}", format!("{:48}", "task <subcommand> [<parameters>]").dark_grey().bold(), "Execute ".dark_grey(), "subcommand".dark_grey().italic(), " related to tasks".dark_grey());
+                println!("{}", "Available subcommands:".dark_grey());
+                println!("{:6}{}{}", "", format!("{:42}", "add <function> <output> <inputs> <reward> <amount>").dark_grey().bold(), "Add task: reward controller that transmits to output channel the value of function of input channels".dark_grey());
+                println!("{:48}{}{}", "", "function".dark_grey().italic(), " : id, not (1 input); and, or, xor, sum, less, equal, greater (2 inputs)".dark_grey());
+                println!("{:48}{}{}", "", "output".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:48}{}{}", "", "inputs".dark_grey().italic(), " : 1 or 2 unsigned integers in decimal".dark_grey());
+                println!("{:48}{}{}", "", "reward".dark_grey().italic(), " : one of the following (case-insensitive):".dark_grey());
+                println!("{:54}{}{}", "", "ticks".dark_grey().italic(), " — controller gets amount extra ticks".dark_grey());
+                println!("{:54}{}{}", "", "renew".dark_grey().italic(), " — controller survives amount evictions by newer ones".dark_grey());
+                println!("{:48}{}{}", "", "amount".dark_grey().italic(), " : positive integer in decimal".dark_grey());
+                println!("{:6}{}{}", "", format!("{:42}", "del <index>").dark_grey().bold(), "Remove task".dark_grey());
+                println!("{:48}{}{}", "", "index".dark_grey().italic(), " : unsigned integer in decimal".dark_grey());
+                println!("{:6}{}{}", "", format!("{:42}", "list").dark_grey().bold(), "Show all tasks".dark_grey());
+                println!("{:6}{}{}", "", format!("{:42}", "reset").dark_grey().bold(), "Reset attempts and completions counts of all tasks".dark_grey());
+            },
+
             "showsizes" => {
                 println!("{}{}", format!("{:32}", "showsizes").dark_grey().bold(), "Show constant sizes of some Ælhometta- and Controller-related arrays".dark_grey());
             },
 
+            "snap" | "snapshot" => {
+                println!("{}{}{}{}", format!("{:32}", "snapshot <subcommand> [<parameters>]").dark_grey().bold(), "Execute ".dark_grey(), "subcommand".dark_grey().italic(), " related to snapshots of state".dark_grey());
+                println!("{}", "Available subcommands:".dark_grey());
+                println!("{:6}{}{}", "", format!("{:26}", "list").dark_grey().bold(), "Show snapshots, oldest first".dark_grey());
+                println!("{:6}{}{}", "", format!("{:26}", "take").dark_grey().bold(), "Save current state to new snapshot, removing oldest ones beyond snapshots_max".dark_grey());
+                println!("{:6}{}{}", "", format!("{:26}", "restore <index>").dark_grey().bold(), "Replace current state with snapshot, taking snapshot of the former".dark_grey());
+                println!("{:32}{}{}", "", "index".dark_grey().italic(), " : unsigned integer in decimal, as in list".dark_grey());
+            },
+
+            "journal" => {
+                println!("{}{}", format!("{:32}", "journal [compact]").dark_grey().bold(), "Show status of journal of saves, or write new base and empty journal".dark_grey());
+            },
+
+            "export" => {
+                println!("{}{}{}{}", format!("{:32}", "export json <path>").dark_grey().bold(), "Write state to JSON file at ".dark_grey(), "path".dark_grey().italic(), ", secret key excluded".dark_grey());
+                println!("{:32}{}{}", "", "path".dark_grey().italic(), " : must be without spaces".dark_grey());
+            },
+
+            "import" => {
+                println!("{}{}{}{}", format!("{:32}", "import json <path>").dark_grey().bold(), "Replace state with one read from JSON file at ".dark_grey(), "path".dark_grey().italic(), ", keeping secret key, taking snapshot of the former".dark_grey());
+                println!("{:32}{}{}", "", "path".dark_grey().italic(), " : must be without spaces".dark_grey());
+            },
+
+            "merge" => {
+                println!("{}{}{}{}", format!("{:32}", "merge <path> [selection]").dark_grey().bold(), "Copy nodes and controllers of state saved at ".dark_grey(), "path".dark_grey().italic(), " with new uids, as the newest ones, taking snapshot beforehand".dark_grey());
+                println!("{:32}{}{}", "", "path".dark_grey().italic(), " : must be without spaces".dark_grey());
+                println!("{:32}{}{}", "", "selection".dark_grey().italic(), " : none means all nodes and controllers, otherwise only chosen controllers with nodes they can reach:".dark_grey());
+                println!("{:6}{}{}", "", format!("{:26}", "fraction <p>").dark_grey().bold(), "Each controller with probability p, from 0 to 1".dark_grey());
+                println!("{:6}{}{}", "", format!("{:26}", "ctrl <uid> [<uid> ...]").dark_grey().bold(), "Controllers with these uids, in hexadecimal".dark_grey());
+                println!("{:32}{}", "", "Ether, counters, and configuration of current state stay as they are".dark_grey());
+            },
+
             "sets" | "settings" => {
                 println!("{}{}{}{}", format!("{:32}", "settings [setting]").dark_grey().bold(), "Show value(s) of all settings or of ".dark_grey(), "setting".dark_grey().italic(), " only".dark_grey());
                 println!("{:32}{}{}", "", "setting".dark_grey().italic(), " : name of required one. None means all".dark_grey());
@@ -280,6 +404,22 @@ impl Commander {
                 println!("{:32}{}{}", "", "limit".dark_grey().italic(), " : positive integer in decimal, default is full history length".dark_grey());
             },
 
+            "source" => {
+                println!("{}{}{}{}", format!("{:32}", "source <path>").dark_grey().bold(), "Execute commands from file at ".dark_grey(), "path".dark_grey().italic(), ", one per line, as if typed".dark_grey());
+                println!("{:32}{}{}", "", "path".dark_grey().italic(), " : must be without spaces, '-' is stdin".dark_grey());
+                println!("{:32}{}", "", "Empty lines and those beginning with # are skipped; after failed command, script goes on unless script_abort_on_error".dark_grey());
+            },
+
+            "sched" | "schedule" => {
+                println!("{}{}", format!("{:32}", "schedule [list]").dark_grey().bold(), "Show scheduled commands".dark_grey());
+                println!("{}{}{}{}{}", format!("{:32}", "schedule add <moment> [every <n>] <command>").dark_grey().bold(), "Execute ".dark_grey(), "command".dark_grey().italic(), " during run at ".dark_grey(), "moment".dark_grey().italic());
+                println!("{:32}{}{}", "", "moment".dark_grey().italic(), " : age <ticks> OR time <seconds since run began>".dark_grey());
+                println!("{:32}{}{}", "", "n".dark_grey().italic(), " : repeat each n ticks or seconds, otherwise command is removed once executed".dark_grey());
+                println!("{}{}{}", format!("{:32}", "schedule del <index>").dark_grey().bold(), "Remove scheduled command at ".dark_grey(), "index".dark_grey().italic());
+                println!("{}{}", format!("{:32}", "schedule clear").dark_grey().bold(), "Remove all scheduled commands".dark_grey());
+                println!("{:32}{}", "", "e.g. schedule add age 10000000 glitch back 0.001".dark_grey());
+            },
+
             _ => {
                 return Err(format!("No help on this command"));
             }
@@ -0,0 +1,143 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use {
+    crate::{
+        aelhometta::{
+            Ælhometta,
+            CONTENTS
+        },
+        serbin::ToBits
+    },
+    super::Commander
+};
+
+impl Commander {
+    // Non-interactive, before anything is loaded: "aelhometta inspect <file>"
+    pub fn inspect(paramstr: &[&str]) -> Result<(), String> {
+        if paramstr.is_empty() {
+            return Err(String::from("File to inspect not specified"));
+        }
+        let filepath = paramstr[0];
+        let insp = Ælhometta::inspect(filepath)?;
+        let æh = &insp.world;
+
+        println!("{:4}{}", " ", "File".dark_grey());
+        println!("{}{}", format!("{:24}", "Path").dark_grey(), filepath.magenta());
+        println!("{}{}", format!("{:24}", "Size").dark_grey(), format!("{} KiB", insp.file_size >> 10).dark_blue());
+        println!("{}{}", format!("{:24}", "Compressed").dark_grey(), if insp.compressed { "yes".green() } else { "no".dark_yellow() });
+        println!("{}{}", format!("{:24}", "Format").dark_grey(), format!("v{}", &insp.format_version).blue());
+        println!("{}{}", format!("{:24}", "Checksum").dark_grey(), match insp.checksum {
+            Some(checksum) => format!("{:016X}", checksum).green(),
+            None => String::from("none (older format)").dark_yellow()
+        });
+        println!("{}{}", format!("{:24}", "Journal").dark_grey(), match insp.journal_size {
+            Some(size) => format!("{} KiB, not applied", size >> 10).dark_yellow(),
+            None => String::from("none").dark_grey()
+        });
+
+        println!("{:4}{}", " ", "Chains".dark_grey());
+        println!("{}{}", format!("{:24}", "Limit").dark_red(), format!("{}=2^{}", æh.max_num_chains(), æh.max_num_chains_binlog()).red());
+        println!("{}{}", format!("{:24}", "Nodes").dark_magenta(), format!("{}", insp.num_nodes).magenta());
+        println!("{}{}", format!("{:24}", "Nodes in historing").dark_magenta(), format!("{}", insp.nodes_historing_some).magenta());
+        println!("{}{}", format!("{:24}", "Controllers").dark_cyan(), format!("{}", insp.num_controllers).cyan());
+        println!("{}{}", format!("{:24}", "Ctrls in historing").dark_cyan(), format!("{}", insp.controllers_historing_some).cyan());
+        println!("{}{}", format!("{:24}", "Generation").dark_cyan(), format!("{} / {} / {}", insp.generations.minimum, insp.generations.average, insp.generations.maximum).cyan());
+
+        println!("{:4}{}", " ", "Counts".dark_grey());
+        println!("{}{}", format!("{:24}", "Age").dark_blue(), format!("{}", æh.age()).blue());
+        println!("{}{}", format!("{:24}", "Spaces").dark_yellow(), format!("{}", æh.spaces_count()).blue());
+        println!("{}{}", format!("{:24}", "Branches (main)").dark_magenta(), format!("{}", æh.branches_main_count()).blue());
+        println!("{}{}", format!("{:24}", "Branches (alt)").dark_magenta(), format!("{}", æh.branches_alt_count()).blue());
+        println!("{}{}", format!("{:24}", "Commands").dark_yellow(), format!("{}", æh.commands_count().values().sum::<u128>()).blue());
+        println!("{}{}", format!("{:24}", "Constructions").dark_yellow(), format!("{}", æh.constructions_count().values().sum::<u128>()).blue());
+        let switched_off: Vec<String> = CONTENTS.iter().filter_map(|content| match content.to_bits() {
+            b if (b < 0x80) && !æh.commandswitch(b) => Some(format!("{:?}", content)),
+            _ => None
+        }).collect();
+        println!("{}{}", format!("{:24}", "Commands off").dark_red(), if switched_off.is_empty() { String::from("none").dark_grey() } else { switched_off.join(" ").red() });
+
+        println!("{:4}{}", " ", "Ether".dark_grey());
+        println!("{}{}", format!("{:24}", "Optuid non-none").dark_magenta(), format!("{} / {}", insp.channels.optuids_some, insp.ether_optuids).magenta());
+        println!("{}{}", format!("{:24}", "Integer non-zero").dark_blue(), format!("{} / {}", insp.channels.integers_nonzero, insp.ether_integers).blue());
+
+        println!("{:4}{}", " ", "Glitches".dark_grey());
+        for (kind, prob, count) in [
+            ("Background", æh.glitch_background_prob(), æh.glitch_background_count()),
+            ("Replicate", æh.glitch_replicate_prob(), æh.glitch_replicate_count()),
+            ("Construct", æh.glitch_construct_prob(), æh.glitch_construct_count())
+        ] {
+            println!("{}{}", format!("{:24}", kind).dark_red(), format!("{:e}, {} occurred", prob, count).red());
+        }
+
+        println!("{:4}{}", " ", "Peer".dark_grey());
+        println!("{}{}", format!("{:24}", "Exposed").dark_grey(), if æh.exposed() { "yes, not now".yellow() } else { "no".dark_grey() });
+        println!("{}{}", format!("{:24}", "Port").dark_grey(), format!("{}", æh.port()).green());
+        println!("{}{}", format!("{:24}", "Tor proxy").dark_grey(), format!("{}:{}", æh.torproxy_host(), æh.torproxy_port()).green());
+        println!("{}{}", format!("{:24}", "Share").dark_grey(), format!("{} channels every {} μs", æh.share_size(), æh.share_interval()).green());
+        println!("{}{}", format!("{:24}", "Incoming").dark_grey(), format!("{} permitted of {} attempted", æh.in_permitted_num(), æh.in_attempted_num()).green());
+        for op in æh.other_peers() {
+            println!("{}{}", format!("{:24}", "Other peer").dark_grey(), format!("{} {}:{}", op.publickey(), op.onion(), op.port()).cyan());
+        }
+
+        println!("{:4}{}", " ", "Mappings".dark_grey());
+        for om in æh.output_mappings() {
+            println!("{}{}", format!("{:24}", "Output").dark_blue(), format!("{}+{} → {} ({} {}) every {} μs", om.start(), om.length(), om.filepath(), om.format().name(), om.format_param(), om.interval()).blue());
+        }
+        for im in æh.input_mappings() {
+            println!("{}{}", format!("{:24}", "Input").dark_blue(), format!("{}+{} ← {} ({} {}) every {} μs", im.start(), im.length(), im.filepath(), im.format().name(), im.format_param(), im.interval()).blue());
+        }
+        for sm in æh.sensor_mappings() {
+            println!("{}{}", format!("{:24}", "Sensor").dark_yellow(), format!("{}+{} ← {} every {} μs", sm.start(), sm.sensor().length(), sm.sensor().name(), sm.interval()).yellow());
+        }
+        for gm in æh.generator_mappings() {
+            println!("{}{}", format!("{:24}", "Generator").dark_green(), format!("{}+{} ← {} by {} with period {}", gm.start(), gm.length(), gm.waveform().name(), gm.clock().name(), gm.period()).green());
+        }
+        for task in æh.tasks() {
+            println!("{}{}", format!("{:24}", "Task").dark_cyan(), format!("{} {} ← {}, {} of {} completed", task.function().name(), task.output(), task.inputs().iter().map(|chan| chan.to_string()).collect::<Vec<String>>().join(" "), task.completions_count(), task.attempts_count()).cyan());
+        }
+
+        println!("{:4}{}", " ", "Contents".dark_grey());
+        for content in CONTENTS {
+            let count = * insp.contents.get(&content).unwrap_or(&0);
+            println!("{}{}{}{}",
+                format!("{:<4}", format!("{:02X}", content.to_bits())).blue(),
+                format!("{:<40}", format!("{:?}", content)).yellow(),
+                format!("{:>12}", count).dark_yellow(),
+                format!("{:>11.3} %", (count as f64) * 100.0 / (insp.num_nodes.max(1) as f64)).dark_blue()
+            );
+        }
+
+        Ok(())
+    }
+
+}
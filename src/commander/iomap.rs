@@ -32,14 +32,180 @@ use chrono::prelude::*;
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use {
-    crate::aelhometta::Ælhometta,
+    crate::aelhometta::{
+        Clock,
+        FileFormat,
+        GeneratorMapping,
+        Integer,
+        IntegersFileMapping,
+        Sensor,
+        SensorMapping,
+        Waveform,
+        Ælhometta,
+        FILE_FORMATS,
+        SENSORS,
+        WAVEFORMS
+    },
     super::{
         Commander,
         ParseErrorPrefixise
     }
 };
 
+// Optional "[format [param]]" after filepath
+
+fn parse_file_format(paramstr: &[&str]) -> Result<(FileFormat, usize), String> {
+    let format = match paramstr.first() {
+        Some(name) => match FileFormat::from_name(& name.to_lowercase()) {
+            Some(format) => format,
+            None => {
+                return Err(format!("Unknown format '{}', must be one of: {}", name, FILE_FORMATS.iter().map(|(_, n)| *n).collect::<Vec<&str>>().join(", ")));
+            }
+        },
+        None => FileFormat::Raw
+    };
+    let format_param = match paramstr.get(1) {
+        Some(ps) => match ps.parse::<usize>() {
+            Ok(p) => p,
+            Err(err) => {
+                return err.prefixised("format parameter");
+            }
+        },
+        None => 0
+    };
+    Ok((format, format_param))
+}
+
+#[derive(Serialize)]
+pub(super) struct FileMappingView {
+    start: usize,
+    length: usize,
+    interval: i64,
+    filepath: String,
+    format: &'static str,
+    format_param: usize,
+    ut_last_update: i64 // microseconds since Unix epoch
+}
+
+#[derive(Serialize)]
+pub(super) struct SensorMappingView {
+    sensor: &'static str,
+    start: usize,
+    length: usize,
+    interval: i64,
+    ut_last_update: i64
+}
+
+// Parameters that the waveform does not use are absent
+#[derive(Serialize)]
+pub(super) struct GeneratorMappingView<'a> {
+    waveform: &'static str,
+    start: usize,
+    length: usize,
+    clock: &'static str,
+    period: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    values: Option<&'a [Integer]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amplitude: Option<Integer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<Integer>,
+    value: Integer
+}
+
+pub(super) fn file_mappings_view(mappings: &[IntegersFileMapping]) -> Vec<FileMappingView> {
+    mappings.iter().map(|fm| FileMappingView {
+        start: fm.start(),
+        length: fm.length(),
+        interval: fm.interval(),
+        filepath: fm.filepath(),
+        format: fm.format().name(),
+        format_param: fm.format_param(),
+        ut_last_update: fm.ut_last_update()
+    }).collect()
+}
+
+pub(super) fn sensor_mappings_view(mappings: &[SensorMapping]) -> Vec<SensorMappingView> {
+    mappings.iter().map(|sm| SensorMappingView {
+        sensor: sm.sensor().name(),
+        start: sm.start(),
+        length: sm.sensor().length(),
+        interval: sm.interval(),
+        ut_last_update: sm.ut_last_update()
+    }).collect()
+}
+
+pub(super) fn generator_mappings_view(mappings: &[GeneratorMapping]) -> Vec<GeneratorMappingView<'_>> {
+    mappings.iter().map(|gm| GeneratorMappingView {
+        waveform: gm.waveform().name(),
+        start: gm.start(),
+        length: gm.length(),
+        clock: gm.clock().name(),
+        period: gm.period(),
+        values: (gm.waveform() == Waveform::Sequence).then_some(gm.values().as_slice()),
+        amplitude: (!matches!(gm.waveform(), Waveform::Constant | Waveform::Sequence)).then_some(gm.amplitude()),
+        width: (gm.waveform() == Waveform::Pulse).then_some(gm.width()),
+        offset: (gm.waveform() != Waveform::Sequence).then_some(gm.offset()),
+        value: gm.value()
+    }).collect()
+}
+
+fn print_utc_micros(label: &str, ut: i64) {
+    println!("{}{}", format!("{:24}", label).dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(ut).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (ut / 1000) % 1000).green());
+}
+
+fn print_file_mappings_view(views: &[FileMappingView], heading: &str) {
+    for (i, fmv) in views.iter().enumerate() {
+        println!("{:4}{}", " ", format!("{} {}", heading, i).dark_grey());
+        println!("{}{}", format!("{:24}", "Start").dark_blue(), format!("{}", fmv.start).blue());
+        println!("{}{}", format!("{:24}", "Length").dark_blue(), format!("{}", fmv.length).blue());
+        println!("{}{}", format!("{:24}", "Interval (μs)").dark_green(), format!("{}", fmv.interval).green());
+        println!("{}{}", format!("{:24}", "Filepath").dark_magenta(), fmv.filepath.as_str().magenta());
+        println!("{}{}", format!("{:24}", "Format").dark_magenta(), format!("{} {}", fmv.format, fmv.format_param).magenta());
+        print_utc_micros("Last update", fmv.ut_last_update);
+    }
+}
+
+fn print_sensor_mappings_view(views: &[SensorMappingView]) {
+    for (i, smv) in views.iter().enumerate() {
+        println!("{:4}{}", " ", format!("Sensor mapping {}", i).dark_grey());
+        println!("{}{}", format!("{:24}", "Sensor").dark_yellow(), smv.sensor.yellow());
+        println!("{}{}", format!("{:24}", "Start").dark_blue(), format!("{}", smv.start).blue());
+        println!("{}{}", format!("{:24}", "Length").dark_blue(), format!("{}", smv.length).blue());
+        println!("{}{}", format!("{:24}", "Interval (μs)").dark_green(), format!("{}", smv.interval).green());
+        print_utc_micros("Last update", smv.ut_last_update);
+    }
+}
+
+fn print_generator_mappings_view(views: &[GeneratorMappingView]) {
+    for (i, gmv) in views.iter().enumerate() {
+        println!("{:4}{}", " ", format!("Generator mapping {}", i).dark_grey());
+        println!("{}{}", format!("{:24}", "Waveform").dark_yellow(), gmv.waveform.yellow());
+        println!("{}{}", format!("{:24}", "Start").dark_blue(), format!("{}", gmv.start).blue());
+        println!("{}{}", format!("{:24}", "Length").dark_blue(), format!("{}", gmv.length).blue());
+        println!("{}{}", format!("{:24}", "Clock").dark_green(), gmv.clock.green());
+        println!("{}{}", format!("{:24}", "Period").dark_green(), format!("{}", gmv.period).green());
+        if let Some(values) = gmv.values {
+            println!("{}{}", format!("{:24}", "Values").dark_blue(), values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" ").blue());
+        }
+        if let Some(amplitude) = gmv.amplitude {
+            println!("{}{}", format!("{:24}", "Amplitude").dark_blue(), format!("{}", amplitude).blue());
+        }
+        if let Some(width) = gmv.width {
+            println!("{}{}", format!("{:24}", "Width").dark_green(), format!("{}", width).green());
+        }
+        if let Some(offset) = gmv.offset {
+            println!("{}{}", format!("{:24}", "Offset").dark_blue(), format!("{}", offset).blue());
+        }
+        println!("{}{}", format!("{:24}", "Last value").dark_blue(), format!("{}", gmv.value).blue());
+    }
+}
+
 impl Commander {
     pub fn iomap(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() > 0 {
@@ -60,7 +226,8 @@ impl Commander {
                                                             match paramstr[4].parse::<i64>() {
                                                                 Ok(interval) => {
                                                                     if paramstr.len() > 5 {
-                                                                        æh.iomap_out_add(start, length, interval, paramstr[5])?;
+                                                                        let (format, format_param) = parse_file_format(& paramstr[6..])?;
+                                                                        æh.iomap_out_add(start, length, interval, paramstr[5], format, format_param)?;
                                                                         println!("{}", "Mapping added".green());
                                                                         Ok(())
                                                                     } else {
@@ -102,15 +269,7 @@ impl Commander {
                             },
 
                             "list" => {
-                                for (i, om) in æh.output_mappings().iter().enumerate() {
-                                    println!("{:4}{}", " ", format!("Output mapping {}", i).dark_grey());
-                                    println!("{}{}", format!("{:24}", "Start").dark_blue(), format!("{}", om.start()).blue());
-                                    println!("{}{}", format!("{:24}", "Length").dark_blue(), format!("{}", om.length()).blue());
-                                    println!("{}{}", format!("{:24}", "Interval (μs)").dark_green(), format!("{}", om.interval()).green());
-                                    println!("{}{}", format!("{:24}", "Filepath").dark_magenta(), format!("{}", & om.filepath()).magenta());
-                                    println!("{}{}", format!("{:24}", "Last update").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(om.ut_last_update()).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (om.ut_last_update() / 1000) % 1000).green());
-                                }
-                                Ok(())
+                                self.present(&file_mappings_view(æh.output_mappings()), |views| print_file_mappings_view(views, "Output mapping"))
                             },
 
                             _ => Err(String::from("Unknown subsubcommand"))
@@ -135,7 +294,8 @@ impl Commander {
                                                             match paramstr[4].parse::<i64>() {
                                                                 Ok(interval) => {
                                                                     if paramstr.len() > 5 {
-                                                                        æh.iomap_in_add(start, length, interval, paramstr[5])?;
+                                                                        let (format, format_param) = parse_file_format(& paramstr[6..])?;
+                                                                        æh.iomap_in_add(start, length, interval, paramstr[5], format, format_param)?;
                                                                         println!("{}", "Mapping added".green());
                                                                         Ok(())
                                                                     } else {
@@ -177,15 +337,150 @@ impl Commander {
                             },
 
                             "list" => {
-                                for (i, im) in æh.input_mappings().iter().enumerate() {
-                                    println!("{:4}{}", " ", format!("Input mapping {}", i).dark_grey());
-                                    println!("{}{}", format!("{:24}", "Start").dark_blue(), format!("{}", im.start()).blue());
-                                    println!("{}{}", format!("{:24}", "Length").dark_blue(), format!("{}", im.length()).blue());
-                                    println!("{}{}", format!("{:24}", "Interval (μs)").dark_green(), format!("{}", im.interval()).green());
-                                    println!("{}{}", format!("{:24}", "Filepath").dark_magenta(), format!("{}", & im.filepath()).magenta());
-                                    println!("{}{}", format!("{:24}", "Last update").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(im.ut_last_update()).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (im.ut_last_update() / 1000) % 1000).green());
+                                self.present(&file_mappings_view(æh.input_mappings()), |views| print_file_mappings_view(views, "Input mapping"))
+                            },
+
+                            _ => Err(String::from("Unknown subsubcommand"))
+                        }
+                    } else {
+                        Err(String::from("Subsubcommand not specified"))
+                    }
+                },
+
+                "sens" | "sensor" => {
+                    if paramstr.len() > 1 {
+                        let subcommand = paramstr[1].to_lowercase();
+                        match subcommand.as_str() {
+                            "add" => {
+                                if paramstr.len() > 2 {
+                                    let sensor_name = paramstr[2].to_lowercase();
+                                    match Sensor::from_name(&sensor_name) {
+                                        Some(sensor) => {
+                                            if paramstr.len() > 3 {
+                                                match paramstr[3].parse::<usize>() {
+                                                    Ok(start) => {
+                                                        if paramstr.len() > 4 {
+                                                            match paramstr[4].parse::<i64>() {
+                                                                Ok(interval) => {
+                                                                    æh.iomap_sens_add(sensor, start, interval)?;
+                                                                    println!("{}", "Mapping added".green());
+                                                                    Ok(())
+                                                                },
+                                                                Err(err) => err.prefixised("mapping interval")
+                                                            }
+                                                        } else {
+                                                            Err(String::from("Mapping interval not specified"))
+                                                        }
+                                                    },
+                                                    Err(err) => err.prefixised("range start")
+                                                }
+                                            } else {
+                                                Err(String::from("Range start not specified"))
+                                            }
+                                        },
+                                        None => Err(format!("Unknown sensor '{}', available are: {}", sensor_name, SENSORS.iter().map(|(_, name)| *name).collect::<Vec<&str>>().join(", ")))
+                                    }
+                                } else {
+                                    Err(String::from("Sensor not specified"))
+                                }
+                            },
+
+                            "del" => {
+                                if paramstr.len() > 2 {
+                                    match paramstr[2].parse::<usize>() {
+                                        Ok(index) => {
+                                            æh.iomap_sens_del(index)?;
+                                            println!("{}", "Mapping removed".green());
+                                            Ok(())
+                                        },
+                                        Err(err) => err.prefixised("index")
+                                    }
+                                } else {
+                                    Err(String::from("Index not specified"))
+                                }
+                            },
+
+                            "list" => {
+                                self.present(&sensor_mappings_view(æh.sensor_mappings()), |views| print_sensor_mappings_view(views))
+                            },
+
+                            _ => Err(String::from("Unknown subsubcommand"))
+                        }
+                    } else {
+                        Err(String::from("Subsubcommand not specified"))
+                    }
+                },
+
+                "gen" | "generator" => {
+                    if paramstr.len() > 1 {
+                        let subcommand = paramstr[1].to_lowercase();
+                        match subcommand.as_str() {
+                            "add" => {
+                                if paramstr.len() > 2 {
+                                    let start = match paramstr[2].parse::<usize>() {
+                                        Ok(start) => start,
+                                        Err(err) => return err.prefixised("range start")
+                                    };
+                                    if paramstr.len() > 3 {
+                                        let length = match paramstr[3].parse::<usize>() {
+                                            Ok(length) => length,
+                                            Err(err) => return err.prefixised("range length")
+                                        };
+                                        if paramstr.len() > 4 {
+                                            let clock_name = paramstr[4].to_lowercase();
+                                            let clock = Clock::from_name(&clock_name).ok_or(format!("Unknown clock '{}', available are: tick, time", clock_name))?;
+                                            if paramstr.len() > 5 {
+                                                let period = match paramstr[5].parse::<i64>() {
+                                                    Ok(period) => period,
+                                                    Err(err) => return err.prefixised("period")
+                                                };
+                                                if paramstr.len() > 6 {
+                                                    let waveform_name = paramstr[6].to_lowercase();
+                                                    let waveform = Waveform::from_name(&waveform_name).ok_or(format!("Unknown waveform '{}', available are: {}", waveform_name, WAVEFORMS.iter().map(|(_, name)| *name).collect::<Vec<&str>>().join(", ")))?;
+                                                    let mut params = Vec::<Integer>::with_capacity(paramstr.len() - 7);
+                                                    for ps in & paramstr[7..] {
+                                                        match ps.parse::<Integer>() {
+                                                            Ok(p) => params.push(p),
+                                                            Err(err) => return err.prefixised("waveform parameter")
+                                                        }
+                                                    }
+                                                    æh.iomap_gen_add(waveform, start, length, clock, period, &params)?;
+                                                    println!("{}", "Mapping added".green());
+                                                    Ok(())
+                                                } else {
+                                                    Err(String::from("Waveform not specified"))
+                                                }
+                                            } else {
+                                                Err(String::from("Period not specified"))
+                                            }
+                                        } else {
+                                            Err(String::from("Clock not specified"))
+                                        }
+                                    } else {
+                                        Err(String::from("Range length not specified"))
+                                    }
+                                } else {
+                                    Err(String::from("Range start not specified"))
+                                }
+                            },
+
+                            "del" => {
+                                if paramstr.len() > 2 {
+                                    match paramstr[2].parse::<usize>() {
+                                        Ok(index) => {
+                                            æh.iomap_gen_del(index)?;
+                                            println!("{}", "Mapping removed".green());
+                                            Ok(())
+                                        },
+                                        Err(err) => err.prefixised("index")
+                                    }
+                                } else {
+                                    Err(String::from("Index not specified"))
                                 }
-                                Ok(())
+                            },
+
+                            "list" => {
+                                self.present(&generator_mappings_view(æh.generator_mappings()), |views| print_generator_mappings_view(views))
                             },
 
                             _ => Err(String::from("Unknown subsubcommand"))
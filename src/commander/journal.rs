@@ -0,0 +1,84 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use std::fs;
+
+use {
+    crate::aelhometta::{
+        journal_filepath,
+        Ælhometta
+    },
+    super::Commander
+};
+
+impl Commander {
+    fn print_journal_status(&self, æh: &Ælhometta) {
+        println!("{}{}", format!("{:24}", "Journal").dark_grey(), if self.settings.journal { "on".green().bold() } else { "off".dark_yellow().bold() });
+        match æh.journal_base() {
+            Some(filepath) => {
+                let size = fs::metadata(journal_filepath(filepath)).map_or(0, |md| md.len());
+                let (nodes, ctrls) = æh.journal_pending();
+                println!("{}{}", format!("{:24}", "Base").dark_grey(), filepath.magenta());
+                println!("{}{}", format!("{:24}", "Deltas").dark_grey(), format!("{} of {}", æh.journal_deltas(), self.settings.journal_max_deltas).blue());
+                println!("{}{}", format!("{:24}", "Journal size").dark_grey(), format!("{} KiB", size >> 10).dark_blue());
+                println!("{}{}", format!("{:24}", "Pending nodes").dark_grey(), format!("{}", nodes).cyan());
+                println!("{}{}", format!("{:24}", "Pending controllers").dark_grey(), format!("{}", ctrls).cyan());
+            },
+            None => {
+                println!("{}", "Not tracking changes, next save is full".dark_grey());
+            }
+        }
+    }
+
+    pub fn journal(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
+        if !paramstr.is_empty() {
+            let command = paramstr[0].to_lowercase();
+            match command.as_str() {
+                "compact" => {
+                    if self.settings.journal {
+                        æh.journal_compact(&self.world_filepath, self.settings.compress_saves)?;
+                        println!("{} {}", "New base written:".green(), self.world_filepath.as_str().green().bold());
+                        Ok(())
+                    } else {
+                        Err(String::from("Journal is off, see 'journal' setting"))
+                    }
+                },
+
+                _ => Err(String::from("Unknown subcommand"))
+            }
+        } else {
+            self.print_journal_status(æh);
+            Ok(())
+        }
+    }
+
+}
@@ -0,0 +1,102 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use {
+    crate::aelhometta::{
+        MergeSelection,
+        Uid,
+        Ælhometta
+    },
+    super::{
+        Commander,
+        ParseErrorPrefixise,
+        ParseHex
+    }
+};
+
+impl Commander {
+    pub fn merge(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
+        if !paramstr.is_empty() {
+            let filepath = paramstr[0];
+            let selection = if paramstr.len() >= 2 {
+                match paramstr[1].to_lowercase().as_str() {
+                    "fraction" => {
+                        if paramstr.len() >= 3 {
+                            match paramstr[2].parse::<f64>() {
+                                Ok(p) if (0.0..=1.0).contains(&p) => MergeSelection::Fraction(p),
+                                Ok(_) => return Err(String::from("Fraction must be from 0 to 1")),
+                                Err(err) => return err.prefixised("fraction")
+                            }
+                        } else {
+                            return Err(String::from("Fraction not specified"));
+                        }
+                    },
+                    "ctrl" => {
+                        if paramstr.len() >= 3 {
+                            let mut cuids = Vec::with_capacity(paramstr.len() - 2);
+                            for s in & paramstr[2..] {
+                                match s.parse_hex::<Uid>() {
+                                    Ok(cuid) => cuids.push(cuid),
+                                    Err(err) => return err.prefixised("ctrl uid")
+                                }
+                            }
+                            MergeSelection::Controllers(cuids)
+                        } else {
+                            return Err(String::from("Controllers not specified"));
+                        }
+                    },
+                    _ => return Err(String::from("Unknown selection"))
+                }
+            } else {
+                MergeSelection::All
+            };
+            let other = Ælhometta::load_unexposed(filepath)?;
+            self.snapshot_before(æh)?;
+            let outcome = æh.merge(&other, &selection);
+            println!("{} {}", "Merged from".green(), filepath.green().bold());
+            println!("{}{}", format!("{:24}", "Nodes copied").dark_grey(), outcome.nodes_copied.to_string().magenta());
+            println!("{}{}", format!("{:24}", "Controllers copied").dark_grey(), outcome.controllers_copied.to_string().cyan());
+            if (outcome.nodes_skipped > 0) || (outcome.controllers_skipped > 0) {
+                println!("{}{} {} {}",
+                    format!("{:24}", "Skipped over limit").dark_grey(),
+                    outcome.nodes_skipped.to_string().dark_magenta(),
+                    "/".dark_grey(),
+                    outcome.controllers_skipped.to_string().dark_cyan()
+                );
+            }
+            Ok(())
+        } else {
+            Err(String::from("Path not specified"))
+        }
+    }
+
+}
@@ -0,0 +1,344 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Read-only HTTP server on localhost for watching long runs from browser or dashboard.
+// Its thread only serves documents prepared beforehand; they are prepared by the thread that ticks,
+// between ticks, no more often than monitor_interval and only if someone has asked since the last time
+
+use serde::Serialize;
+
+use std::{
+    io::{
+        ErrorKind,
+        Read,
+        Write
+    },
+    net::{
+        Ipv4Addr,
+        TcpListener,
+        TcpStream
+    },
+    sync::{
+        atomic::{
+            AtomicBool,
+            Ordering
+        },
+        Arc,
+        Mutex
+    },
+    thread::{
+        self,
+        JoinHandle
+    },
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+        UNIX_EPOCH
+    }
+};
+
+use {
+    crate::aelhometta::{
+        ControllerGenerationStatistics,
+        Ælhometta
+    },
+    super::{
+        iomap::{
+            file_mappings_view,
+            generator_mappings_view,
+            sensor_mappings_view,
+            FileMappingView,
+            GeneratorMappingView,
+            SensorMappingView
+        },
+        peer::peer_view,
+        statistics::{
+            chan_stats_view,
+            cont_stats_view,
+            tick_stats_view,
+            ChanStatsView,
+            ContStatsView,
+            TickStatsView
+        },
+        Commander
+    }
+};
+
+const MONITOR_ACCEPT_SLEEP: Duration = Duration::from_millis(50);
+const MONITOR_READ_TIMEOUT: Duration = Duration::from_secs(2);
+const MONITOR_MAX_REQUEST_LEN: usize = 0x2000;
+
+const MONITOR_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Ælhometta</title>
+<style>
+body { font-family: monospace; background: #111; color: #ccc; margin: 2em; }
+h1 { color: #fff; font-size: 1.2em; }
+h2 { color: #888; font-size: 1em; margin-top: 1.5em; }
+td { padding: 0 1.5em 0 0; }
+td.n { text-align: right; color: #8af; }
+#error { color: #f66; }
+</style>
+</head>
+<body>
+<h1>Ælhometta</h1>
+<div id="error"></div>
+<h2>State</h2><table id="state"></table>
+<h2>Generations</h2><table id="generations"></table>
+<h2>Channels</h2><table id="channels"></table>
+<h2>Contents</h2><table id="contents"></table>
+<h2>Peers</h2><table id="peer"></table>
+<h2>Mappings</h2><table id="iomap"></table>
+<script>
+function fill(id, rows) {
+    document.getElementById(id).innerHTML = rows.map(r => "<tr><td>" + r[0] + "</td><td class=\"n\">" + r[1] + "</td></tr>").join("");
+}
+async function poll() {
+    try {
+        const [state, stats, peer, iomap] = await Promise.all(["state", "statistics", "peer", "iomap"].map(p => fetch(p).then(r => r.json())));
+        fill("state", [["Age", state.age], ["Nodes", state.num_nodes], ["Controllers", state.num_controllers], ["Limit", state.max_num_chains], ["Memory, MiB", state.mem_usage >> 20], ["As of", new Date(state.ut_refresh / 1000).toISOString()]]);
+        fill("generations", [["Minimum", stats.generations.minimum], ["Average", stats.generations.average], ["Maximum", stats.generations.maximum]]);
+        fill("channels", [["Optuid non-none", stats.channels.optuids_some + " / " + stats.channels.optuids], ["Integer non-zero", stats.channels.integers_nonzero + " / " + stats.channels.integers]]);
+        fill("contents", stats.contents.contents.filter(c => c.count > 0).sort((a, b) => b.count - a.count).map(c => [c.content, c.count]));
+        fill("peer", [["Exposed", peer.exposed], ["Share size", peer.share_size]].concat(peer.other_peers.map(op => [op.onion, op.share_size])));
+        fill("iomap", [["Output", iomap.output.length], ["Input", iomap.input.length], ["Sensor", iomap.sensor.length], ["Generator", iomap.generator.length]]);
+        document.getElementById("error").textContent = "";
+    } catch (err) {
+        document.getElementById("error").textContent = "No answer: " + err;
+    }
+}
+poll();
+setInterval(poll, 2000);
+</script>
+</body>
+</html>
+"#;
+
+#[derive(Serialize)]
+struct StateView {
+    age: u128,
+    num_nodes: usize,
+    num_controllers: usize,
+    max_num_chains: usize,
+    mem_usage: usize, // bytes, approximately
+    ut_refresh: i64 // microseconds since Unix epoch, when these documents were prepared
+}
+
+#[derive(Serialize)]
+struct StatisticsView {
+    contents: ContStatsView,
+    generations: ControllerGenerationStatistics,
+    channels: ChanStatsView,
+    ticks: TickStatsView // commands and constructions counts
+}
+
+#[derive(Serialize)]
+struct IomapView<'a> {
+    output: Vec<FileMappingView>,
+    input: Vec<FileMappingView>,
+    sensor: Vec<SensorMappingView>,
+    generator: Vec<GeneratorMappingView<'a>>
+}
+
+#[derive(Default)]
+struct Documents {
+    state: String,
+    statistics: String,
+    peer: String,
+    iomap: String
+}
+
+pub(super) struct Monitor {
+    port: u16,
+    documents: Arc<Mutex<Documents>>,
+    wanted: Arc<AtomicBool>, // asked for since the last refresh
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>, // none if could not listen
+    last_refresh: Option<Instant>
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let head = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n", status, content_type, body.len());
+    stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(body.as_bytes())).unwrap_or(());
+}
+
+fn serve_one(mut stream: TcpStream, documents: &Mutex<Documents>, wanted: &AtomicBool) {
+    if stream.set_nonblocking(false).and_then(|_| stream.set_read_timeout(Some(MONITOR_READ_TIMEOUT))).is_err() {
+        return;
+    }
+    let mut request = Vec::new();
+    let mut buf = [0u8; 0x400];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && (request.len() < MONITOR_MAX_REQUEST_LEN) {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => request.extend_from_slice(&buf[..n])
+        }
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut words = request.lines().next().unwrap_or("").split_whitespace();
+    let method = words.next().unwrap_or("");
+    let path = words.next().unwrap_or("").split('?').next().unwrap_or("");
+    if method != "GET" {
+        respond(&mut stream, "405 Method Not Allowed", "text/plain", "Only GET\n");
+        return;
+    }
+    let document = match path {
+        "/" | "/index.html" => {
+            respond(&mut stream, "200 OK", "text/html; charset=utf-8", MONITOR_PAGE);
+            return;
+        },
+        "/state" => |docs: &Documents| docs.state.clone(),
+        "/statistics" => |docs: &Documents| docs.statistics.clone(),
+        "/peer" => |docs: &Documents| docs.peer.clone(),
+        "/iomap" => |docs: &Documents| docs.iomap.clone(),
+        _ => {
+            respond(&mut stream, "404 Not Found", "text/plain", "Not found\n");
+            return;
+        }
+    };
+    wanted.store(true, Ordering::Relaxed);
+    let body = match documents.lock() {
+        Ok(docs) => document(&docs),
+        Err(_) => String::from("{}")
+    };
+    respond(&mut stream, "200 OK", "application/json", &body);
+}
+
+fn serve(listener: TcpListener, documents: Arc<Mutex<Documents>>, wanted: Arc<AtomicBool>, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => serve_one(stream, &documents, &wanted),
+            Err(ref err) if err.kind() == ErrorKind::WouldBlock => thread::sleep(MONITOR_ACCEPT_SLEEP),
+            Err(_) => thread::sleep(MONITOR_ACCEPT_SLEEP)
+        }
+    }
+}
+
+impl Monitor {
+    fn start(port: u16) -> Result<Self, (Self, String)> {
+        let mut monitor = Self {
+            port,
+            documents: Arc::new(Mutex::new(Documents::default())),
+            wanted: Arc::new(AtomicBool::new(true)),
+            stop: Arc::new(AtomicBool::new(false)),
+            thread: None,
+            last_refresh: None
+        };
+        let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).and_then(|listener| listener.set_nonblocking(true).map(|_| listener)) {
+            Ok(listener) => listener,
+            Err(err) => {
+                return Err((monitor, format!("Cannot listen at port {}: {}", port, &err)));
+            }
+        };
+        let (documents, wanted, stop) = (monitor.documents.clone(), monitor.wanted.clone(), monitor.stop.clone());
+        monitor.thread = Some(thread::spawn(move || serve(listener, documents, wanted, stop)));
+        Ok(monitor)
+    }
+
+    fn refresh(&mut self, æh: &Ælhometta) -> Result<(), String> {
+        let state = StateView {
+            age: æh.age(),
+            num_nodes: æh.num_nodes(),
+            num_controllers: æh.num_controllers(),
+            max_num_chains: æh.max_num_chains(),
+            mem_usage: æh.mem_usage(),
+            ut_refresh: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |dur| dur.as_micros() as i64)
+        };
+        let statistics = StatisticsView {
+            contents: cont_stats_view(æh),
+            generations: æh.generation_statistics(),
+            channels: chan_stats_view(æh),
+            ticks: tick_stats_view(æh)
+        };
+        let iomap = IomapView {
+            output: file_mappings_view(æh.output_mappings()),
+            input: file_mappings_view(æh.input_mappings()),
+            sensor: sensor_mappings_view(æh.sensor_mappings()),
+            generator: generator_mappings_view(æh.generator_mappings())
+        };
+        let documents = Documents {
+            state: serde_json::to_string(&state).map_err(|err| err.to_string())?,
+            statistics: serde_json::to_string(&statistics).map_err(|err| err.to_string())?,
+            peer: serde_json::to_string(&peer_view(æh, false)).map_err(|err| err.to_string())?,
+            iomap: serde_json::to_string(&iomap).map_err(|err| err.to_string())?
+        };
+        *self.documents.lock().map_err(|_| String::from("Monitor thread has panicked"))? = documents;
+        Ok(())
+    }
+
+}
+
+impl Drop for Monitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap_or(());
+        }
+    }
+}
+
+impl Commander {
+    // Port given by command line, if any, else by setting; 0 if none
+    fn monitor_port(&self) -> u16 {
+        self.monitor_port.unwrap_or(self.settings.monitor_port)
+    }
+
+    // Starts or stops server as port requires, and refreshes its documents if due.
+    // Called between ticks and before shell prompt
+    pub(super) fn monitor_update(&mut self, æh: &Ælhometta) -> Result<(), String> {
+        let port = self.monitor_port();
+        if self.monitor.as_ref().map_or(0, |monitor| monitor.port) != port {
+            self.monitor = None;
+            if port != 0 {
+                match Monitor::start(port) {
+                    Ok(monitor) => {
+                        self.monitor = Some(monitor);
+                    },
+                    Err((monitor, err)) => {
+                        self.monitor = Some(monitor); // not to try again with the same port
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        if let Some(monitor) = self.monitor.as_mut() {
+            let due = monitor.last_refresh.is_none_or(|last| last.elapsed().as_secs() >= self.settings.monitor_interval as u64);
+            if monitor.thread.is_some() && due && monitor.wanted.swap(false, Ordering::Relaxed) {
+                monitor.last_refresh = Some(Instant::now());
+                monitor.refresh(æh).map_err(|err| format!("Cannot refresh monitor: {}", &err))?;
+            }
+        }
+        Ok(())
+    }
+
+}
@@ -0,0 +1,65 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Inspection commands gather what they show into views, which are then printed either as text for people
+// or, with --json or output_json setting, as one line of JSON for scripts and daemon clients
+
+use serde::Serialize;
+
+use super::Commander;
+
+// "--json" anywhere among parameters
+pub const JSON_FLAG: &str = "--json";
+
+#[derive(Serialize)]
+struct ErrorView<'a> {
+    error: &'a str
+}
+
+impl Commander {
+    pub(super) fn json_output(&self) -> bool {
+        self.json_once || self.settings.output_json
+    }
+
+    pub(super) fn present<T: Serialize>(&self, view: &T, print_text: impl FnOnce(&T)) -> Result<(), String> {
+        if self.json_output() {
+            println!("{}", serde_json::to_string(view).map_err(|err| format!("Cannot serialize output: {}", &err))?);
+        } else {
+            print_text(view);
+        }
+        Ok(())
+    }
+
+    // Error of the command being executed, instead of red text
+    pub(super) fn present_error(&self, err: &str) {
+        println!("{}", serde_json::to_string(&ErrorView { error: err }).unwrap_or_default());
+    }
+
+}
\ No newline at end of file
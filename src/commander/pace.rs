@@ -0,0 +1,146 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Real-time pacing of ticks, so that organisms are as fast relative to I/O on any host, see pace_tps and pace_ticks_per_io settings
+
+use crossterm::style::Stylize;
+
+use std::time::{
+    Duration,
+    Instant
+};
+
+use {
+    crate::aelhometta::Ælhometta,
+    super::Commander
+};
+
+const PACE_MIN_DELAY: Duration = Duration::from_millis(1); // shorter waits are left to accumulate, sleeping is not that precise
+const PACE_MAX_LAG: Duration = Duration::from_secs(1); // when behind by more, e.g. after pause, do not hurry to catch up
+
+pub(super) struct Pacer {
+    target: f64, // ticks per second, 0 if as fast as possible
+    t_start: Instant, // since target has been set
+    ticks: u64, // since t_start
+    t_window: Instant,
+    window_ticks: u64,
+    achieved: f64 // ticks per second during the last whole second
+}
+
+impl Pacer {
+    pub(super) fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            target: 0.0,
+            t_start: now,
+            ticks: 0,
+            t_window: now,
+            window_ticks: 0,
+            achieved: 0.0
+        }
+    }
+
+    pub(super) fn retarget(&mut self, target: f64) {
+        if target != self.target {
+            self.target = target;
+            self.t_start = Instant::now();
+            self.ticks = 0;
+        }
+    }
+
+    pub(super) fn ticked(&mut self) {
+        self.ticks += 1;
+        self.window_ticks += 1;
+        let window = self.t_window.elapsed();
+        if window >= Duration::from_secs(1) {
+            self.achieved = (self.window_ticks as f64) / window.as_secs_f64();
+            self.t_window = Instant::now();
+            self.window_ticks = 0;
+        }
+    }
+
+    // How long to wait before the next tick, zero if it is due or there is no target
+    pub(super) fn delay(&mut self) -> Duration {
+        if self.target <= 0.0 {
+            return Duration::ZERO;
+        }
+        let t_due = self.t_start + Duration::from_secs_f64((self.ticks as f64) / self.target);
+        let now = Instant::now();
+        if t_due >= now + PACE_MIN_DELAY {
+            t_due - now
+        } else {
+            if now > t_due + PACE_MAX_LAG {
+                self.t_start = now;
+                self.ticks = 0;
+            }
+            Duration::ZERO
+        }
+    }
+
+}
+
+// Slow rates need fractions
+fn rate_str(rate: f64) -> String {
+    if rate < 100.0 {
+        format!("{:.1}", rate)
+    } else {
+        format!("{:.0}", rate)
+    }
+}
+
+impl Commander {
+    // Ticks per second, 0 if as fast as possible. Ticks per I/O interval, if set and there are I/O mappings, take precedence
+    pub(super) fn pace_target(&self, æh: &Ælhometta) -> f64 {
+        if self.settings.pace_ticks_per_io > 0 {
+            let min_interval = æh.output_mappings().iter().map(|fm| fm.interval())
+                .chain(æh.input_mappings().iter().map(|fm| fm.interval()))
+                .chain(æh.sensor_mappings().iter().map(|sm| sm.interval()))
+                .min();
+            if let Some(interval) = min_interval {
+                return (self.settings.pace_ticks_per_io as f64) * 1_000_000.0 / (interval.max(1) as f64);
+            }
+        }
+        self.settings.pace_tps
+    }
+
+    // Continuation of status line
+    pub(super) fn print_pace(pacer: &Pacer) {
+        if pacer.target > 0.0 {
+            print!(" {} {}{}{} {}",
+                "|".dark_grey(),
+                rate_str(pacer.achieved).green(),
+                "/".dark_grey(),
+                rate_str(pacer.target).dark_green(),
+                "t/s".dark_grey()
+            );
+        }
+    }
+
+}
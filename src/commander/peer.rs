@@ -32,14 +32,100 @@ use chrono::prelude::*;
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use {
     crate::aelhometta::Ælhometta,
     super::{
+        ether::{
+            channels_view,
+            print_integers_view
+        },
         Commander,
         ParseErrorPrefixise
     }
 };
 
+#[derive(Serialize)]
+pub(super) struct OtherPeerView {
+    publickey: String,
+    onion: String,
+    port: u16,
+    share_size: usize,
+    ut_last_update: i64 // microseconds since Unix epoch
+}
+
+#[derive(Serialize)]
+pub(super) struct PeerView {
+    share_size: usize,
+    share_interval: i64,
+    ut_last_share: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secretkey: Option<String>, // not given away by monitor
+    port: u16,
+    torproxy_port: u16,
+    torproxy_host: String,
+    exposed: bool,
+    in_absorbing_num: Option<u64>, // none if not exposed
+    in_permitted_num: u64,
+    in_attempted_num: u64,
+    other_peers: Vec<OtherPeerView>
+}
+
+pub(super) fn peer_view(æh: &Ælhometta, with_secretkey: bool) -> PeerView {
+    PeerView {
+        share_size: æh.share_size(),
+        share_interval: æh.share_interval(),
+        ut_last_share: æh.ut_last_share(),
+        secretkey: with_secretkey.then(|| æh.secretkey()),
+        port: æh.port(),
+        torproxy_port: æh.torproxy_port(),
+        torproxy_host: æh.torproxy_host(),
+        exposed: æh.exposed(),
+        in_absorbing_num: æh.in_absorbing_num(),
+        in_permitted_num: æh.in_permitted_num(),
+        in_attempted_num: æh.in_attempted_num(),
+        other_peers: æh.other_peers().iter().map(|op| OtherPeerView {
+            publickey: op.publickey(),
+            onion: op.onion(),
+            port: op.port(),
+            share_size: op.ether_integers().len(),
+            ut_last_update: op.ut_last_update()
+        }).collect()
+    }
+}
+
+fn print_peer_view(view: &PeerView) {
+    println!("{}{}", format!("{:24}", "Share size").dark_blue(), format!("{}", view.share_size).blue());
+    println!("{}{}", format!("{:24}", "Share interval (μs)").dark_green(), format!("{}", view.share_interval).green());
+    println!("{}{}", format!("{:24}", "Last share").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(view.ut_last_share).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (view.ut_last_share / 1000) % 1000).green());
+    println!("{}{}", format!("{:24}", "Secret key").dark_red(), view.secretkey.as_deref().unwrap_or_default().red());
+    println!("{}{}", format!("{:24}", "Port").dark_blue(), format!("{}", view.port).blue());
+    println!("{}{}", format!("{:24}", "Tor proxy port").dark_blue(), format!("{}", view.torproxy_port).blue());
+    println!("{}{}", format!("{:24}", "Tor proxy host").dark_magenta(), view.torproxy_host.as_str().magenta());
+    println!("{}{}", format!("{:24}", "Exposed").dark_yellow(), format!("{}", view.exposed).yellow());
+    print!("{}", format!("{:24}", "Incoming absorbing").dark_blue());
+    match view.in_absorbing_num {
+        Some(num) => {
+            println!("{}", format!("{}", num).blue());
+        },
+        None => {
+            println!("{}", "Not exposed".dark_yellow().bold());
+        }
+    }
+    println!("{}{}", format!("{:24}", "Incoming permitted").dark_blue(), format!("{}", view.in_permitted_num).blue());
+    println!("{}{}", format!("{:24}", "Incoming attempted").dark_blue(), format!("{}", view.in_attempted_num).blue());
+    println!("{:8}{}", " ", format!("Other peers ({})", view.other_peers.len()).dark_grey());
+    for (i, op) in view.other_peers.iter().enumerate() {
+        println!("{:4}{}", " ", format!("Peer {}", 1 + i).dark_grey());
+        println!("{}{}", format!("{:24}", "Public key").dark_yellow(), op.publickey.as_str().yellow());
+        println!("{}{}", format!("{:24}", "Onion").dark_magenta(), op.onion.as_str().magenta());
+        println!("{}{}", format!("{:24}", "Port").dark_blue(), format!("{}", op.port).blue());
+        println!("{}{}", format!("{:24}", "Share size").dark_blue(), format!("{}", op.share_size).blue());
+        println!("{}{}", format!("{:24}", "Last update").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(op.ut_last_update).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (op.ut_last_update / 1000) % 1000).green());
+    }
+}
+
 impl Commander {
     pub fn peer(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() > 0 {
@@ -227,14 +313,7 @@ impl Commander {
                                                 1
                                             };
                                             
-                                            let ether_integers = peer.ether_integers();
-                                            for chan in start..(start + length) {
-                                                if chan < ether_integers.len() {
-                                                    println!("{}{:8}{}", format!("{:>12}", chan).dark_blue(), " ", format!("{0}={0:X}h", ether_integers[chan]).blue());
-                                                } else {
-                                                    println!("{}{:8}{}", format!("{:>12}", chan).dark_red(), " ", "OUT OF BOUNDS".red());
-                                                };                                
-                                            }
+                                            self.present(&channels_view(peer.ether_integers(), start, length), print_integers_view)?;
                                         },
                                         Err(err) => return err.prefixised("range start")
                                     }
@@ -297,35 +376,7 @@ impl Commander {
             }
 
         } else {
-            println!("{}{}", format!("{:24}", "Share size").dark_blue(), format!("{}", æh.share_size()).blue());
-            println!("{}{}", format!("{:24}", "Share interval (μs)").dark_green(), format!("{}", æh.share_interval()).green());
-            println!("{}{}", format!("{:24}", "Last share").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(æh.ut_last_share()).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (æh.ut_last_share() / 1000) % 1000).green());
-            println!("{}{}", format!("{:24}", "Secret key").dark_red(), format!("{}", & æh.secretkey()).red());
-            println!("{}{}", format!("{:24}", "Port").dark_blue(), format!("{}", æh.port()).blue());
-            println!("{}{}", format!("{:24}", "Tor proxy port").dark_blue(), format!("{}", æh.torproxy_port()).blue());
-            println!("{}{}", format!("{:24}", "Tor proxy host").dark_magenta(), format!("{}", & æh.torproxy_host()).magenta());
-            println!("{}{}", format!("{:24}", "Exposed").dark_yellow(), format!("{}", æh.exposed()).yellow());
-            print!("{}", format!("{:24}", "Incoming absorbing").dark_blue());
-            match æh.in_absorbing_num() {
-                Some(num) => {
-                    println!("{}", format!("{}", num).blue());
-                },
-                None => {
-                    println!("{}", "Not exposed".dark_yellow().bold());
-                }
-            }
-            println!("{}{}", format!("{:24}", "Incoming permitted").dark_blue(), format!("{}", æh.in_permitted_num()).blue());
-            println!("{}{}", format!("{:24}", "Incoming attempted").dark_blue(), format!("{}", æh.in_attempted_num()).blue());
-            println!("{:8}{}", " ", format!("Other peers ({})", æh.other_peers().len()).dark_grey());
-            for (i, op) in æh.other_peers().iter().enumerate() {
-                println!("{:4}{}", " ", format!("Peer {}", 1 + i).dark_grey());
-                println!("{}{}", format!("{:24}", "Public key").dark_yellow(), format!("{}", & op.publickey()).yellow());
-                println!("{}{}", format!("{:24}", "Onion").dark_magenta(), format!("{}", & op.onion()).magenta());
-                println!("{}{}", format!("{:24}", "Port").dark_blue(), format!("{}", op.port()).blue());
-                println!("{}{}", format!("{:24}", "Share size").dark_blue(), format!("{}", op.ether_integers().len()).blue());
-                println!("{}{}", format!("{:24}", "Last update").dark_green(), format!("{}.{:03} UTC", NaiveDateTime::from_timestamp_micros(op.ut_last_update()).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S"), (op.ut_last_update() / 1000) % 1000).green());
-            }
-            Ok(())
+            self.present(&peer_view(æh, true), print_peer_view)
         }
     }
 
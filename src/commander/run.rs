@@ -78,6 +78,9 @@ use {
         Hexly
     },
     super::{
+        pace::Pacer,
+        statistics::StatsLogTotals,
+        until::Condition,
         Commander,
         Settings
     }
@@ -86,6 +89,21 @@ use {
 const FREQ_BRANCHES_MAIN_CAPTION: &str = "BranchesMain";
 const FREQ_BRANCHES_ALT_CAPTION: &str = "BranchesAlt";
 
+// When run stops by itself, not by keypress
+#[derive(Clone, Copy)]
+pub enum RunLimit {
+    Seconds(u64),
+    Ticks(u128)
+}
+
+// Why run has stopped
+pub enum Stop {
+    Keypress,
+    Limit,
+    Condition(String), // what holds
+    Extinct // by on_extinction policy
+}
+
 fn sec_to_hms_str(mut seconds: u64) -> String {
     let s = seconds % 60;
     seconds /= 60;
@@ -323,8 +341,86 @@ fn draw_frequencies(comm_count: &Vec<(Command, u128)>, cons_count: &Vec<(Constru
     Ok(())
 }
 
+// What running does besides ticks, be it in shell or in daemon
+pub(super) struct Chores {
+    last_io_log_t_elapsed: u64,
+    last_stats_log_t_elapsed: u64,
+    last_stats_log_age: u128,
+    stats_log_totals: StatsLogTotals,
+    last_autosave_t_elapsed: u64,
+    last_autosave_age: u128
+}
+
+impl Chores {
+    pub(super) fn new(æh: &Ælhometta) -> Self {
+        Self {
+            last_io_log_t_elapsed: 0,
+            last_stats_log_t_elapsed: 0,
+            last_stats_log_age: æh.age(),
+            stats_log_totals: StatsLogTotals::new(æh),
+            last_autosave_t_elapsed: 0,
+            last_autosave_age: æh.age()
+        }
+    }
+
+}
+
 impl Commander {
-    pub fn run(&mut self, æh: &mut Ælhometta, duration: Option<u64>) -> Result<bool, String> {
+    // Errors, if any, are for the caller to show
+    pub(super) fn do_chores(&mut self, æh: &mut Ælhometta, chores: &mut Chores, t_elapsed: u64) -> Vec<String> {
+        let mut errs = Vec::new();
+
+        if !self.settings.io_log.is_empty() && (t_elapsed >= chores.last_io_log_t_elapsed + (self.settings.io_log_interval as u64)) {
+            chores.last_io_log_t_elapsed = t_elapsed;
+            if let Err(err) = self.io_log_append(æh) {
+                errs.push(format!("Cannot log I/O statistics: {}", &err));
+            }
+        }
+
+        // Restored state may be younger, then ticks are counted from its age
+        let age = æh.age();
+        if age < chores.last_stats_log_age {
+            chores.last_stats_log_age = age;
+        }
+        if age < chores.last_autosave_age {
+            chores.last_autosave_age = age;
+        }
+
+        let stats_log_by_time = (self.settings.stats_log_interval > 0) && (t_elapsed >= chores.last_stats_log_t_elapsed + (self.settings.stats_log_interval as u64));
+        let stats_log_by_ticks = (self.settings.stats_log_ticks > 0) && (æh.age() >= chores.last_stats_log_age + (self.settings.stats_log_ticks as u128));
+        if !self.settings.stats_log.is_empty() && (stats_log_by_time || stats_log_by_ticks) {
+            chores.last_stats_log_t_elapsed = t_elapsed;
+            chores.last_stats_log_age = æh.age();
+            if let Err(err) = self.stats_log_append(æh, &mut chores.stats_log_totals) {
+                errs.push(format!("Cannot log statistics: {}", &err));
+            }
+        }
+
+        let autosave_by_time = (self.settings.autosave_interval > 0) && (t_elapsed >= chores.last_autosave_t_elapsed + (self.settings.autosave_interval as u64));
+        let autosave_by_ticks = (self.settings.autosave_ticks > 0) && (æh.age() >= chores.last_autosave_age + (self.settings.autosave_ticks as u128));
+        if (autosave_by_time || autosave_by_ticks) && !self.no_save && !self.background_save_running() { // otherwise postponed until it finishes
+            chores.last_autosave_t_elapsed = t_elapsed;
+            chores.last_autosave_age = æh.age();
+            if let Err(err) = self.autosave(æh) {
+                errs.push(format!("Cannot autosave: {}", &err));
+            }
+        }
+        if let Some(Err(err)) = self.background_save_poll() {
+            errs.push(format!("Cannot autosave: {}", &err));
+        }
+
+        if let Err(err) = self.monitor_update(æh) {
+            errs.push(err);
+        }
+
+        errs
+    }
+
+    pub fn run(&mut self, æh: &mut Ælhometta, limit: Option<RunLimit>, until: Option<&Condition>) -> Result<Stop, String> {
+        if let Some(cond) = until {
+            cond.validate(æh)?;
+        }
+
         terminal::enable_raw_mode().map_err(|err| err.to_string())?;
         let _ = io::stdout().execute(cursor::Hide);
 
@@ -336,6 +432,7 @@ impl Commander {
 
         let t_start = Instant::now();
         let mut last_t_elapsed: u64 = 0;
+        let start_age = æh.age();
 
         let mut last_comm_totals = BTreeMap::<Command, u128>::new();
         for (command, count) in æh.commands_count() {
@@ -355,15 +452,39 @@ impl Commander {
         let mut branches_main_counts = VecDeque::<u128>::with_capacity(self.settings.freqs_interval);
         let mut branches_main_sum_count = 0;
 
+        let mut chores = Chores::new(æh);
+
         let mut last_branches_alt_count = æh.branches_alt_count();
         let mut branches_alt_counts = VecDeque::<u128>::with_capacity(self.settings.freqs_interval);
         let mut branches_alt_sum_count = 0;
 
-        let keypress = 'ext: loop {
+        // Expensive conditions are checked once a second
+        let until_each_tick = until.is_none_or(|cond| cond.cheap());
+        let mut last_until_t_elapsed: u64 = 0;
+
+        let mut pacer = Pacer::new();
+        pacer.retarget(self.pace_target(æh));
+        let mut last_pace_t_elapsed: u64 = 0;
+
+        self.schedule_restart();
+
+        let stop = 'ext: loop {
+            let delay = pacer.delay();
+            if !delay.is_zero() {
+                // Returns early on keypress, to be read below
+                let _ = event::poll(delay);
+            }
             let tick_data = æh.tick(&None);
+            pacer.ticked();
 
             let t_elapsed = t_start.elapsed().as_secs();   
 
+            // Settings and I/O mappings may change meanwhile
+            if t_elapsed > last_pace_t_elapsed {
+                last_pace_t_elapsed = t_elapsed;
+                pacer.retarget(self.pace_target(æh));
+            }
+
             if self.settings.show_ticks || (t_elapsed > last_t_elapsed) {
                 if self.settings.show_abs_time {
                     print!("{} ", format!("[{} UTC]", NaiveDateTime::from_timestamp_micros(
@@ -371,9 +492,16 @@ impl Commander {
                     ).unwrap_or_default().format("%Y.%m.%d %a %H:%M:%S")).dark_green());
                 } else {
                     print!("{}", format!("[{}", sec_to_hms_str(t_elapsed)).dark_green());
-                    if let Some(dur) = duration {
-                        print!(" {} ", "/".dark_grey());
-                        print!("{}", sec_to_hms_str(dur).dark_green());
+                    match limit {
+                        Some(RunLimit::Seconds(dur)) => {
+                            print!(" {} ", "/".dark_grey());
+                            print!("{}", sec_to_hms_str(dur).dark_green());
+                        },
+                        Some(RunLimit::Ticks(ticks)) => {
+                            print!(" {} ", "/".dark_grey());
+                            print!("{}", format!("{} of {} ticks", æh.age().saturating_sub(start_age), ticks).dark_green());
+                        },
+                        None => {}
                     }
                     print!("{} ", "]".dark_green());
                 }
@@ -407,9 +535,12 @@ impl Commander {
                     }
 
                     Self::print_state(æh, false);
+                    self.print_background_save();
+                    Self::print_pace(&pacer);
                     print!("\r\n");
 
                     if self.settings.show_freqs {
+                        // Restored state may be younger, hence saturating_sub() below
                         // For Command-s...
                         let mut comm_totals = BTreeMap::<Command, u128>::new();
                         for (command, count) in æh.commands_count() {
@@ -418,7 +549,7 @@ impl Commander {
                         let l = comm_totals.len();
                         let mut comm_count_last_sec = Vec::<(Command, u128)>::with_capacity(l);
                         for (command, count) in & comm_totals {
-                            comm_count_last_sec.push((*command, (*count) - last_comm_totals.get(command).unwrap_or(&0)));
+                            comm_count_last_sec.push((*command, count.saturating_sub(*last_comm_totals.get(command).unwrap_or(&0))));
                         }
                         last_comm_totals = comm_totals;
                         comm_counts.push_back(comm_count_last_sec);
@@ -437,7 +568,7 @@ impl Commander {
                         let l = cons_totals.len();
                         let mut cons_count_last_sec = Vec::<(Construction, u128)>::with_capacity(l);
                         for (construction, count) in & cons_totals {
-                            cons_count_last_sec.push((*construction, (*count) - last_cons_totals.get(construction).unwrap_or(&0)));
+                            cons_count_last_sec.push((*construction, count.saturating_sub(*last_cons_totals.get(construction).unwrap_or(&0))));
                         }
                         last_cons_totals = cons_totals;
                         cons_counts.push_back(cons_count_last_sec);
@@ -450,7 +581,7 @@ impl Commander {
                         // TODO: factor out the duplication
 
                         let branches_main_count = æh.branches_main_count();
-                        branches_main_counts.push_back(branches_main_count - last_branches_main_count);
+                        branches_main_counts.push_back(branches_main_count.saturating_sub(last_branches_main_count));
                         if branches_main_counts.len() > self.settings.freqs_interval {
                             branches_main_counts.pop_front();
                         }
@@ -458,7 +589,7 @@ impl Commander {
                         branches_main_sum_count = branches_main_counts.iter().sum();                        
 
                         let branches_alt_count = æh.branches_alt_count();
-                        branches_alt_counts.push_back(branches_alt_count - last_branches_alt_count);
+                        branches_alt_counts.push_back(branches_alt_count.saturating_sub(last_branches_alt_count));
                         if branches_alt_counts.len() > self.settings.freqs_interval {
                             branches_alt_counts.pop_front();
                         }
@@ -472,6 +603,22 @@ impl Commander {
                 }
             }
 
+            for err in self.do_chores(æh, &mut chores, t_elapsed) {
+                print!("{}\r\n", err.red().bold());
+            }
+
+            if !self.schedule.is_empty() {
+                let commands = self.schedule_due(æh, t_elapsed);
+                if !commands.is_empty() {
+                    // Output is the same as in shell
+                    terminal::disable_raw_mode().unwrap_or(());
+                    for command in commands {
+                        self.schedule_execute(æh, &command);
+                    }
+                    terminal::enable_raw_mode().unwrap_or(());
+                }
+            }
+
             while let Ok(true) = event::poll(Duration::from_secs(0)) {
                 if let Ok(Event::Key(kev)) = event::read() {
                     if self.settings.show_freqs {
@@ -503,31 +650,45 @@ impl Commander {
                                 io::stdout().flush().unwrap_or(());
                             },
                             _ => {
-                                break 'ext true;
+                                break 'ext Stop::Keypress;
                             }
                         }  
                     } else {
-                        break 'ext true;
+                        break 'ext Stop::Keypress;
                     }                  
                 }
             }
-/*
             if æh.num_controllers() == 0 {
-                print!("\r\n\r\n{}", "NO CONTROLLERS LEFT".dark_yellow().bold());
-                break;
+                if let Some((stop, message)) = self.extinction(æh) {
+                    print!("{} {}\r\n", format!("[{} UTC]", Utc::now().format("%Y.%m.%d %a %H:%M:%S")).dark_green(), message.dark_yellow().bold());
+                    if stop {
+                        break 'ext Stop::Extinct;
+                    }
+                }
             }
-*/
-            if let Some(dur) = duration {
-                if t_elapsed >= dur {
-                    break 'ext false;
+            if let Some(cond) = until {
+                if until_each_tick || (t_elapsed > last_until_t_elapsed) {
+                    last_until_t_elapsed = t_elapsed;
+                    if let Some(reason) = cond.reason(æh, t_elapsed) {
+                        break 'ext Stop::Condition(reason);
+                    }
                 }
             }
+            match limit {
+                Some(RunLimit::Seconds(dur)) if t_elapsed >= dur => break 'ext Stop::Limit,
+                Some(RunLimit::Ticks(ticks)) if æh.age() >= start_age + ticks => break 'ext Stop::Limit,
+                _ => {}
+            }
         };
 
+        if let Stop::Condition(ref reason) = stop {
+            print!("{}\r\n", format!("Stopped: {}", reason).dark_yellow().bold());
+        }
+
         let _ = stdout().execute(cursor::Show);
         terminal::disable_raw_mode().map_err(|err| err.to_string())?;
 
-        Ok(keypress)
+        Ok(stop)
     }
 
 }
\ No newline at end of file
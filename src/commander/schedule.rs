@@ -0,0 +1,246 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Shell commands executed by run (or daemon) between ticks, at given age or time since run began, maybe repeatedly.
+// One-shot ones are removed once executed
+
+use std::mem;
+
+use crossterm::style::Stylize;
+
+use serde::{
+    Deserialize,
+    Serialize
+};
+
+use {
+    crate::aelhometta::Ælhometta,
+    super::{
+        output::JSON_FLAG,
+        shell::Executed,
+        Commander,
+        ParseErrorPrefixise
+    }
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum Moment {
+    Age(u128),
+    Time(u64) // seconds since run began
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ScheduledCommand {
+    at: Moment, // the next one for age, the first one for time
+    every: u128, // ticks or seconds, 0 if once
+    command: String,
+    #[serde(skip)] times_done: u128 // during this run, for time
+}
+
+impl ScheduledCommand {
+    fn due(&self, age: u128, t_elapsed: u64) -> bool {
+        match self.at {
+            Moment::Age(at) => age >= at,
+            Moment::Time(at) => (t_elapsed as u128) >= (at as u128) + self.every * self.times_done
+        }
+    }
+
+}
+
+// Run is already going on, and quitting is for the shell to decide, so these are refused, be they scheduled or sourced by scheduled
+fn unschedulable(line: &str) -> Option<&str> {
+    match line.split_whitespace().find(|&token| token != JSON_FLAG) {
+        Some(token @ ("q" | "quit" | "exit" | "end" | "bye" | "qq" | "quitquit" | "exitexit" | "endend" | "byebye" | "r" | "run" | "t" | "tick" | "=")) => Some(token),
+        _ => None
+    }
+}
+
+impl Commander {
+    // Time is counted anew for each run
+    pub(super) fn schedule_restart(&mut self) {
+        for sc in &mut self.schedule {
+            sc.times_done = 0;
+        }
+    }
+
+    // Commands due now, in order of schedule; repeated ones are moved to their next moment, past ones skipped
+    pub(super) fn schedule_due(&mut self, æh: &Ælhometta, t_elapsed: u64) -> Vec<String> {
+        let age = æh.age();
+        let mut commands = Vec::new();
+        self.schedule.retain_mut(|sc| {
+            if !sc.due(age, t_elapsed) {
+                return true;
+            }
+            commands.push(sc.command.clone());
+            if sc.every == 0 {
+                return false;
+            }
+            match sc.at {
+                Moment::Age(ref mut at) => {
+                    *at += ((age - *at) / sc.every + 1) * sc.every;
+                },
+                Moment::Time(at) => {
+                    sc.times_done = ((t_elapsed as u128) - (at as u128)) / sc.every + 1;
+                }
+            }
+            true
+        });
+        commands
+    }
+
+    // As if typed in shell, along with lines it sources, if any
+    pub(super) fn schedule_execute(&mut self, æh: &mut Ælhometta, command: &str) {
+        // Own queue for what the command sources, while the script the run was started from, if any, waits for the run to end
+        let outer_script = mem::take(&mut self.script);
+        let outer_sourced = mem::replace(&mut self.script_sourced, 0);
+        self.script.push_back(String::from(command));
+        let mut first = true;
+        while let Some(line) = self.script.pop_front() {
+            if first {
+                println!("{} {}", format!("@ [{}]", æh.age()).dark_grey(), &line);
+                first = false;
+            } else {
+                println!("@ {}", &line);
+            }
+            let failed = match unschedulable(&line) {
+                Some(token) => {
+                    println!("{}", format!("Ignored: '{}' cannot be executed during run", token).red().bold());
+                    true
+                },
+                None => match self.execute(æh, &line) {
+                    Executed::Done => false,
+                    Executed::Failed => true,
+                    Executed::Quit(_) => { // unschedulable() should have caught it
+                        println!("{}", "Ignored: quitting cannot be executed during run".red().bold());
+                        true
+                    }
+                }
+            };
+            if failed && !self.script.is_empty() && self.script_abort_on_error() {
+                println!("{}", format!("Script aborted, {} lines skipped", self.script.len()).red().bold());
+                self.script.clear();
+            }
+        }
+        self.script = outer_script;
+        self.script_sourced = outer_sourced;
+    }
+
+    fn schedule_list(&self) {
+        for (i, sc) in self.schedule.iter().enumerate() {
+            let (moment, unit) = match sc.at {
+                Moment::Age(at) => (format!("age {}", at), "ticks"),
+                Moment::Time(at) => (format!("time {}", at), "s")
+            };
+            let every = if sc.every > 0 {
+                format!("every {} {}", sc.every, unit)
+            } else {
+                String::from("once")
+            };
+            println!("{}{}{}{}",
+                format!("{:<6}", i).dark_grey(),
+                format!("{:<24}", moment).blue(),
+                format!("{:<24}", every).dark_blue(),
+                sc.command.clone().yellow()
+            );
+        }
+    }
+
+    pub fn schedule(&mut self, paramstr: &[&str]) -> Result<(), String> {
+        let subcommand = paramstr.first().map(|s| s.to_lowercase()).unwrap_or(String::from("list"));
+        match subcommand.as_str() {
+            "list" => {
+                self.schedule_list();
+                Ok(())
+            },
+
+            "add" => {
+                if paramstr.len() < 4 {
+                    return Err(String::from("Moment or command not specified"));
+                }
+                let value = match paramstr[2].parse::<u128>() {
+                    Ok(value) => value,
+                    Err(err) => return err.prefixised("moment")
+                };
+                let at = match paramstr[1].to_lowercase().as_str() {
+                    "age" => Moment::Age(value),
+                    "time" => Moment::Time(value.try_into().map_err(|_| String::from("Time too large"))?),
+                    other => return Err(format!("Unknown moment '{}', must be 'age' or 'time'", other))
+                };
+                let (every, i_command) = if paramstr[3].eq_ignore_ascii_case("every") {
+                    match paramstr.get(4).map(|s| s.parse::<u128>()) {
+                        Some(Ok(every)) if every > 0 => (every, 5),
+                        Some(Ok(_)) => return Err(String::from("Repetition must be greater than 0")),
+                        Some(Err(err)) => return err.prefixised("every"),
+                        None => return Err(String::from("Repetition not specified"))
+                    }
+                } else {
+                    (0, 3)
+                };
+                if paramstr.len() <= i_command {
+                    return Err(String::from("Command not specified"));
+                }
+                let command = paramstr[i_command..].join(" ");
+                if let Some(token) = unschedulable(&command) {
+                    return Err(format!("'{}' cannot be scheduled", token));
+                }
+                self.schedule.push(ScheduledCommand {
+                    at,
+                    every,
+                    command,
+                    times_done: 0
+                });
+                println!("{} {}", "Scheduled as".green(), format!("{}", self.schedule.len() - 1).green().bold());
+                Ok(())
+            },
+
+            "del" => {
+                match paramstr.get(1).map(|s| s.parse::<usize>()) {
+                    Some(Ok(index)) if index < self.schedule.len() => {
+                        self.schedule.remove(index);
+                        println!("{}", "Removed".green());
+                        Ok(())
+                    },
+                    Some(Ok(_)) => Err(format!("There are only {} scheduled commands", self.schedule.len())),
+                    Some(Err(err)) => err.prefixised("index"),
+                    None => Err(String::from("Index not specified"))
+                }
+            },
+
+            "clear" => {
+                self.schedule.clear();
+                println!("{}", "Cleared".green());
+                Ok(())
+            },
+
+            _ => Err(String::from("Unknown subcommand"))
+        }
+    }
+
+}
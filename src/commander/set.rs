@@ -31,6 +31,7 @@
 use crossterm::style::Stylize;
 
 use super::{
+    extinction::ExtinctionPolicy,
     Commander,
     ParseErrorPrefixise
 };
@@ -137,6 +138,250 @@ impl Commander {
                     }
                 },
 
+                "io_bins" => {
+                    match value.parse::<usize>() {
+                        Ok(b) => {
+                            if b >= 2 {
+                                self.settings.io_bins = b;
+                                println!("{}", "Set".green());
+                                Ok(())
+                            } else {
+                                Err(String::from("Number of bins must be at least 2"))
+                            }
+                        },
+                        Err(err) => err.prefixised("bins")
+                    }
+                },
+
+                "io_log" => {
+                    self.settings.io_log = if value == "none" {
+                        String::new()
+                    } else {
+                        String::from(value)
+                    };
+                    println!("{}", "Set".green());
+                    Ok(())
+                },
+
+                "io_log_interval" => {
+                    match value.parse::<usize>() {
+                        Ok(t) => {
+                            if t > 0 {
+                                self.settings.io_log_interval = t;
+                                println!("{}", "Set".green());
+                                Ok(())
+                            } else {
+                                Err(String::from("Interval must be greater than 0"))
+                            }
+                        },
+                        Err(err) => err.prefixised("interval")
+                    }
+                },
+
+                "stats_log" => {
+                    self.settings.stats_log = if value == "none" {
+                        String::new()
+                    } else {
+                        String::from(value)
+                    };
+                    println!("{}", "Set".green());
+                    Ok(())
+                },
+
+                "stats_log_format" => {
+                    match value {
+                        "csv" | "jsonl" => {
+                            self.settings.stats_log_format = String::from(value);
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        _ => Err(format!("Unknown format '{}', must be 'csv' or 'jsonl'", value))
+                    }
+                },
+
+                "stats_log_interval" => {
+                    match value.parse::<usize>() {
+                        Ok(t) => {
+                            self.settings.stats_log_interval = t;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("interval")
+                    }
+                },
+
+                "stats_log_ticks" => {
+                    match value.parse::<usize>() {
+                        Ok(n) => {
+                            self.settings.stats_log_ticks = n;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("ticks")
+                    }
+                },
+
+                "autosave_interval" => {
+                    match value.parse::<usize>() {
+                        Ok(t) => {
+                            self.settings.autosave_interval = t;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("interval")
+                    }
+                },
+
+                "autosave_ticks" => {
+                    match value.parse::<usize>() {
+                        Ok(n) => {
+                            self.settings.autosave_ticks = n;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("ticks")
+                    }
+                },
+
+                "autosave_background" => {
+                    match value.parse::<bool>() {
+                        Ok(b) => {
+                            self.settings.autosave_background = b;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("bool")
+                    }
+                },
+
+                "snapshots_dir" => {
+                    self.settings.snapshots_dir = String::from(value);
+                    println!("{}", "Set".green());
+                    Ok(())
+                },
+
+                "snapshots_max" => {
+                    match value.parse::<usize>() {
+                        Ok(n) => {
+                            self.settings.snapshots_max = n;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("number")
+                    }
+                },
+
+                "compress_saves" => {
+                    match value.parse::<bool>() {
+                        Ok(b) => {
+                            self.settings.compress_saves = b;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("bool")
+                    }
+                },
+
+                "journal" => {
+                    match value.parse::<bool>() {
+                        Ok(b) => {
+                            self.settings.journal = b;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("bool")
+                    }
+                },
+
+                "journal_max_deltas" => {
+                    match value.parse::<usize>() {
+                        Ok(n) => {
+                            self.settings.journal_max_deltas = n;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("number")
+                    }
+                },
+
+                "script_abort_on_error" => {
+                    match value.parse::<bool>() {
+                        Ok(b) => {
+                            self.settings.script_abort_on_error = b;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("bool")
+                    }
+                },
+
+                "output_json" => {
+                    match value.parse::<bool>() {
+                        Ok(b) => {
+                            self.settings.output_json = b;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("bool")
+                    }
+                },
+
+                "monitor_port" => {
+                    match value.parse::<u16>() {
+                        Ok(port) => {
+                            self.settings.monitor_port = port;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("port")
+                    }
+                },
+
+                "monitor_interval" => {
+                    match value.parse::<usize>() {
+                        Ok(secs) => {
+                            self.settings.monitor_interval = secs;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("seconds")
+                    }
+                },
+
+                "pace_tps" => {
+                    match value.parse::<f64>() {
+                        Ok(tps) if tps.is_finite() && (tps >= 0.0) => {
+                            self.settings.pace_tps = tps;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Ok(_) => Err(String::from("Ticks per second must be finite and non-negative")),
+                        Err(err) => err.prefixised("ticks per second")
+                    }
+                },
+
+                "pace_ticks_per_io" => {
+                    match value.parse::<usize>() {
+                        Ok(n) => {
+                            self.settings.pace_ticks_per_io = n;
+                            println!("{}", "Set".green());
+                            Ok(())
+                        },
+                        Err(err) => err.prefixised("ticks")
+                    }
+                },
+
+                "on_extinction" => {
+                    // Policy may take several words, e.g. "anc b 5"
+                    let policy = paramstr[1..].join(" ");
+                    self.settings.on_extinction = match ExtinctionPolicy::parse(&policy)? {
+                        ExtinctionPolicy::Ignore => String::new(),
+                        _ => policy
+                    };
+                    println!("{}", "Set".green());
+                    Ok(())
+                },
+
                 _ => {
                     Err(String::from("Unknown setting"))
                 }
@@ -65,6 +65,106 @@ impl Commander {
         println!("{}{}", format!("{:24}", "freqs_cons_str_len").dark_blue(), format!("{}", self.settings.freqs_cons_str_len).blue());
     }
 
+    fn print_io_bins(&self) {
+        println!("{}{}", format!("{:24}", "io_bins").dark_blue(), format!("{}", self.settings.io_bins).blue());
+    }
+
+    fn print_io_log(&self) {
+        println!("{}{}", format!("{:24}", "io_log").dark_cyan(), if self.settings.io_log.is_empty() {
+            String::from("none").dark_grey()
+        } else {
+            self.settings.io_log.clone().cyan()
+        });
+    }
+
+    fn print_io_log_interval(&self) {
+        println!("{}{}", format!("{:24}", "io_log_interval").dark_green(), format!("{}", self.settings.io_log_interval).green());
+    }
+
+    fn print_stats_log(&self) {
+        println!("{}{}", format!("{:24}", "stats_log").dark_cyan(), if self.settings.stats_log.is_empty() {
+            String::from("none").dark_grey()
+        } else {
+            self.settings.stats_log.clone().cyan()
+        });
+    }
+
+    fn print_stats_log_format(&self) {
+        println!("{}{}", format!("{:24}", "stats_log_format").dark_cyan(), self.settings.stats_log_format.clone().cyan());
+    }
+
+    fn print_stats_log_interval(&self) {
+        println!("{}{}", format!("{:24}", "stats_log_interval").dark_green(), format!("{}", self.settings.stats_log_interval).green());
+    }
+
+    fn print_stats_log_ticks(&self) {
+        println!("{}{}", format!("{:24}", "stats_log_ticks").dark_blue(), format!("{}", self.settings.stats_log_ticks).blue());
+    }
+
+    fn print_autosave_interval(&self) {
+        println!("{}{}", format!("{:24}", "autosave_interval").dark_green(), format!("{}", self.settings.autosave_interval).green());
+    }
+
+    fn print_autosave_ticks(&self) {
+        println!("{}{}", format!("{:24}", "autosave_ticks").dark_green(), format!("{}", self.settings.autosave_ticks).green());
+    }
+
+    fn print_autosave_background(&self) {
+        println!("{}{}", format!("{:24}", "autosave_background").dark_green(), format!("{}", self.settings.autosave_background).green().bold());
+    }
+
+    fn print_snapshots_dir(&self) {
+        println!("{}{}", format!("{:24}", "snapshots_dir").dark_cyan(), self.settings.snapshots_dir.clone().cyan());
+    }
+
+    fn print_snapshots_max(&self) {
+        println!("{}{}", format!("{:24}", "snapshots_max").dark_blue(), format!("{}", self.settings.snapshots_max).blue());
+    }
+
+    fn print_compress_saves(&self) {
+        println!("{}{}", format!("{:24}", "compress_saves").dark_yellow(), format!("{}", self.settings.compress_saves).dark_yellow().bold());
+    }
+
+    fn print_journal(&self) {
+        println!("{}{}", format!("{:24}", "journal").dark_yellow(), format!("{}", self.settings.journal).dark_yellow().bold());
+    }
+
+    fn print_journal_max_deltas(&self) {
+        println!("{}{}", format!("{:24}", "journal_max_deltas").dark_blue(), format!("{}", self.settings.journal_max_deltas).blue());
+    }
+
+    fn print_script_abort_on_error(&self) {
+        println!("{}{}", format!("{:24}", "script_abort_on_error").dark_yellow(), format!("{}", self.settings.script_abort_on_error).dark_yellow().bold());
+    }
+
+    fn print_output_json(&self) {
+        println!("{}{}", format!("{:24}", "output_json").dark_yellow(), format!("{}", self.settings.output_json).dark_yellow().bold());
+    }
+
+    fn print_monitor_port(&self) {
+        println!("{}{}", format!("{:24}", "monitor_port").dark_blue(), format!("{}", self.settings.monitor_port).blue());
+    }
+
+    fn print_monitor_interval(&self) {
+        println!("{}{}", format!("{:24}", "monitor_interval").dark_blue(), format!("{}", self.settings.monitor_interval).blue());
+    }
+
+    fn print_pace_tps(&self) {
+        println!("{}{}", format!("{:24}", "pace_tps").dark_green(), format!("{}", self.settings.pace_tps).green());
+    }
+
+    fn print_pace_ticks_per_io(&self) {
+        println!("{}{}", format!("{:24}", "pace_ticks_per_io").dark_green(), format!("{}", self.settings.pace_ticks_per_io).green());
+    }
+
+    fn print_on_extinction(&self) {
+        println!("{}{}", format!("{:24}", "on_extinction").dark_magenta(), if self.settings.on_extinction.is_empty() {
+            String::from("none").dark_grey()
+        } else {
+            self.settings.on_extinction.clone().magenta()
+        });
+    }
+
     pub fn settings(&self, setting: &str) -> Result<(), String> {
         match setting {
             "" => {
@@ -76,6 +176,28 @@ impl Commander {
                 self.print_freqs_window_margin();
                 self.print_freqs_comm_str_len();
                 self.print_freqs_cons_str_len();
+                self.print_io_bins();
+                self.print_io_log();
+                self.print_io_log_interval();
+                self.print_stats_log();
+                self.print_stats_log_format();
+                self.print_stats_log_interval();
+                self.print_stats_log_ticks();
+                self.print_autosave_interval();
+                self.print_autosave_ticks();
+                self.print_autosave_background();
+                self.print_snapshots_dir();
+                self.print_snapshots_max();
+                self.print_compress_saves();
+                self.print_journal();
+                self.print_journal_max_deltas();
+                self.print_script_abort_on_error();
+                self.print_output_json();
+                self.print_monitor_port();
+                self.print_monitor_interval();
+                self.print_on_extinction();
+                self.print_pace_tps();
+                self.print_pace_ticks_per_io();
                 Ok(())
             },
             "show_abs_time" => {
@@ -110,6 +232,94 @@ impl Commander {
                 self.print_freqs_cons_str_len();
                 Ok(())
             },
+            "io_bins" => {
+                self.print_io_bins();
+                Ok(())
+            },
+            "io_log" => {
+                self.print_io_log();
+                Ok(())
+            },
+            "io_log_interval" => {
+                self.print_io_log_interval();
+                Ok(())
+            },
+            "stats_log" => {
+                self.print_stats_log();
+                Ok(())
+            },
+            "stats_log_format" => {
+                self.print_stats_log_format();
+                Ok(())
+            },
+            "stats_log_interval" => {
+                self.print_stats_log_interval();
+                Ok(())
+            },
+            "stats_log_ticks" => {
+                self.print_stats_log_ticks();
+                Ok(())
+            },
+            "autosave_interval" => {
+                self.print_autosave_interval();
+                Ok(())
+            },
+            "autosave_ticks" => {
+                self.print_autosave_ticks();
+                Ok(())
+            },
+            "autosave_background" => {
+                self.print_autosave_background();
+                Ok(())
+            },
+            "snapshots_dir" => {
+                self.print_snapshots_dir();
+                Ok(())
+            },
+            "snapshots_max" => {
+                self.print_snapshots_max();
+                Ok(())
+            },
+            "compress_saves" => {
+                self.print_compress_saves();
+                Ok(())
+            },
+            "journal" => {
+                self.print_journal();
+                Ok(())
+            },
+            "journal_max_deltas" => {
+                self.print_journal_max_deltas();
+                Ok(())
+            },
+            "script_abort_on_error" => {
+                self.print_script_abort_on_error();
+                Ok(())
+            },
+            "output_json" => {
+                self.print_output_json();
+                Ok(())
+            },
+            "monitor_port" => {
+                self.print_monitor_port();
+                Ok(())
+            },
+            "monitor_interval" => {
+                self.print_monitor_interval();
+                Ok(())
+            },
+            "on_extinction" => {
+                self.print_on_extinction();
+                Ok(())
+            },
+            "pace_tps" => {
+                self.print_pace_tps();
+                Ok(())
+            },
+            "pace_ticks_per_io" => {
+                self.print_pace_ticks_per_io();
+                Ok(())
+            },
             _ => {
                 Err(format!("Unknown setting"))
             }
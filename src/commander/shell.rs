@@ -41,20 +41,107 @@ use std::io::{
 
 use {
     crate::aelhometta::Ælhometta,
-    super::Commander
+    super::{
+        output::JSON_FLAG,
+        Commander
+    }
 };
 
+// Outcome of one line of shell commands
+pub enum Executed {
+    Done,
+    Failed, // error already shown
+    Quit(bool) // whether to save
+}
+
 impl Commander {
-    pub fn shell(&mut self, æh: &mut Ælhometta) -> Result<bool, String> {
+    // Dispatch of a single line, the same for keyboard and scripts
+    pub fn execute(&mut self, æh: &mut Ælhometta, input: &str) -> Executed {
+        let mut tokens: Vec<&str> = input.split_whitespace().collect();
+        let num_tokens = tokens.len();
+        tokens.retain(|&token| token != JSON_FLAG);
+        self.json_once = tokens.len() < num_tokens;
+        let result = if !tokens.is_empty() {
+            match tokens[0] {
+                "q" | "quit" | "exit" | "end" | "bye" => return Executed::Quit(true),
+                "qq" | "quitquit" | "exitexit" | "endend" | "byebye" => return Executed::Quit(false),
+                "help" | "?" => self.help(tokens.get(1).copied().unwrap_or("")).map_err(|err| format!("Error showing help: {}", &err)),
+                "anc" | "ancestor" => self.ancestors(æh, & tokens[1..]).map_err(|err| format!("Error introducing ancestor: {}", &err)),
+                "r" | "run" => self.run_until(æh, & tokens[1..]).map_err(|err| format!("Error running: {}", &err)),
+                "t" | "tick" => self.tick(æh, & tokens[1..]).map_err(|err| format!("Error running ticks: {}", &err)),
+                "glitch" => self.glitch(æh, & tokens[1..]).map_err(|err| format!("Error changing glitch probability: {}", &err)),
+                "sn" | "shownode" => self.shownode(æh, & tokens[1..]).map_err(|err| format!("Error showing node: {}", &err)),
+                "sct" | "showctrl" => self.showctrl(æh, & tokens[1..]).map_err(|err| format!("Error showing controller: {}", &err)),
+                "ss" | "showseq" => self.showseq(æh, & tokens[1..]).map_err(|err| format!("Error showing forward sequence: {}", &err)),
+                "prev" | "prevnodes" => self.prevnodes(æh, & tokens[1..]).map_err(|err| format!("Error showing previous nodes: {}", &err)),
+                "back" | "backtrace" => self.backtrace(æh, & tokens[1..]).map_err(|err| format!("Error showing backward sequence: {}", &err)),
+                "eth" | "ether" => self.ether(æh, & tokens[1..]).map_err(|err| format!("Error showing ether: {}", &err)),
+                "rand" | "random" => self.random(æh, & tokens[1..]).map_err(|err| format!("Error obtaining random identifier: {}", &err)),
+                "stat" | "statistics" => self.statistics(æh, & tokens[1..]).map_err(|err| format!("Error showing statistics: {}", &err)),
+                "cleanse" => self.cleanse(æh).map_err(|err| format!("Error cleansing: {}", &err)),
+                "commsw" | "commandswitch" => self.commandswitch(æh, & tokens[1..]).map_err(|err| format!("Error showing or changing commandswitches: {}", &err)),
+                "changelim" => self.changelim(æh, & tokens[1..]).map_err(|err| format!("Error changing limits: {}", &err)),
+                "p" | "peer" => self.peer(æh, & tokens[1..]).map_err(|err| format!("Error configuring peer: {}", &err)),
+                "iomap" => self.iomap(æh, & tokens[1..]).map_err(|err| format!("Error configuring input/output mappings: {}", &err)),
+                "task" => self.task(æh, & tokens[1..]).map_err(|err| format!("Error configuring tasks: {}", &err)),
+                "snap" | "snapshot" => self.snapshot(æh, & tokens[1..]).map_err(|err| format!("Error with snapshots: {}", &err)),
+                "journal" => self.journal(æh, & tokens[1..]).map_err(|err| format!("Error with journal: {}", &err)),
+                "export" => self.export(æh, & tokens[1..]).map_err(|err| format!("Error exporting: {}", &err)),
+                "import" => self.import(æh, & tokens[1..]).map_err(|err| format!("Error importing: {}", &err)),
+                "merge" => self.merge(æh, & tokens[1..]).map_err(|err| format!("Error merging: {}", &err)),
+                "showsizes" => self.showsizes().map_err(|err| format!("Error showing sizes: {}", &err)),
+                "sets" | "settings" => self.settings(tokens.get(1).copied().unwrap_or("")).map_err(|err| format!("Error showing setting: {}", &err)),
+                "set" => self.set(& tokens[1..]).map_err(|err| format!("Error setting: {}", &err)),
+                "hist" | "history" => self.history(& tokens[1..]).map_err(|err| format!("Error showing history: {}", &err)),
+                "source" => self.source(& tokens[1..]).map_err(|err| format!("Error sourcing script: {}", &err)),
+                "sched" | "schedule" => self.schedule(& tokens[1..]).map_err(|err| format!("Error with schedule: {}", &err)),
+                _ => Err(String::from("Unknown command"))
+            }
+        } else {
+            println!("{}", "No command, nothing to do".dark_yellow().bold());
+            Ok(())
+        };
+        self.history.add(input);
+        let executed = match result {
+            Ok(_) => Executed::Done,
+            Err(err) => {
+                if self.json_output() {
+                    self.present_error(&err);
+                } else {
+                    println!("{}", err.red().bold());
+                }
+                Executed::Failed
+            }
+        };
+        self.json_once = false;
+        executed
+    }
+
+    // Some(do_save) on quit, None when not interactive and the script is over
+    pub fn shell(&mut self, æh: &mut Ælhometta, interactive: bool) -> Result<Option<bool>, String> {
         let _ = io::stdout().execute(cursor::Show);
 
-        let mut hint_shown: bool = false;
+        let mut hint_shown: bool = !interactive;
 
         let do_save = loop {
+            if !interactive && self.script.is_empty() {
+                return Ok(None);
+            }
+
             println!("");
 
+            // Background save started by run, if any
+            if let Some(Err(err)) = self.background_save_poll() {
+                println!("{}", format!("Cannot autosave: {}", &err).red().bold());
+            }
+
+            if let Err(err) = self.monitor_update(æh) {
+                println!("{}", err.red().bold());
+            }
+
             // State
             Self::print_state(æh, true);
+            self.print_background_save();
             println!("");
 
             // Hint
@@ -67,7 +154,22 @@ impl Commander {
             io::stdout().flush().unwrap_or(());
 
             let mut input = String::new();
-            match io::stdin().read_line(&mut input) {
+            let (read, from_script) = match self.script.pop_front() {
+                Some(line) => {
+                    println!("{}", &line);
+                    input = line + "\n";
+                    (Ok(input.len()), true)
+                },
+                None => {
+                    self.script_sourced = 0;
+                    (io::stdin().read_line(&mut input), false)
+                }
+            };
+            match read {
+                Ok(0) => { // end of stdin, as if quitting
+                    println!();
+                    break true;
+                },
                 Ok(..) => {
                     input.pop(); // remove trailing newline
                     
@@ -85,236 +187,18 @@ impl Commander {
                         }
                     }
 
-                    let tokens: Vec<&str> = input.split_whitespace().collect();
-                    if tokens.len() > 0 {
-                        let command = tokens[0];
-                        match command {
-                            "q" | "quit" | "exit" | "end" | "bye" => {
-                                break true;
-                            },
-
-                            "qq" | "quitquit" | "exitexit" | "endend" | "byebye" => {
-                                break false;
-                            },
-
-                            "help" | "?" => {
-                                let comm = if tokens.len() > 1 {
-                                    tokens[1]
-                                } else {
-                                    ""
-                                };
-                                match self.help(comm) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing help: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "anc" | "ancestor" => {
-                                match self.ancestors(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error introducing ancestor: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "r" | "run" => {
-                                match self.run(æh, None) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error running: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-                                                        
-                            "t" | "tick" => {
-                                match self.tick(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error running ticks: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "glitch" => {
-                                match self.glitch(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error changing glitch probability: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "sn" | "shownode" => {
-                                match self.shownode(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing node: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "sct" | "showctrl" => {
-                                match self.showctrl(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing controller: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "ss" | "showseq" => {
-                                match self.showseq(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing forward sequence: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "prev" | "prevnodes" => {
-                                match self.prevnodes(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing previous nodes: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "back" | "backtrace" => {
-                                match self.backtrace(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing backward sequence: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "eth" | "ether" => {
-                                match self.ether(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing ether: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "rand" | "random" => {
-                                match self.random(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error obtaining random identifier: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "stat" | "statistics" => {
-                                match self.statistics(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing statistics: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "cleanse" => {
-                                match self.cleanse(æh) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error cleansing: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "commsw" | "commandswitch" => {
-                                match self.commandswitch(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing or changing commandswitches: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "changelim" => {
-                                match self.changelim(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error changing limits: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "p" | "peer" => {
-                                match self.peer(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error configuring peer: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "iomap" => {
-                                match self.iomap(æh, & tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error configuring input/output mappings: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "showsizes" => {
-                                match self.showsizes() {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing sizes: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "sets" | "settings" => {
-                                let setting = if tokens.len() > 1 {
-                                    tokens[1]
-                                } else {
-                                    ""
-                                };
-                                match self.settings(setting) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing setting: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "set" => {
-                                match self.set(& tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error setting: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            "hist" | "history" => {
-                                match self.history(& tokens[1..]) {
-                                    Ok(_) => {},
-                                    Err(err) => {
-                                        println!("{}", format!("Error showing history: {}", &err).red().bold());
-                                    }
-                                }
-                            },
-
-                            _ => {
-                                println!("{}", "Unknown command".red().bold());
+                    match self.execute(æh, &input) {
+                        Executed::Quit(do_save) => break do_save,
+                        Executed::Failed if from_script && self.script_abort_on_error() => {
+                            let skipped = self.script.len();
+                            self.script.clear();
+                            if !interactive {
+                                return Err(format!("Script aborted at '{}', {} lines skipped", &input, skipped));
                             }
-                        }
-
-                    } else {
-                        println!("{}", "No command, nothing to do".dark_yellow().bold());
+                            println!("{}", format!("Script aborted, {} lines skipped", skipped).red().bold());
+                        },
+                        _ => {}
                     }
-
-                    self.history.add(&input);
                 },
                 Err(err) => {
                     println!("{}", format!("Error reading input: {}", err.to_string()).red().bold());
@@ -322,7 +206,7 @@ impl Commander {
             }
         };
         
-        Ok(do_save)
+        Ok(Some(do_save))
     }
 
 }
\ No newline at end of file
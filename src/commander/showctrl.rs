@@ -30,10 +30,14 @@
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use {
     crate::aelhometta::{
         Ælhometta,
         Hexly,
+        Integer,
+        Optuid,
         Uid
     },
     super::{
@@ -43,91 +47,131 @@ use {
     }
 };
 
+#[derive(Serialize)]
+struct CtrlView<'a> {
+    uid: Uid,
+    chain_start: Optuid,
+    exec: Optuid,
+    data_optuids: &'a [Optuid],
+    i_data_optuid: usize,
+    new_chain: Optuid,
+    new_controller: bool,
+    integer: Integer,
+    success: bool,
+    optuids: &'a [Optuid],
+    i_optuid: usize,
+    integers: &'a [Integer],
+    i_integer: usize,
+    optuid_channels: &'a [usize],
+    i_optuid_channel: usize,
+    integer_channels: &'a [usize],
+    i_integer_channel: usize,
+    generation: u128,
+    ticks: u128
+}
+
+fn ctrl_view(æh: &Ælhometta, cuid: Uid) -> Result<CtrlView<'_>, String> {
+    match æh.controller(&cuid) {
+        Some(ctrl) => Ok(CtrlView {
+            uid: cuid,
+            chain_start: ctrl.chain_start_optuid(),
+            exec: ctrl.exec_optuid(),
+            data_optuids: ctrl.data_optuids(),
+            i_data_optuid: ctrl.i_data_optuid(),
+            new_chain: ctrl.new_chain_optuid(),
+            new_controller: ctrl.new_controller_is_some(),
+            integer: ctrl.registers().integer(),
+            success: ctrl.flags().success(),
+            optuids: ctrl.optuids(),
+            i_optuid: ctrl.i_optuid(),
+            integers: ctrl.integers(),
+            i_integer: ctrl.i_integer(),
+            optuid_channels: ctrl.optuid_channels(),
+            i_optuid_channel: ctrl.i_optuid_channel(),
+            integer_channels: ctrl.integer_channels(),
+            i_integer_channel: ctrl.i_integer_channel(),
+            generation: ctrl.generation(),
+            ticks: ctrl.ticks()
+        }),
+        None => Err(String::from("Uid not found"))
+    }
+}
+
+fn print_ctrl_view(view: &CtrlView) {
+    println!("{:8}{}", " ", "Self-chain & Data optuids".dark_grey());
+    println!("{}{}", format!("{:24}", "ChainStart").dark_magenta(), view.chain_start.hexly().magenta());
+    println!("{}{}", format!("{:24}", "Exec").dark_magenta(), view.exec.hexly().magenta());
+    print!("{}", format!("{:24}", "Data").dark_magenta());
+    for i in 0..view.data_optuids.len() {
+        let mut ouid_hexly_stl = view.data_optuids[i].hexly().magenta();
+        if i == view.i_data_optuid {
+            ouid_hexly_stl = ouid_hexly_stl.bold();
+        }
+        print!("{}{}", ouid_hexly_stl, ", ".dark_grey());
+    }
+    println!("");
+    println!("{}{}", format!("{:24}", "Data index").dark_blue(), format!("{}", view.i_data_optuid).blue());
+    println!("{:8}{}", " ", "New chain".dark_grey());
+    println!("{}{}", format!("{:24}", "Chain").dark_magenta(), view.new_chain.hexly().magenta());
+    println!("{}{}", format!("{:24}", "Ctrl").dark_cyan(), format!("{}",if view.new_controller {"Some"} else {"None"}).blue());
+    println!("{:8}{}", " ", "Registers".dark_grey());
+    println!("{}{}", format!("{:24}", "Integer").dark_blue(), format!("{0}={0:X}h", view.integer).blue());
+    println!("{:8}{}", " ", "Flags".dark_grey());
+    println!("{}{}", format!("{:24}", "Success").dark_blue(), format!("{}", view.success).blue());
+    println!("{:8}{}", " ", "Node optuids".dark_grey());
+    print!("{}", format!("{:24}", "Optuids").dark_magenta());
+    for i in 0..view.optuids.len() {
+        let mut ouid_hexly_stl = view.optuids[i].hexly().magenta();
+        if i == view.i_optuid {
+            ouid_hexly_stl = ouid_hexly_stl.bold();
+        }
+        print!("{}{}", ouid_hexly_stl, ", ".dark_grey());
+    }
+    println!("");
+    println!("{}{}", format!("{:24}", "Optuid index").dark_blue(), format!("{}", view.i_optuid).blue());
+    println!("{:8}{}", " ", "Integers".dark_grey());
+    print!("{}", format!("{:24}", "Integers").dark_blue());
+    for i in 0..view.integers.len() {
+        let mut int_stl = format!("{0}={0:X}h", view.integers[i]).blue();
+        if i == view.i_integer {
+            int_stl = int_stl.bold();
+        }
+        print!("{}{}", int_stl, ", ".dark_grey());
+    }
+    println!("");
+    println!("{}{}", format!("{:24}", "Integer index").dark_blue(), format!("{}", view.i_integer).blue());
+    println!("{:8}{}", " ", "Optuid channels".dark_grey());
+    print!("{}", format!("{:24}", "Channels").dark_magenta());
+    for i in 0..view.optuid_channels.len() {
+        let mut chan_stl = format!("{0}={0:X}h", view.optuid_channels[i]).magenta();
+        if i == view.i_optuid_channel {
+            chan_stl = chan_stl.bold();
+        }
+        print!("{}{}", chan_stl, ", ".dark_grey());
+    }
+    println!("");
+    println!("{}{}", format!("{:24}", "Channel index").dark_blue(), format!("{}", view.i_optuid_channel).blue());
+    println!("{:8}{}", " ", "Integer channels".dark_grey());
+    print!("{}", format!("{:24}", "Channels").dark_blue());
+    for i in 0..view.integer_channels.len() {
+        let mut chan_stl = format!("{0}={0:X}h", view.integer_channels[i]).blue();
+        if i == view.i_integer_channel {
+            chan_stl = chan_stl.bold();
+        }
+        print!("{}{}", chan_stl, ", ".dark_grey());
+    }
+    println!("");
+    println!("{}{}", format!("{:24}", "Channel index").dark_blue(), format!("{}", view.i_integer_channel).blue());
+    println!("{:8}{}", " ", "Debug".dark_grey());
+    println!("{}{}", format!("{:24}", "Generation").dark_blue(), format!("{}", view.generation).blue());
+    println!("{}{}", format!("{:24}", "Ticks").dark_blue(), format!("{}", view.ticks).blue());
+}
+
 impl Commander {
     pub fn showctrl(&self, æh: &Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() >= 1 {
             match paramstr[0].parse_hex::<Uid>() {
-                Ok(cuid) => {
-                    match æh.controller(&cuid) {
-                        Some(ctrl) => {
-                            println!("{:8}{}", " ", "Self-chain & Data optuids".dark_grey());
-                            println!("{}{}", format!("{:24}", "ChainStart").dark_magenta(), ctrl.chain_start_optuid().hexly().magenta());
-                            println!("{}{}", format!("{:24}", "Exec").dark_magenta(), ctrl.exec_optuid().hexly().magenta());
-                            print!("{}", format!("{:24}", "Data").dark_magenta());
-                            let data_optuids = ctrl.data_optuids();
-                            for i in 0..data_optuids.len() {
-                                let mut ouid_hexly_stl = data_optuids[i].hexly().magenta();
-                                if i == ctrl.i_data_optuid() {
-                                    ouid_hexly_stl = ouid_hexly_stl.bold();
-                                }
-                                print!("{}{}", ouid_hexly_stl, ", ".dark_grey());
-                            }
-                            println!("");
-                            println!("{}{}", format!("{:24}", "Data index").dark_blue(), format!("{}", ctrl.i_data_optuid()).blue());
-                            println!("{:8}{}", " ", "New chain".dark_grey());
-                            println!("{}{}", format!("{:24}", "Chain").dark_magenta(), ctrl.new_chain_optuid().hexly().magenta());
-                            println!("{}{}", format!("{:24}", "Ctrl").dark_cyan(), format!("{}",if ctrl.new_controller_is_some() {"Some"} else {"None"}).blue());
-                            println!("{:8}{}", " ", "Registers".dark_grey());
-                            println!("{}{}", format!("{:24}", "Integer").dark_blue(), format!("{0}={0:X}h", ctrl.registers().integer()).blue());
-                            println!("{:8}{}", " ", "Flags".dark_grey());
-                            println!("{}{}", format!("{:24}", "Success").dark_blue(), format!("{}", ctrl.flags().success()).blue());
-                            println!("{:8}{}", " ", "Node optuids".dark_grey());
-                            print!("{}", format!("{:24}", "Optuids").dark_magenta());
-                            let optuids = ctrl.optuids();
-                            for i in 0..optuids.len() {
-                                let mut ouid_hexly_stl = optuids[i].hexly().magenta();
-                                if i == ctrl.i_optuid() {
-                                    ouid_hexly_stl = ouid_hexly_stl.bold();
-                                }
-                                print!("{}{}", ouid_hexly_stl, ", ".dark_grey());
-                            }
-                            println!("");
-                            println!("{}{}", format!("{:24}", "Optuid index").dark_blue(), format!("{}", ctrl.i_optuid()).blue());
-                            println!("{:8}{}", " ", "Integers".dark_grey());
-                            print!("{}", format!("{:24}", "Integers").dark_blue());
-                            let integers = ctrl.integers();
-                            for i in 0..integers.len() {
-                                let mut int_stl = format!("{0}={0:X}h", integers[i]).blue();
-                                if i == ctrl.i_integer() {
-                                    int_stl = int_stl.bold();
-                                }
-                                print!("{}{}", int_stl, ", ".dark_grey());
-                            }
-                            println!("");
-                            println!("{}{}", format!("{:24}", "Integer index").dark_blue(), format!("{}", ctrl.i_integer()).blue());
-                            println!("{:8}{}", " ", "Optuid channels".dark_grey());
-                            print!("{}", format!("{:24}", "Channels").dark_magenta());
-                            let optuid_channels = ctrl.optuid_channels();
-                            for i in 0..optuid_channels.len() {
-                                let mut chan_stl = format!("{0}={0:X}h", optuid_channels[i]).magenta();
-                                if i == ctrl.i_optuid_channel() {
-                                    chan_stl = chan_stl.bold();
-                                }
-                                print!("{}{}", chan_stl, ", ".dark_grey());
-                            }
-                            println!("");
-                            println!("{}{}", format!("{:24}", "Channel index").dark_blue(), format!("{}", ctrl.i_optuid_channel()).blue());
-                            println!("{:8}{}", " ", "Integer channels".dark_grey());
-                            print!("{}", format!("{:24}", "Channels").dark_blue());
-                            let integer_channels = ctrl.integer_channels();
-                            for i in 0..integer_channels.len() {
-                                let mut chan_stl = format!("{0}={0:X}h", integer_channels[i]).blue();
-                                if i == ctrl.i_integer_channel() {
-                                    chan_stl = chan_stl.bold();
-                                }
-                                print!("{}{}", chan_stl, ", ".dark_grey());
-                            }
-                            println!("");
-                            println!("{}{}", format!("{:24}", "Channel index").dark_blue(), format!("{}", ctrl.i_integer_channel()).blue());
-                            println!("{:8}{}", " ", "Debug".dark_grey());
-                            println!("{}{}", format!("{:24}", "Generation").dark_blue(), format!("{}", ctrl.generation()).blue());
-                            println!("{}{}", format!("{:24}", "Ticks").dark_blue(), format!("{}", ctrl.ticks()).blue());
-            
-                            Ok(())
-                        },
-                        None => Err(format!("Uid not found"))
-                    }
-                },
+                Ok(cuid) => self.present(&ctrl_view(æh, cuid)?, print_ctrl_view),
                 Err(err) => err.prefixised("ctrl uid")
             }
         } else {
@@ -30,6 +30,8 @@
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use {
     crate::{
         aelhometta::{
@@ -48,21 +50,39 @@ use {
     }
 };
 
+#[derive(Serialize)]
+struct NodeView {
+    uid: Uid,
+    byte: u8,
+    content: String,
+    next: Optuid,
+    altnext: Optuid
+}
+
+fn node_view(æh: &Ælhometta, nuid: Uid) -> Result<NodeView, String> {
+    match æh.node(&nuid) {
+        Some(node) => Ok(NodeView {
+            uid: nuid,
+            byte: node.b_content(),
+            content: format!("{:?}", Content::ot_bits(node.b_content())),
+            next: Optuid::ot_bits(node.b_next()),
+            altnext: Optuid::ot_bits(node.b_altnext())
+        }),
+        None => Err(String::from("Uid not found"))
+    }
+}
+
+fn print_node_view(view: &NodeView) {
+    println!("{}{}{}", format!("{:16}", "Content").dark_yellow(), format!("{:4}", format!("{:02X}", view.byte)).blue(), view.content.as_str().yellow());
+    println!("{}{}", format!("{:16}", "Next").dark_magenta(), view.next.hexly().magenta());
+    println!("{}{}", format!("{:16}", "AltNext").dark_magenta(), view.altnext.hexly().magenta());
+}
+
 impl Commander {
     pub fn shownode(&self, æh: &Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() > 0 {
             match paramstr[0].parse_hex::<Uid>() {
-                Ok(nuid) => {
-                    match æh.node(&nuid) {
-                        Some(node) => {
-                            println!("{}{}{}", format!("{:16}", "Content").dark_yellow(), format!("{:4}", format!("{:02X}", node.b_content())).blue(), format!("{:?}", Content::ot_bits(node.b_content())).yellow());
-                            println!("{}{}", format!("{:16}", "Next").dark_magenta(), Optuid::ot_bits(node.b_next()).hexly().magenta());
-                            println!("{}{}", format!("{:16}", "AltNext").dark_magenta(), Optuid::ot_bits(node.b_altnext()).hexly().magenta());
-                            Ok(())
-                        },
-                        None => Err(format!("Uid not found"))
-                    }
-                },
+                Ok(nuid) => self.present(&node_view(æh, nuid)?, print_node_view),
                 Err(err) => err.prefixised("node uid")
             }
         } else {
@@ -30,6 +30,8 @@
 
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use std::collections::HashSet;
 
 use crate::aelhometta::Hexly;
@@ -51,6 +53,78 @@ use {
     }
 };
 
+#[derive(Serialize)]
+struct SeqNodeView {
+    uid: Uid,
+    content: String
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum SeqEnd {
+    Loop,
+    Limit,
+    Missing, // next uid not found
+    End
+}
+
+#[derive(Serialize)]
+struct SeqView {
+    nodes: Vec<SeqNodeView>,
+    end: SeqEnd
+}
+
+fn seq_view(æh: &Ælhometta, snuid: Uid, limit: usize) -> Result<SeqView, String> {
+    let mut node = æh.node(&snuid).ok_or(String::from("Uid not found"))?;
+    let mut nuid = snuid;
+    let mut nuids_set: HashSet<Uid> = HashSet::new();
+    let mut nodes: Vec<SeqNodeView> = Vec::new();
+    let end = loop {
+        nodes.push(SeqNodeView {
+            uid: nuid,
+            content: format!("{:?}", Content::ot_bits(node.b_content()))
+        });
+        if ! nuids_set.insert(nuid) {
+            break SeqEnd::Loop;
+        };
+        if nodes.len() >= limit {
+            break SeqEnd::Limit;
+        }
+        match Optuid::ot_bits(node.b_next()) {
+            Some(nnuid) => {
+                nuid = nnuid;
+                match æh.node(&nuid) {
+                    Some(nnode) => {
+                        node = nnode;
+                    },
+                    None => {
+                        break SeqEnd::Missing;
+                    }
+                }
+            },
+            None => {
+                break SeqEnd::End;
+            }
+        }
+    };
+    Ok(SeqView {
+        nodes,
+        end
+    })
+}
+
+fn print_seq_view(view: &SeqView) {
+    for snv in &view.nodes {
+        println!("{}{}", format!("{:20}", Some(snv.uid).hexly()).magenta(), snv.content.as_str().yellow());
+    }
+    match view.end {
+        SeqEnd::Loop => println!("{}", "LOOP".dark_yellow().bold()),
+        SeqEnd::Limit => println!("{}", "LIMIT".dark_yellow().bold()),
+        SeqEnd::Missing => println!("{}", "Next uid not found".red().bold()),
+        SeqEnd::End => println!("{}", "×".magenta())
+    }
+}
+
 impl Commander {
     pub fn showseq(&self, æh: &Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         let (snuid, limit) = if paramstr.len() >= 1 {
@@ -81,45 +155,7 @@ impl Commander {
             return Err(String::from("Uid not specified"))
         };
 
-        match æh.node(&snuid) {
-            Some(snode) => {
-                let mut nuid = snuid;
-                let mut node = snode;
-                let mut nuids_set: HashSet<Uid> = HashSet::new();
-                let mut n: usize = 0;
-                loop {
-                    println!("{}{}", format!("{:20}", Some(nuid).hexly()).magenta(), format!("{:?}", Content::ot_bits(node.b_content())).yellow());
-                    n += 1;
-                    if ! nuids_set.insert(nuid) {
-                        println!("{}", "LOOP".dark_yellow().bold());
-                        break;
-                    };
-                    if n >= limit {
-                        println!("{}", "LIMIT".dark_yellow().bold());
-                        break;
-                    }
-                    match Optuid::ot_bits(node.b_next()) {
-                        Some(nnuid) => {
-                            nuid = nnuid;
-                            match æh.node(&nuid) {
-                                Some(nnode) => {
-                                    node = nnode;
-                                },
-                                None => {
-                                    println!("{}", "Next uid not found".red().bold());
-                                }
-                            }
-                        },
-                        None => {
-                            println!("{}", "×".magenta());
-                            break;
-                        }
-                    }
-                }
-                Ok(())
-            },
-            None => Err(format!("Uid not found"))
-        }
+        self.present(&seq_view(æh, snuid, limit)?, print_seq_view)
     }
 
 }
\ No newline at end of file
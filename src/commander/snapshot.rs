@@ -0,0 +1,283 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::prelude::*;
+
+use crossterm::style::Stylize;
+
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf
+    }
+};
+
+use {
+    crate::aelhometta::{
+        BackgroundSave,
+        Ælhometta
+    },
+    super::{
+        Commander,
+        ParseErrorPrefixise
+    }
+};
+
+const SNAPSHOT_PREFIX: &str = "aelhometta-";
+const SNAPSHOT_SUFFIX: &str = ".bin";
+
+impl Commander {
+    // Oldest first, since names begin with UTC timestamp
+    pub(super) fn snapshot_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = match fs::read_dir(& self.settings.snapshots_dir) {
+            Ok(entries) => entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).filter(|path| {
+                path.file_name().and_then(|name| name.to_str()).is_some_and(|name|
+                    name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(SNAPSHOT_SUFFIX)
+                )
+            }).collect(),
+            Err(_) => Vec::new()
+        };
+        files.sort();
+        files
+    }
+
+    fn snapshot_new_filepath(&self, æh: &Ælhometta) -> Result<PathBuf, String> {
+        fs::create_dir_all(& self.settings.snapshots_dir).map_err(|err| format!("Cannot create '{}': {}", & self.settings.snapshots_dir, &err))?;
+        Ok(Path::new(& self.settings.snapshots_dir).join(format!("{}{}-{}{}", SNAPSHOT_PREFIX, Utc::now().format("%Y%m%d-%H%M%S%.3f"), æh.age(), SNAPSHOT_SUFFIX)))
+    }
+
+    fn snapshots_rotate(&self) -> Result<(), String> {
+        let files = self.snapshot_files();
+        if files.len() > self.settings.snapshots_max {
+            for path in & files[..(files.len() - self.settings.snapshots_max)] {
+                fs::remove_file(path).map_err(|err| format!("Cannot remove '{}': {}", path.display(), &err))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns filepath of new snapshot, or None if snapshots are disabled
+    pub(super) fn snapshot_take(&self, æh: &Ælhometta) -> Result<Option<String>, String> {
+        if self.settings.snapshots_max == 0 {
+            return Ok(None);
+        }
+        let path = self.snapshot_new_filepath(æh)?;
+        let filepath = path.display().to_string();
+        æh.save(&filepath, self.settings.compress_saves)?;
+        self.snapshots_rotate()?;
+        Ok(Some(filepath))
+    }
+
+    // Saves current state as if on quit, and keeps copy of it among snapshots.
+    // Unless journal is on, writing may be left to background, see background_save_poll()
+    pub(super) fn autosave(&mut self, æh: &mut Ælhometta) -> Result<(), String> {
+        if self.settings.autosave_background && !self.settings.journal {
+            let mut filepaths = vec![self.world_filepath.clone()];
+            if self.settings.snapshots_max > 0 {
+                filepaths.push(self.snapshot_new_filepath(æh)?.display().to_string());
+            }
+            æh.journal_stop();
+            self.background_save = Some(æh.save_background(&filepaths, self.settings.compress_saves)?);
+            return Ok(());
+        }
+        self.save_world(æh)?;
+        if self.settings.journal {
+            // The default file is only the base then, so the snapshot is saved in full
+            self.snapshot_take(æh)?;
+        } else if self.settings.snapshots_max > 0 {
+            let path = self.snapshot_new_filepath(æh)?;
+            fs::copy(&self.world_filepath, &path).map_err(|err| format!("Cannot copy '{}' to '{}': {}", &self.world_filepath, path.display(), &err))?;
+            self.snapshots_rotate()?;
+        }
+        Ok(())
+    }
+
+    pub(super) fn background_save_running(&self) -> bool {
+        self.background_save.is_some()
+    }
+
+    fn background_save_join(&self, bs: BackgroundSave) -> Result<(), String> {
+        let snapshot_taken = bs.filepaths().len() > 1;
+        bs.join()?;
+        if snapshot_taken {
+            self.snapshots_rotate()?;
+        }
+        Ok(())
+    }
+
+    // Result of background save, if it has finished since the previous call, for the caller to report
+    pub(super) fn background_save_poll(&mut self) -> Option<Result<(), String>> {
+        if self.background_save.as_ref().is_some_and(|bs| bs.is_finished()) {
+            let result = self.background_save.take().map(|bs| self.background_save_join(bs));
+            self.background_save_done = result.as_ref().is_some_and(|r| r.is_ok());
+            result
+        } else {
+            None
+        }
+    }
+
+    pub fn background_save_wait(&mut self) -> Result<(), String> {
+        match self.background_save.take() {
+            Some(bs) => self.background_save_join(bs),
+            None => Ok(())
+        }
+    }
+
+    // Continuation of status line
+    pub(super) fn print_background_save(&mut self) {
+        if let Some(ref bs) = self.background_save {
+            print!(" {} {} {} {}", "|".dark_grey(), "Saving".dark_yellow(), format!("{:.0}%", 100.0 * bs.progress()).yellow(),
+                format!("({} MiB held, {:.2} s paused)", bs.payload_size() >> 20, bs.pause().as_secs_f64()).dark_yellow());
+        } else if self.background_save_done {
+            print!(" {} {}", "|".dark_grey(), "Saved".dark_green());
+            self.background_save_done = false;
+        }
+    }
+
+    // Used before irreversible changes such as cleanse
+    pub(super) fn snapshot_before(&self, æh: &Ælhometta) -> Result<(), String> {
+        match self.snapshot_take(æh) {
+            Ok(Some(filepath)) => {
+                println!("{} {}", "Snapshot taken:".dark_grey(), filepath.dark_grey().bold());
+                Ok(())
+            },
+            Ok(None) => Ok(()),
+            Err(err) => Err(format!("Cannot take snapshot beforehand: {}", &err))
+        }
+    }
+
+    pub fn snapshot(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
+        if !paramstr.is_empty() {
+            let command = paramstr[0].to_lowercase();
+            match command.as_str() {
+                "list" => {
+                    for (i, path) in self.snapshot_files().iter().enumerate() {
+                        let size = fs::metadata(path).map_or(0, |md| md.len());
+                        println!("{}{}{}",
+                            format!("{:<6}", i).dark_grey(),
+                            format!("{:<56}", path.display()).magenta(),
+                            format!("{:>10} MiB", size >> 20).dark_blue()
+                        );
+                    }
+                    Ok(())
+                },
+
+                "take" => {
+                    match self.snapshot_take(æh)? {
+                        Some(filepath) => {
+                            println!("{} {}", "Snapshot taken:".green(), filepath.green().bold());
+                            Ok(())
+                        },
+                        None => Err(String::from("Snapshots are disabled, snapshots_max is 0"))
+                    }
+                },
+
+                "restore" => {
+                    if paramstr.len() > 1 {
+                        match paramstr[1].parse::<usize>() {
+                            Ok(index) => {
+                                let files = self.snapshot_files();
+                                if index < files.len() {
+                                    let filepath = files[index].display().to_string();
+                                    // Read before the snapshot below, whose rotation may remove this very file
+                                    let mut æh_restored = Ælhometta::load_unexposed(&filepath)?;
+                                    // Taken while peer is as it is, so that restoring this snapshot brings it back
+                                    self.snapshot_before(æh)?;
+                                    // Peer must release its port before the restored one can expose itself
+                                    let was_exposed = æh.peer_repose().is_ok();
+                                    if æh_restored.exposed() && !self.no_peer {
+                                        if let Err(err) = æh_restored.peer_expose() {
+                                            if was_exposed {
+                                                æh.peer_expose().unwrap_or(());
+                                            }
+                                            return Err(format!("Cannot read from '{}': {}", filepath, &err));
+                                        }
+                                    }
+                                    *æh = æh_restored;
+                                    println!("{} {}", "Restored from".green(), filepath.green().bold());
+                                    Ok(())
+                                } else {
+                                    Err(format!("There are only {} snapshots", files.len()))
+                                }
+                            },
+                            Err(err) => err.prefixised("index")
+                        }
+                    } else {
+                        Err(String::from("Index not specified"))
+                    }
+                },
+
+                _ => Err(String::from("Unknown subcommand"))
+            }
+        } else {
+            Err(String::from("Subcommand not specified"))
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        process,
+        thread,
+        time::Duration
+    };
+
+    use super::*;
+
+    #[test]
+    fn restore_oldest_at_cap() {
+        let dir = env::temp_dir().join(format!("aelhometta-test-snapshots-{}", process::id()));
+        fs::remove_dir_all(&dir).unwrap_or(());
+        let mut comm = Commander::new_default();
+        comm.settings.snapshots_dir = dir.display().to_string();
+        comm.settings.snapshots_max = 3;
+        comm.no_peer = true;
+        let mut æh = Ælhometta::new(4);
+        for _ in 0..3 {
+            æh.tick(&None);
+            comm.snapshot_take(&æh).unwrap();
+            thread::sleep(Duration::from_millis(2)); // names differ in milliseconds
+        }
+        æh.tick(&None);
+        let result = comm.snapshot(&mut æh, &["restore", "0"]);
+        let files = comm.snapshot_files();
+        fs::remove_dir_all(&dir).unwrap_or(());
+        assert_eq!(result, Ok(()));
+        assert_eq!(æh.age(), 1);
+        // The oldest one has given way to the one taken before restoring
+        assert_eq!(files.len(), 3);
+        assert!(files[2].display().to_string().ends_with("-4.bin"));
+    }
+
+}
@@ -0,0 +1,87 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use std::{
+    fs,
+    io::{
+        self,
+        Read
+    }
+};
+
+use super::Commander;
+
+const SCRIPT_MAX_SOURCED: usize = 0x10000; // lines at once, against a script that sources itself
+
+// Empty lines and those beginning with '#' are skipped. "-" is stdin, read to its end
+fn script_lines(filepath: &str) -> Result<Vec<String>, String> {
+    let mut text = String::new();
+    if filepath == "-" {
+        io::stdin().read_to_string(&mut text).map_err(|err| format!("Cannot read from stdin: {}", &err))?;
+    } else {
+        text = fs::read_to_string(filepath).map_err(|err| format!("Cannot read from '{}': {}", filepath, &err))?;
+    }
+    Ok(text.lines().map(|line| line.trim()).filter(|line| !line.is_empty() && !line.starts_with('#')).map(String::from).collect())
+}
+
+impl Commander {
+    // Lines go after those already pending
+    pub fn script_load(&mut self, filepath: &str) -> Result<(), String> {
+        let lines = script_lines(filepath)?;
+        self.script.extend(lines);
+        Ok(())
+    }
+
+    pub(super) fn script_abort_on_error(&self) -> bool {
+        self.script_abort_on_error.unwrap_or(self.settings.script_abort_on_error)
+    }
+
+    // Lines go before those already pending, so that a script can source another one
+    pub fn source(&mut self, paramstr: &[&str]) -> Result<(), String> {
+        if !paramstr.is_empty() {
+            let lines = script_lines(paramstr[0])?;
+            self.script_sourced += lines.len();
+            if self.script_sourced > SCRIPT_MAX_SOURCED {
+                self.script.clear();
+                return Err(format!("Too many lines at once, more than {}: does script source itself?", SCRIPT_MAX_SOURCED));
+            }
+            println!("{} {}", format!("{}", lines.len()).green().bold(), "lines to execute".green());
+            for line in lines.into_iter().rev() {
+                self.script.push_front(line);
+            }
+            Ok(())
+        } else {
+            Err(String::from("Path not specified"))
+        }
+    }
+
+}
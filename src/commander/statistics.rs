@@ -28,10 +28,15 @@
  * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
  */
 
+use chrono::prelude::*;
+
 use crossterm::style::Stylize;
 
+use serde::Serialize;
+
 use std::{
     collections::BTreeMap,
+    fs::OpenOptions,
     io::{
         self,
         Write
@@ -41,106 +46,324 @@ use std::{
 use {
     crate::{
         aelhometta::{
+            ChannelsStatistics,
+            ControllerGenerationStatistics,
+            IoPairStatistics,
             Ælhometta,
             CONTENTS
         },
         serbin::ToBits
     },
-    super::Commander
+    super::{
+        Commander,
+        ParseErrorPrefixise
+    }
 };
 
+#[derive(Serialize)]
+pub(super) struct ChanStatsView {
+    #[serde(flatten)]
+    stats: ChannelsStatistics,
+    optuids: usize,
+    integers: usize
+}
+
+#[derive(Serialize)]
+pub(super) struct ContentCountView {
+    byte: u8,
+    content: String,
+    count: usize
+}
+
+#[derive(Serialize)]
+pub(super) struct ContStatsView {
+    nodes: usize,
+    contents: Vec<ContentCountView>
+}
+
+#[derive(Serialize)]
+struct IoStatsView {
+    bins: usize,
+    samples: usize,
+    pairs: Vec<IoPairStatistics> // by mutual information, descending
+}
+
+#[derive(Serialize)]
+struct TaskCountView<'a> {
+    function: &'static str,
+    output: usize,
+    inputs: &'a [usize],
+    attempts: u128,
+    completions: u128
+}
+
+#[derive(Serialize)]
+struct TaskStatsView<'a> {
+    tasks: Vec<TaskCountView<'a>>,
+    pending_extra_ticks: usize,
+    renewed_controllers: usize
+}
+
+#[derive(Serialize)]
+pub(super) struct TickStatsView {
+    spaces: u128,
+    branches_main: u128,
+    branches_alt: u128,
+    commands: BTreeMap<String, u128>,
+    constructions: BTreeMap<String, u128>
+}
+
+// Counts since which stats log gives deltas
+pub(super) struct StatsLogTotals {
+    commands: BTreeMap<String, u128>,
+    constructions: BTreeMap<String, u128>,
+    branches_main: u128,
+    branches_alt: u128
+}
+
+#[derive(Serialize)]
+struct GlitchCountsView {
+    background: u128,
+    replicate: u128,
+    construct: u128
+}
+
+#[derive(Serialize)]
+struct StatsLogRow {
+    time: String,
+    age: u128,
+    nodes: usize,
+    controllers: usize,
+    commands: BTreeMap<String, u128>, // deltas
+    constructions: BTreeMap<String, u128>, // deltas
+    branches_main: u128, // delta
+    branches_alt: u128, // delta
+    glitches: GlitchCountsView, // totals
+    generation: ControllerGenerationStatistics,
+    channels: ChannelsStatistics
+}
+
+impl StatsLogTotals {
+    pub(super) fn new(æh: &Ælhometta) -> Self {
+        let ticks = tick_stats_view(æh);
+        Self {
+            commands: ticks.commands,
+            constructions: ticks.constructions,
+            branches_main: ticks.branches_main,
+            branches_alt: ticks.branches_alt
+        }
+    }
+
+}
+
+fn deltas(totals: &BTreeMap<String, u128>, last_totals: &BTreeMap<String, u128>) -> BTreeMap<String, u128> {
+    totals.iter().map(|(name, count)| (name.clone(), count.saturating_sub(* last_totals.get(name).unwrap_or(&0)))).collect()
+}
+
+pub(super) fn chan_stats_view(æh: &Ælhometta) -> ChanStatsView {
+    ChanStatsView {
+        stats: æh.channels_statistics(),
+        optuids: æh.ether_optuids().len(),
+        integers: æh.ether_integers().len()
+    }
+}
+
+pub(super) fn cont_stats_view(æh: &Ælhometta) -> ContStatsView {
+    let cont_stats = æh.content_statistics();
+    ContStatsView {
+        nodes: æh.num_nodes(),
+        contents: CONTENTS.iter().map(|content| ContentCountView {
+            byte: content.to_bits(),
+            content: format!("{:?}", content),
+            count: * cont_stats.get(content).unwrap_or(&0)
+        }).collect()
+    }
+}
+
+fn io_stats_view(æh: &Ælhometta, bins: usize) -> IoStatsView {
+    let mut pairs = æh.io_statistics(bins);
+    pairs.sort_by(|a, b| b.mutual_information.total_cmp(& a.mutual_information));
+    IoStatsView {
+        bins,
+        samples: æh.io_samples_num(),
+        pairs
+    }
+}
+
+fn task_stats_view(æh: &Ælhometta) -> TaskStatsView<'_> {
+    TaskStatsView {
+        tasks: æh.tasks().iter().map(|task| TaskCountView {
+            function: task.function().name(),
+            output: task.output(),
+            inputs: task.inputs(),
+            attempts: task.attempts_count(),
+            completions: task.completions_count()
+        }).collect(),
+        pending_extra_ticks: æh.bonus_ticks_num(),
+        renewed_controllers: æh.renewals_num()
+    }
+}
+
+pub(super) fn tick_stats_view(æh: &Ælhometta) -> TickStatsView {
+    TickStatsView {
+        spaces: æh.spaces_count(),
+        branches_main: æh.branches_main_count(),
+        branches_alt: æh.branches_alt_count(),
+        commands: æh.commands_count().iter().map(|(command, count)| (format!("{:?}", *command), *count)).collect(),
+        constructions: æh.constructions_count().iter().map(|(construction, count)| (format!("{:?}", *construction), *count)).collect()
+    }
+}
+
+fn print_chan_stats_view(view: &ChanStatsView) {
+    println!("{} {} {}{}{}",
+        "Optuid:".dark_magenta(),
+        "non-none".dark_grey(),
+        format!("{}", view.stats.optuids_some).magenta().bold(),
+        "/".dark_grey(),
+        format!("{}", view.optuids).magenta()
+    );
+    println!("{} {} {}{}{}",
+        "Integer:".dark_blue(),
+        "non-zero".dark_grey(),
+        format!("{}", view.stats.integers_nonzero).blue().bold(),
+        "/".dark_grey(),
+        format!("{}", view.integers).blue()
+    );
+}
+
+fn print_cont_stats_view(view: &ContStatsView) {
+    for ccv in &view.contents {
+        println!("{}{}{}{}",
+            format!("{:<4}", format!("{:02X}", ccv.byte)).blue(),
+            format!("{:<40}", ccv.content).yellow(),
+            format!("{:>12}", ccv.count).dark_yellow(),
+            format!("{:>11.3} %", (ccv.count as f64) * 100.0 / (view.nodes.max(1) as f64)).dark_blue()
+        );
+    }
+}
+
+fn print_io_stats_view(view: &IoStatsView) {
+    println!("{}{}{}{}",
+        format!("{:>10}", "Input").dark_grey(),
+        format!("{:>10}", "Output").dark_grey(),
+        format!("{:>16}", "Correlation").dark_grey(),
+        format!("{:>16}", "MI, bits").dark_grey()
+    );
+    for ps in &view.pairs {
+        println!("{}{}{}{}",
+            format!("{:>10}", ps.input).blue(),
+            format!("{:>10}", ps.output).blue(),
+            format!("{:>16.4}", ps.correlation).yellow(),
+            format!("{:>16.4}", ps.mutual_information).green()
+        );
+    }
+    println!("{}{}",
+        format!("{:36}", "Samples").dark_green(),
+        format!("{:>10}", view.samples).green()
+    );
+}
+
+fn print_task_stats_view(view: &TaskStatsView) {
+    for (i, tcv) in view.tasks.iter().enumerate() {
+        println!("{}{}{}{}",
+            format!("{:<6}", i).dark_grey(),
+            format!("{:<40}", format!("{} {} ← {}", tcv.function, tcv.output, tcv.inputs.iter().map(|chan| chan.to_string()).collect::<Vec<String>>().join(" "))).yellow(),
+            format!("{:>16}", tcv.completions).blue(),
+            format!("{:>11.3} %", (tcv.completions as f64) * 100.0 / (tcv.attempts.max(1) as f64)).dark_blue()
+        );
+    }
+    println!("{}{}",
+        format!("{:46}", "Pending extra ticks").dark_green(),
+        format!("{:>16}", view.pending_extra_ticks).green()
+    );
+    println!("{}{}",
+        format!("{:46}", "Renewed controllers").dark_green(),
+        format!("{:>16}", view.renewed_controllers).green()
+    );
+}
+
+fn print_tick_stats_view(view: &TickStatsView) {
+    println!("{}{}",
+        format!("{:32}", "Spaces").dark_yellow(),
+        format!("{:>16}", view.spaces).blue()
+    );
+
+    println!("{:4}{}", " ", "Branches".dark_grey());
+    println!("{}{}",
+        format!("{:32}", "Branches (main)").dark_magenta(),
+        format!("{:>16}", view.branches_main).blue()
+    );
+    println!("{}{}",
+        format!("{:32}", "Branches (alt)").dark_magenta(),
+        format!("{:>16}", view.branches_alt).blue()
+    );
+
+    println!("{:4}{}", " ", "Commands".dark_grey());
+    for (comm_str, count) in &view.commands {
+        println!("{}{}",
+            format!("{:<32}", comm_str).yellow(),
+            format!("{:>16}", *count).blue()
+        );
+    }
+
+    println!("{:4}{}", " ", "Constructions".dark_grey());
+    for (cons_str, count) in &view.constructions {
+        println!("{}{}",
+            format!("{:<32}", cons_str).magenta(),
+            format!("{:>16}", *count).blue()
+        );
+    }
+}
+
 impl Commander {
     pub fn statistics(&self, æh: &Ælhometta, paramstr: &[&str]) -> Result<(), String> {
         if paramstr.len() >= 1 {
             let topic = paramstr[0].to_lowercase();
             match topic.as_str() {
                 "cgen" => {
-                    let cg_stats = æh.generation_statistics();
-                    println!("{}{}", format!("{:12}", "Minimum").dark_blue(), format!("{:>12}", cg_stats.minimum).blue());
-                    println!("{}{}", format!("{:12}", "Average").dark_blue(), format!("{:>12}", cg_stats.average).blue());
-                    println!("{}{}", format!("{:12}", "Maximum").dark_blue(), format!("{:>12}", cg_stats.maximum).blue());
-                    Ok(())
+                    self.present(&æh.generation_statistics(), |cg_stats| {
+                        println!("{}{}", format!("{:12}", "Minimum").dark_blue(), format!("{:>12}", cg_stats.minimum).blue());
+                        println!("{}{}", format!("{:12}", "Average").dark_blue(), format!("{:>12}", cg_stats.average).blue());
+                        println!("{}{}", format!("{:12}", "Maximum").dark_blue(), format!("{:>12}", cg_stats.maximum).blue());
+                    })
                 },
 
-                "chan" => {
-                    let ch_stats = æh.channels_statistics();
-                    println!("{} {} {}{}{}",
-                        "Optuid:".dark_magenta(),
-                        "non-none".dark_grey(),
-                        format!("{}", ch_stats.optuids_some).magenta().bold(),
-                        "/".dark_grey(),
-                        format!("{}", æh.ether_optuids().len()).magenta()
-                    );
-                    println!("{} {} {}{}{}",
-                        "Integer:".dark_blue(),
-                        "non-zero".dark_grey(),
-                        format!("{}", ch_stats.integers_nonzero).blue().bold(),
-                        "/".dark_grey(),
-                        format!("{}", æh.ether_integers().len()).blue()
-                    );
-                    Ok(())
-                },
+                "chan" => self.present(&chan_stats_view(æh), print_chan_stats_view),
+
+                "ext" | "extinct" => self.extinctions_show(),
     
                 "cont" => {
-                    print!("{}", "Obtaining content statistics... ".dark_blue());
-                    io::stdout().flush().unwrap_or(());
-                    let cont_stats = æh.content_statistics();
-                    println!("{}", "OK".dark_green().bold());
-    
-                    for content in CONTENTS {
-                        let count = * cont_stats.get(&content).unwrap_or(&0);
-                        println!("{}{}{}{}",
-                            format!("{:<4}", format!("{:02X}", content.to_bits())).blue(),
-                            format!("{:<40}", format!("{:?}", content)).yellow(),
-                            format!("{:>12}", count).dark_yellow(),
-                            format!("{:>11.3} %", ((count) as f64) * 100.0 / (æh.num_nodes().max(1) as f64)).dark_blue()
-                        );
+                    if !self.json_output() {
+                        print!("{}", "Obtaining content statistics... ".dark_blue());
+                        io::stdout().flush().unwrap_or(());
+                    }
+                    let view = cont_stats_view(æh);
+                    if !self.json_output() {
+                        println!("{}", "OK".dark_green().bold());
                     }
-                    Ok(())
+                    self.present(&view, print_cont_stats_view)
                 },
     
-                "tick" => {
-                    println!("{}{}",
-                        format!("{:32}", "Spaces").dark_yellow(),
-                        format!("{:>16}", æh.spaces_count()).blue()
-                    );
-
-                    println!("{:4}{}", " ", "Branches".dark_grey());
-                    println!("{}{}",
-                        format!("{:32}", "Branches (main)").dark_magenta(),
-                        format!("{:>16}", æh.branches_main_count()).blue()
-                    );
-                    println!("{}{}",
-                        format!("{:32}", "Branches (alt)").dark_magenta(),
-                        format!("{:>16}", æh.branches_alt_count()).blue()
-                    );
-
-                    println!("{:4}{}", " ", "Commands".dark_grey());
-                    let mut comm_str_count: BTreeMap<String, u128> = BTreeMap::new();
-                    for (command, count) in æh.commands_count() {
-                        comm_str_count.insert(format!("{:?}", *command), *count);
-                    }
-                    for (comm_str, count) in &comm_str_count {
-                        println!("{}{}",
-                            format!("{:<32}", comm_str).yellow(),
-                            format!("{:>16}", *count).blue()
-                        );
+                "io" => {
+                    let bins = if paramstr.len() >= 2 {
+                        match paramstr[1].parse::<usize>() {
+                            Ok(b) => b,
+                            Err(err) => {
+                                return err.prefixised("bins");
+                            }
+                        }
+                    } else {
+                        self.settings.io_bins
+                    };
+                    if bins < 2 {
+                        return Err(String::from("Number of bins must be at least 2"));
                     }
+                    self.present(&io_stats_view(æh, bins), print_io_stats_view)
+                },
 
-                    println!("{:4}{}", " ", "Constructions".dark_grey());
-                    let mut cons_str_count: BTreeMap<String, u128> = BTreeMap::new();
-                    for (construction, count) in æh.constructions_count() {
-                        cons_str_count.insert(format!("{:?}", *construction), *count);
-                    }
-                    for (cons_str, count) in &cons_str_count {
-                        println!("{}{}",
-                            format!("{:<32}", cons_str).magenta(),
-                            format!("{:>16}", *count).blue()
-                        );
-                    }
+                "task" => self.present(&task_stats_view(æh), print_task_stats_view),
 
-                    Ok(())
-                },
+                "tick" => self.present(&tick_stats_view(æh), print_tick_stats_view),
     
                 _ => {
                     Err(format!("Unknown topic '{}'", topic))
@@ -151,4 +374,67 @@ impl Commander {
         }
     }
 
+    // CSV lines "time,age,input,output,correlation,mutual_information", appended during run
+
+    pub(super) fn io_log_append(&self, æh: &Ælhometta) -> Result<(), String> {
+        let mut file = OpenOptions::new().create(true).append(true).open(& self.settings.io_log).map_err(|err| format!("Cannot open '{}': {}", & self.settings.io_log, &err))?;
+        if file.metadata().map_or(0, |md| md.len()) == 0 {
+            writeln!(file, "time,age,input,output,correlation,mutual_information").map_err(|err| err.to_string())?;
+        }
+        let time = Utc::now().format("%Y-%m-%d %H:%M:%S");
+        for ps in æh.io_statistics(self.settings.io_bins) {
+            writeln!(file, "{},{},{},{},{:.6},{:.6}", time, æh.age(), ps.input, ps.output, ps.correlation, ps.mutual_information).map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    // One row per call, CSV or JSON Lines, with deltas since the previous call (or since run start)
+
+    pub(super) fn stats_log_append(&self, æh: &Ælhometta, last_totals: &mut StatsLogTotals) -> Result<(), String> {
+        let totals = StatsLogTotals::new(æh);
+        let row = StatsLogRow {
+            time: Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            age: æh.age(),
+            nodes: æh.num_nodes(),
+            controllers: æh.num_controllers(),
+            commands: deltas(&totals.commands, &last_totals.commands),
+            constructions: deltas(&totals.constructions, &last_totals.constructions),
+            branches_main: totals.branches_main.saturating_sub(last_totals.branches_main),
+            branches_alt: totals.branches_alt.saturating_sub(last_totals.branches_alt),
+            glitches: GlitchCountsView {
+                background: æh.glitch_background_count(),
+                replicate: æh.glitch_replicate_count(),
+                construct: æh.glitch_construct_count()
+            },
+            generation: æh.generation_statistics(),
+            channels: æh.channels_statistics()
+        };
+        *last_totals = totals;
+
+        let mut file = OpenOptions::new().create(true).append(true).open(& self.settings.stats_log).map_err(|err| format!("Cannot open '{}': {}", & self.settings.stats_log, &err))?;
+        let empty = file.metadata().map_or(0, |md| md.len()) == 0;
+        match self.settings.stats_log_format.as_str() {
+            "jsonl" => {
+                let line = serde_json::to_string(&row).map_err(|err| err.to_string())?;
+                writeln!(file, "{}", line).map_err(|err| err.to_string())?;
+            },
+            _ => {
+                if empty {
+                    let mut header = vec![String::from("time"), String::from("age"), String::from("nodes"), String::from("controllers")];
+                    header.extend(row.commands.keys().map(|name| format!("comm_{}", name)));
+                    header.extend(row.constructions.keys().map(|name| format!("cons_{}", name)));
+                    header.extend(["branches_main", "branches_alt", "glitch_background", "glitch_replicate", "glitch_construct", "generation_min", "generation_avg", "generation_max", "optuids_some", "integers_nonzero"].map(String::from));
+                    writeln!(file, "{}", header.join(",")).map_err(|err| err.to_string())?;
+                }
+                let mut fields = vec![row.time.clone(), row.age.to_string(), row.nodes.to_string(), row.controllers.to_string()];
+                fields.extend(row.commands.values().map(|count| count.to_string()));
+                fields.extend(row.constructions.values().map(|count| count.to_string()));
+                fields.extend([row.branches_main, row.branches_alt, row.glitches.background, row.glitches.replicate, row.glitches.construct, row.generation.minimum, row.generation.average, row.generation.maximum].map(|n| n.to_string()));
+                fields.extend([row.channels.optuids_some, row.channels.integers_nonzero].map(|n| n.to_string()));
+                writeln!(file, "{}", fields.join(",")).map_err(|err| err.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
 }
\ No newline at end of file
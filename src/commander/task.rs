@@ -0,0 +1,135 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use {
+    crate::aelhometta::{
+        Reward,
+        TaskFunction,
+        Ælhometta,
+        TASK_FUNCTIONS
+    },
+    super::{
+        Commander,
+        ParseErrorPrefixise
+    }
+};
+
+impl Commander {
+    pub fn task(&self, æh: &mut Ælhometta, paramstr: &[&str]) -> Result<(), String> {
+        if !paramstr.is_empty() {
+            let subcommand = paramstr[0].to_lowercase();
+            match subcommand.as_str() {
+                "add" => {
+                    if paramstr.len() > 1 {
+                        let function_name = paramstr[1].to_lowercase();
+                        let function = TaskFunction::from_name(&function_name).ok_or(format!("Unknown function '{}', available are: {}", function_name, TASK_FUNCTIONS.iter().map(|(_, name)| *name).collect::<Vec<&str>>().join(", ")))?;
+                        let arity = function.arity();
+                        if paramstr.len() > 2 + arity {
+                            let output = match paramstr[2].parse::<usize>() {
+                                Ok(chan) => chan,
+                                Err(err) => return err.prefixised("output channel")
+                            };
+                            let mut inputs = Vec::<usize>::with_capacity(arity);
+                            for ps in & paramstr[3..(3 + arity)] {
+                                match ps.parse::<usize>() {
+                                    Ok(chan) => inputs.push(chan),
+                                    Err(err) => return err.prefixised("input channel")
+                                }
+                            }
+                            if paramstr.len() > 3 + arity {
+                                let reward_name = paramstr[3 + arity].to_lowercase();
+                                let reward = Reward::from_name(&reward_name).ok_or(format!("Unknown reward '{}', available are: ticks, renew", reward_name))?;
+                                if paramstr.len() > 4 + arity {
+                                    match paramstr[4 + arity].parse::<u32>() {
+                                        Ok(amount) => {
+                                            æh.task_add(function, output, inputs, reward, amount)?;
+                                            println!("{}", "Task added".green());
+                                            Ok(())
+                                        },
+                                        Err(err) => err.prefixised("reward amount")
+                                    }
+                                } else {
+                                    Err(String::from("Reward amount not specified"))
+                                }
+                            } else {
+                                Err(String::from("Reward not specified"))
+                            }
+                        } else {
+                            Err(format!("Output channel and {} input channel(s) not specified", arity))
+                        }
+                    } else {
+                        Err(String::from("Function not specified"))
+                    }
+                },
+
+                "del" => {
+                    if paramstr.len() > 1 {
+                        match paramstr[1].parse::<usize>() {
+                            Ok(index) => {
+                                æh.task_del(index)?;
+                                println!("{}", "Task removed".green());
+                                Ok(())
+                            },
+                            Err(err) => err.prefixised("index")
+                        }
+                    } else {
+                        Err(String::from("Index not specified"))
+                    }
+                },
+
+                "list" => {
+                    for (i, task) in æh.tasks().iter().enumerate() {
+                        println!("{:4}{}", " ", format!("Task {}", i).dark_grey());
+                        println!("{}{}", format!("{:24}", "Function").dark_yellow(), task.function().name().yellow());
+                        println!("{}{}", format!("{:24}", "Output").dark_blue(), format!("{}", task.output()).blue());
+                        println!("{}{}", format!("{:24}", "Inputs").dark_blue(), task.inputs().iter().map(|chan| chan.to_string()).collect::<Vec<String>>().join(" ").blue());
+                        println!("{}{}", format!("{:24}", "Reward").dark_green(), format!("{} {}", task.reward().name(), task.amount()).green());
+                        println!("{}{}", format!("{:24}", "Attempts").dark_magenta(), format!("{}", task.attempts_count()).magenta());
+                        println!("{}{}", format!("{:24}", "Completions").dark_magenta(), format!("{}", task.completions_count()).magenta());
+                    }
+                    Ok(())
+                },
+
+                "reset" => {
+                    æh.task_reset_counts();
+                    println!("{}", "Counts reset".green());
+                    Ok(())
+                },
+
+                _ => Err(String::from("Unknown subcommand"))
+            }
+        } else {
+            Err(String::from("Subcommand not specified"))
+        }
+    }
+
+}
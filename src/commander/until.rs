@@ -0,0 +1,271 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Conditions on which run stops by itself: "run until <condition>", or --until on the command line.
+// "and" binds tighter than "or", there are no parentheses
+
+use {
+    crate::aelhometta::{
+        Command,
+        Content,
+        Ælhometta,
+        CONTENTS
+    },
+    super::Commander
+};
+
+#[derive(Clone, Copy)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+    NotEqual
+}
+
+#[derive(Clone, Copy)]
+pub enum Quantity {
+    Age,
+    Controllers,
+    Nodes,
+    Generation, // maximum
+    CommandCount(Command),
+    EtherInteger(usize), // channel
+    Time // seconds since run start
+}
+
+#[derive(Clone)]
+pub enum Condition {
+    Compare(Quantity, Comparison, i128),
+    Extinct,
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>)
+}
+
+impl Comparison {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "<" => Some(Self::Less),
+            "<=" => Some(Self::LessOrEqual),
+            ">" => Some(Self::Greater),
+            ">=" => Some(Self::GreaterOrEqual),
+            "=" | "==" => Some(Self::Equal),
+            "!=" => Some(Self::NotEqual),
+            _ => None
+        }
+    }
+
+    fn token(&self) -> &'static str {
+        match self {
+            Self::Less => "<",
+            Self::LessOrEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterOrEqual => ">=",
+            Self::Equal => "=",
+            Self::NotEqual => "!="
+        }
+    }
+
+    fn holds(&self, a: i128, b: i128) -> bool {
+        match self {
+            Self::Less => a < b,
+            Self::LessOrEqual => a <= b,
+            Self::Greater => a > b,
+            Self::GreaterOrEqual => a >= b,
+            Self::Equal => a == b,
+            Self::NotEqual => a != b
+        }
+    }
+
+}
+
+impl Quantity {
+    fn name(&self) -> String {
+        match self {
+            Self::Age => String::from("age"),
+            Self::Controllers => String::from("controllers"),
+            Self::Nodes => String::from("nodes"),
+            Self::Generation => String::from("generation"),
+            Self::CommandCount(command) => format!("command {:?}", command),
+            Self::EtherInteger(chan) => format!("ether int {}", chan),
+            Self::Time => String::from("time")
+        }
+    }
+
+    fn value(&self, æh: &Ælhometta, t_elapsed: u64) -> i128 {
+        match self {
+            Self::Age => æh.age() as i128,
+            Self::Controllers => æh.num_controllers() as i128,
+            Self::Nodes => æh.num_nodes() as i128,
+            Self::Generation => æh.generation_statistics().maximum as i128,
+            Self::CommandCount(command) => * æh.commands_count().get(command).unwrap_or(&0) as i128,
+            Self::EtherInteger(chan) => æh.ether_integers().get(*chan).copied().unwrap_or_default() as i128,
+            Self::Time => t_elapsed as i128
+        }
+    }
+
+}
+
+fn command_from_name(name: &str) -> Option<Command> {
+    CONTENTS.iter().find_map(|content| match content {
+        Content::Command(command) if format!("{:?}", command).eq_ignore_ascii_case(name) => Some(*command),
+        _ => None
+    })
+}
+
+// "[op] value" after quantity, op being default_cmp if absent
+fn parse_comparison(tokens: &[&str], i: &mut usize, default_cmp: Comparison) -> Result<(Comparison, i128), String> {
+    let cmp = match tokens.get(*i).and_then(|token| Comparison::from_token(token)) {
+        Some(cmp) => {
+            *i += 1;
+            cmp
+        },
+        None => default_cmp
+    };
+    let value = match tokens.get(*i) {
+        Some(token) => token.parse::<i128>().map_err(|err| format!("Parse error: value '{}': {}", token, &err))?,
+        None => return Err(String::from("Value not specified"))
+    };
+    *i += 1;
+    Ok((cmp, value))
+}
+
+fn parse_atom(tokens: &[&str], i: &mut usize) -> Result<Condition, String> {
+    let quantity_name = match tokens.get(*i) {
+        Some(token) => token.to_lowercase(),
+        None => return Err(String::from("Condition not specified"))
+    };
+    *i += 1;
+    let (quantity, default_cmp) = match quantity_name.as_str() {
+        "extinct" => return Ok(Condition::Extinct),
+        "age" => (Quantity::Age, Comparison::GreaterOrEqual),
+        "ctrls" | "controllers" => (Quantity::Controllers, Comparison::GreaterOrEqual),
+        "nodes" => (Quantity::Nodes, Comparison::GreaterOrEqual),
+        "gen" | "generation" => (Quantity::Generation, Comparison::GreaterOrEqual),
+        "comm" | "command" => {
+            let name = tokens.get(*i).ok_or(String::from("Command not specified"))?;
+            *i += 1;
+            let command = command_from_name(name).ok_or(format!("Unknown command '{}'", name))?;
+            (Quantity::CommandCount(command), Comparison::Greater)
+        },
+        "eth" | "ether" => {
+            match tokens.get(*i).map(|token| token.to_lowercase()) {
+                Some(chantype) if (chantype == "int") || (chantype == "integer") => {},
+                Some(chantype) => return Err(format!("Unknown channels type '{}', only int can be compared", chantype)),
+                None => return Err(String::from("Channels type not specified"))
+            }
+            let chan = match tokens.get(*i + 1) {
+                Some(token) => token.parse::<usize>().map_err(|err| format!("Parse error: channel '{}': {}", token, &err))?,
+                None => return Err(String::from("Channel not specified"))
+            };
+            *i += 2;
+            (Quantity::EtherInteger(chan), Comparison::Equal)
+        },
+        "time" => (Quantity::Time, Comparison::GreaterOrEqual),
+        _ => return Err(format!("Unknown quantity '{}'", quantity_name))
+    };
+    let (cmp, value) = parse_comparison(tokens, i, default_cmp)?;
+    Ok(Condition::Compare(quantity, cmp, value))
+}
+
+fn parse_conjunction(tokens: &[&str], i: &mut usize) -> Result<Condition, String> {
+    let mut cond = parse_atom(tokens, i)?;
+    while tokens.get(*i).is_some_and(|token| token.eq_ignore_ascii_case("and")) {
+        *i += 1;
+        cond = Condition::And(Box::new(cond), Box::new(parse_atom(tokens, i)?));
+    }
+    Ok(cond)
+}
+
+impl Condition {
+    pub fn parse(tokens: &[&str]) -> Result<Self, String> {
+        let mut i = 0;
+        let mut cond = parse_conjunction(tokens, &mut i)?;
+        while tokens.get(i).is_some_and(|token| token.eq_ignore_ascii_case("or")) {
+            i += 1;
+            cond = Self::Or(Box::new(cond), Box::new(parse_conjunction(tokens, &mut i)?));
+        }
+        match tokens.get(i) {
+            Some(token) => Err(format!("Unexpected '{}', expected 'and' or 'or'", token)),
+            None => Ok(cond)
+        }
+    }
+
+    // Ether channels exist, checked before running
+    pub(super) fn validate(&self, æh: &Ælhometta) -> Result<(), String> {
+        match self {
+            Self::Compare(Quantity::EtherInteger(chan), _, _) if *chan >= æh.ether_integers().len() => Err(format!("Integer channel {} out of bounds", chan)),
+            Self::And(a, b) | Self::Or(a, b) => a.validate(æh).and_then(|_| b.validate(æh)),
+            _ => Ok(())
+        }
+    }
+
+    // Whether it is worth checking each tick rather than each second
+    pub(super) fn cheap(&self) -> bool {
+        match self {
+            Self::Compare(Quantity::Generation, _, _) => false,
+            Self::And(a, b) | Self::Or(a, b) => a.cheap() && b.cheap(),
+            _ => true
+        }
+    }
+
+    // What holds, if the condition does
+    pub(super) fn reason(&self, æh: &Ælhometta, t_elapsed: u64) -> Option<String> {
+        match self {
+            Self::Compare(quantity, cmp, threshold) => {
+                let value = quantity.value(æh, t_elapsed);
+                cmp.holds(value, *threshold).then(|| format!("{} {} {} {}", quantity.name(), value, cmp.token(), threshold))
+            },
+            Self::Extinct => (æh.num_controllers() == 0).then(|| String::from("extinct")),
+            Self::And(a, b) => {
+                let reason_a = a.reason(æh, t_elapsed)?;
+                let reason_b = b.reason(æh, t_elapsed)?;
+                Some(format!("{} and {}", reason_a, reason_b))
+            },
+            Self::Or(a, b) => a.reason(æh, t_elapsed).or_else(|| b.reason(æh, t_elapsed))
+        }
+    }
+
+}
+
+impl Commander {
+    // "run" or "run until <condition>" from shell
+    pub(super) fn run_until(&mut self, æh: &mut Ælhometta, params: &[&str]) -> Result<(), String> {
+        let until = match params.first() {
+            Some(&"until") => Some(Condition::parse(&params[1..])?),
+            Some(param) => return Err(format!("Unknown parameter '{}', expected 'until'", param)),
+            None => None
+        };
+        self.run(æh, None, until.as_ref())?;
+        Ok(())
+    }
+
+}
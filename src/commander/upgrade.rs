@@ -0,0 +1,66 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crossterm::style::Stylize;
+
+use std::path::Path;
+
+use {
+    crate::aelhometta::{
+        journal_filepath,
+        Ælhometta,
+        FORMAT_VERSION
+    },
+    super::Commander
+};
+
+impl Commander {
+    // Non-interactive, before anything is loaded: "aelhometta upgrade <in> <out>"
+    pub fn upgrade(paramstr: &[&str]) -> Result<(), String> {
+        if paramstr.len() >= 2 {
+            let (from_filepath, to_filepath) = (paramstr[0], paramstr[1]);
+            let from_version = Ælhometta::upgrade(from_filepath, to_filepath)?;
+            println!("{} {} {} {} {}",
+                from_filepath.magenta(),
+                format!("(format v{})", &from_version).dark_grey(),
+                "upgraded to".green(),
+                to_filepath.magenta().bold(),
+                format!("(format v{})", FORMAT_VERSION).dark_grey()
+            );
+            if Path::new(&journal_filepath(from_filepath)).exists() {
+                println!("{}", format!("Journal of {} not applied, load and save it for that", from_filepath).dark_yellow());
+            }
+            Ok(())
+        } else {
+            Err(String::from("Files to upgrade from and to not specified"))
+        }
+    }
+
+}
@@ -41,14 +41,18 @@ use crossterm::style::Stylize;
 
 use std::{
     env,
+    fs,
     io::{
         self,
         Write
-    }
+    },
+    path::Path,
+    process
 };
 
 mod aelhometta;
 mod commander;
+mod options;
 mod serbin;
 
 use {
@@ -58,14 +62,39 @@ use {
     },
     commander::{
         Commander,
-        ParseErrorPrefixise
+        Stop
+    },
+    options::{
+        Options,
+        EXIT_CONDITION,
+        EXIT_EXTINCT,
+        EXIT_FINISHED,
+        EXIT_INTERRUPTED,
+        EXIT_LOAD,
+        EXIT_RUN,
+        EXIT_SAVE,
+        EXIT_TOOL,
+        EXIT_USAGE
     }
 };
 
 const PROG_VERSION: &str = env!("CARGO_PKG_VERSION");
 const PROG_DATE: &str = "2024.02.04";
 
-fn run() -> Result<bool, String> {
+// Subcommand that neither loads nor runs anything
+type Tool = fn(&[&str]) -> Result<(), String>;
+
+fn stop_exit_code(stop: &Stop) -> i32 {
+    match stop {
+        Stop::Keypress => EXIT_INTERRUPTED,
+        Stop::Limit => EXIT_FINISHED,
+        Stop::Condition(_) => EXIT_CONDITION,
+        Stop::Extinct => EXIT_EXTINCT
+    }
+}
+
+// Exit code, or it with error
+fn run() -> Result<i32, (i32, String)> {
     println!("{} {} ({}) {} {}",
         " Æ l h o m e t t a ".black().on_white().bold(),
         format!("v{}", PROG_VERSION).white().bold(),
@@ -75,58 +104,123 @@ fn run() -> Result<bool, String> {
     );
 
     let args = env::args().collect::<Vec<String>>();
-    
-    let duration: Option<u64> = if args.len() > 1 {
-        match args[1].parse::<u64>() {
-            Ok(dur) => Some(dur),
-            Err(err) => {
-                return err.prefixised("duration");
-            }            
-        }        
-    } else {
-        None
+
+    let tool: Option<Tool> = match args.get(1).map(String::as_str) {
+        Some("inspect") => Some(Commander::inspect),
+        Some("diff") => Some(Commander::diff),
+        Some("upgrade") => Some(Commander::upgrade),
+        #[cfg(unix)]
+        Some("attach") => Some(Commander::attach),
+        #[cfg(not(unix))]
+        Some("attach") => return Err((EXIT_USAGE, format!("Cannot attach to '{}': daemon is Unix only", args[2..].join(" ")))),
+        _ => None
     };
+    if let Some(tool) = tool {
+        let paramstr = args[2..].iter().map(|s| s.as_str()).collect::<Vec<&str>>();
+        tool(&paramstr).map_err(|err| (EXIT_TOOL, err))?;
+        return Ok(EXIT_FINISHED);
+    }
+
+    let opts = Options::parse(&args[1..]).map_err(|err| (EXIT_USAGE, err))?;
+    if opts.help {
+        Options::print_usage();
+        return Ok(EXIT_FINISHED);
+    }
+    #[cfg(not(unix))]
+    if opts.daemon {
+        return Err((EXIT_USAGE, String::from("Daemon mode is Unix only")));
+    }
 
+    if let Some(ref dir) = opts.data_dir {
+        fs::create_dir_all(dir).and_then(|_| env::set_current_dir(dir)).map_err(|err| (EXIT_USAGE, format!("Cannot work in '{}': {}", dir, &err)))?;
+    }
+
+    // Absent files mean new defaults, but unreadable ones are not to be overwritten
     print!("{}", "Loading Ælhometta... ".dark_blue());
     io::stdout().flush().unwrap_or(());
-    let mut æh = match Ælhometta::load_default() {
-        Ok(æh) => {
-            println!("{}", "OK".dark_green().bold());
-            æh
-        },
-        Err(err) => {
-            println!("{}\n{}", format!("Cannot load Ælhometta: {}", &err).red().bold(), "Using new default one".blue().bold());
-            Ælhometta::new_default()
+    let mut æh = if opts.fresh {
+        println!("{}", "SKIPPED".dark_yellow().bold());
+        Ælhometta::new_default()
+    } else if !Path::new(&opts.world_filepath).exists() {
+        println!("{}\n{}", format!("'{}' not found", &opts.world_filepath).dark_yellow().bold(), "Using new default one".blue().bold());
+        Ælhometta::new_default()
+    } else {
+        let loaded = if opts.no_peer {
+            Ælhometta::load_unexposed(&opts.world_filepath)
+        } else {
+            Ælhometta::load(&opts.world_filepath)
+        };
+        match loaded {
+            Ok(æh) => {
+                println!("{}", "OK".dark_green().bold());
+                æh
+            },
+            Err(err) => {
+                println!("{}", "FAILED".red().bold());
+                return Err((EXIT_LOAD, format!("Cannot load Ælhometta: {}", &err)));
+            }
         }
     };
+    if let Some(seed) = opts.seed {
+        æh.seed_rng(seed);
+    }
 
     print!("{}", "Loading Commander... ".dark_blue());
     io::stdout().flush().unwrap_or(());
-    let mut comm = match Commander::load_default() {
-        Ok(comm) => {
-            println!("{}", "OK".dark_green().bold());
-            comm
-            
-        },
-        Err(err) => {
-            println!("{}\n{}", format!("Cannot load Commander: {}", &err).red().bold(), "Using new default one".blue().bold());
-            Commander::new_default()
+    let mut comm = if !Path::new(&opts.commander_filepath).exists() {
+        println!("{}\n{}", format!("'{}' not found", &opts.commander_filepath).dark_yellow().bold(), "Using new default one".blue().bold());
+        Commander::new_default()
+    } else {
+        match Commander::load(&opts.commander_filepath) {
+            Ok(comm) => {
+                println!("{}", "OK".dark_green().bold());
+                comm
+            },
+            Err(err) => {
+                println!("{}", "FAILED".red().bold());
+                return Err((EXIT_LOAD, format!("Cannot load Commander: {}", &err)));
+            }
         }
     };
+    comm.set_world_filepath(&opts.world_filepath);
+    comm.set_no_save(opts.no_save);
+    comm.set_no_peer(opts.no_peer);
+    if let Some(abort) = opts.script_abort_on_error {
+        comm.set_script_abort_on_error(abort);
+    }
+    if let Some(port) = opts.monitor_port {
+        comm.set_monitor_port(port);
+    }
+    if let Some(ref filepath) = opts.script_filepath {
+        comm.script_load(filepath).map_err(|err| (EXIT_LOAD, format!("Cannot load script: {}", &err)))?;
+    }
 
-    let (do_save, keyint) = if duration.is_some() {
-        match comm.run(&mut æh, duration) {
-            Ok(kp) => (true, kp),
-            Err(err) => return Err(err)
-        }
-    } else {
-        (comm.shell(&mut æh).map_err(|err| format!("Cannot run shell: {}", &err))?, false)
+    // Script, if any, goes first; without duration, ticks, or condition the shell then goes on interactively, unless in daemon mode
+    let interactive = opts.limit.is_none() && opts.until.is_none() && !opts.daemon;
+    let mut quit: Option<bool> = None;
+    if interactive || opts.script_filepath.is_some() {
+        quit = comm.shell(&mut æh, interactive).map_err(|err| (EXIT_RUN, format!("Cannot run shell: {}", &err)))?;
+    }
+    let (do_save, code) = match quit {
+        Some(do_save) => (do_save, EXIT_FINISHED),
+        #[cfg(unix)]
+        None if opts.daemon => match comm.daemon(&mut æh, &opts.socket_path, opts.limit, opts.until.as_ref()).map_err(|err| (EXIT_RUN, format!("Cannot run daemon: {}", &err)))? {
+            (do_save, Some(stop)) => (do_save, stop_exit_code(&stop)),
+            (do_save, None) => (do_save, EXIT_FINISHED)
+        },
+        None => (true, stop_exit_code(&comm.run(&mut æh, opts.limit, opts.until.as_ref()).map_err(|err| (EXIT_RUN, err))?))
     };
+    let do_save = do_save && !opts.no_save;
+
+    // Before the final save, whatever happens to it
+    if let Err(err) = comm.background_save_wait() {
+        println!("{}", format!("Cannot autosave: {}", &err).red().bold());
+    }
 
     print!("{}", "Saving Ælhometta... ".dark_blue());
     io::stdout().flush().unwrap_or(());
     if do_save {
-        æh.save_default().map_err(|err| format!("Cannot save Ælhometta: {}", &err))?;
+        comm.save_world(&mut æh).map_err(|err| (EXIT_SAVE, format!("Cannot save Ælhometta: {}", &err)))?;
         println!("{}", "OK".dark_green().bold());
     } else {
         println!("{}", "CANCELLED".dark_yellow().bold());
@@ -135,26 +229,24 @@ fn run() -> Result<bool, String> {
     print!("{}", "Saving Commander... ".dark_blue());
     io::stdout().flush().unwrap_or(());
     if do_save {
-        comm.save_default().map_err(|err| format!("Cannot save Commander: {}", &err))?;
+        comm.save(&opts.commander_filepath).map_err(|err| (EXIT_SAVE, format!("Cannot save Commander: {}", &err)))?;
         println!("{}", "OK".dark_green().bold());
     } else {
         println!("{}", "CANCELLED".dark_yellow().bold());
     }
 
-    Ok(keyint)
+    Ok(code)
 }
 
-fn main() -> Result<(), i32> {
-    match run() {
-        Ok(keyint) => match keyint {
-            false => Ok(()),
-            true => Err(1)
-        },
-        Err(err) => {
+fn main() {
+    let code = match run() {
+        Ok(code) => code,
+        Err((code, err)) => {
             eprintln!("Error: {}", &err);
-            Err(2)
+            code
         }
-    }
+    };
+    process::exit(code);
 }
 
 
@@ -0,0 +1,235 @@
+/*
+ * Ælhometta
+ *
+ * Archaic attempt at autonomous non-sandboxed distributed artificial life
+ * of assembler automaton type, it features: separation of descriptive and
+ * executive data that provides branches and loops without jump instructions,
+ * encrypted publish-subscribe interaction with other instances over Tor,
+ * input/output through ordinary files associated with external sensors and
+ * actuators, and built-in shell.
+ * 
+ * https://github.com/aelhometta/aelhometta
+ * 
+ * aelhometta@proton.me
+ * 
+ * Copyright (c) 2024 Ælhometta shapers
+ * 
+ * Ælhometta is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * Ælhometta is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+ * See the GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with Ælhometta. If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Command line of the main mode, i.e. other than "inspect", "diff", "upgrade", "attach"
+
+use crossterm::style::Stylize;
+
+use crate::{
+    aelhometta::DEFAULT_ÆLHOMETTA_FILENAME,
+    commander::{
+        Condition,
+        ParseErrorPrefixise,
+        RunLimit,
+        DEFAULT_COMMANDER_FILENAME,
+        DEFAULT_SOCKET_FILENAME
+    }
+};
+
+// Process exit codes
+pub const EXIT_FINISHED: i32 = 0;
+pub const EXIT_INTERRUPTED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_LOAD: i32 = 3;
+pub const EXIT_RUN: i32 = 4;
+pub const EXIT_SAVE: i32 = 5;
+pub const EXIT_TOOL: i32 = 6;
+pub const EXIT_CONDITION: i32 = 7;
+pub const EXIT_EXTINCT: i32 = 8;
+
+pub struct Options {
+    pub world_filepath: String,
+    pub commander_filepath: String,
+    pub data_dir: Option<String>,
+    pub limit: Option<RunLimit>,
+    pub until: Option<Condition>,
+    pub no_save: bool,
+    pub fresh: bool,
+    pub seed: Option<u64>,
+    pub script_filepath: Option<String>,
+    pub script_abort_on_error: Option<bool>,
+    pub no_peer: bool,
+    pub daemon: bool,
+    pub socket_path: String,
+    pub monitor_port: Option<u16>,
+    pub help: bool
+}
+
+fn value(args: &[String], i: usize) -> Result<&str, String> {
+    args.get(i + 1).map(|s| s.as_str()).ok_or(format!("{} requires value", &args[i]))
+}
+
+impl Options {
+    // Arguments after the program name. A bare number is duration in seconds, as before
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut opts = Self {
+            world_filepath: String::from(DEFAULT_ÆLHOMETTA_FILENAME),
+            commander_filepath: String::from(DEFAULT_COMMANDER_FILENAME),
+            data_dir: None,
+            limit: None,
+            until: None,
+            no_save: false,
+            fresh: false,
+            seed: None,
+            script_filepath: None,
+            script_abort_on_error: None,
+            no_peer: false,
+            daemon: false,
+            socket_path: String::from(DEFAULT_SOCKET_FILENAME),
+            monitor_port: None,
+            help: false
+        };
+        let mut i = 0;
+        while i < args.len() {
+            let mut skip = 1;
+            match args[i].as_str() {
+                "-h" | "--help" => {
+                    opts.help = true;
+                },
+                "--world" => {
+                    opts.world_filepath = String::from(value(args, i)?);
+                    skip = 2;
+                },
+                "--commander" => {
+                    opts.commander_filepath = String::from(value(args, i)?);
+                    skip = 2;
+                },
+                "--data-dir" => {
+                    opts.data_dir = Some(String::from(value(args, i)?));
+                    skip = 2;
+                },
+                "--duration" => {
+                    match value(args, i)?.parse::<u64>() {
+                        Ok(dur) => opts.limit = Some(RunLimit::Seconds(dur)),
+                        Err(err) => return err.prefixised("duration")
+                    }
+                    skip = 2;
+                },
+                "--ticks" => {
+                    match value(args, i)?.parse::<u128>() {
+                        Ok(ticks) => opts.limit = Some(RunLimit::Ticks(ticks)),
+                        Err(err) => return err.prefixised("ticks")
+                    }
+                    skip = 2;
+                },
+                "--until" => {
+                    let tokens: Vec<&str> = value(args, i)?.split_whitespace().collect();
+                    opts.until = Some(Condition::parse(&tokens).map_err(|err| format!("Until: {}", &err))?);
+                    skip = 2;
+                },
+                "--no-save" => {
+                    opts.no_save = true;
+                },
+                "--fresh" => {
+                    opts.fresh = true;
+                },
+                "--seed" => {
+                    match value(args, i)?.parse::<u64>() {
+                        Ok(seed) => opts.seed = Some(seed),
+                        Err(err) => return err.prefixised("seed")
+                    }
+                    skip = 2;
+                },
+                "--script" => {
+                    opts.script_filepath = Some(String::from(value(args, i)?));
+                    skip = 2;
+                },
+                "--on-error" => {
+                    match value(args, i)? {
+                        "abort" => opts.script_abort_on_error = Some(true),
+                        "continue" => opts.script_abort_on_error = Some(false),
+                        other => return Err(format!("Unknown reaction to errors '{}', must be 'abort' or 'continue'", other))
+                    }
+                    skip = 2;
+                },
+                "--no-peer" => {
+                    opts.no_peer = true;
+                },
+                "--daemon" => {
+                    opts.daemon = true;
+                },
+                "--socket" => {
+                    opts.socket_path = String::from(value(args, i)?);
+                    skip = 2;
+                },
+                "--monitor" => {
+                    match value(args, i)?.parse::<u16>() {
+                        Ok(port) => opts.monitor_port = Some(port),
+                        Err(err) => return err.prefixised("monitor port")
+                    }
+                    skip = 2;
+                },
+                arg => {
+                    match arg.parse::<u64>() {
+                        Ok(dur) if !arg.starts_with('-') => opts.limit = Some(RunLimit::Seconds(dur)),
+                        _ => return Err(format!("Unknown argument '{}', see --help", arg))
+                    }
+                }
+            }
+            i += skip;
+        }
+        Ok(opts)
+    }
+
+    pub fn print_usage() {
+        println!("{}", "Usage:".dark_grey());
+        println!("{:2}{}", "", "aelhometta [options] [duration]".bold());
+        println!("{:2}{}", "", "aelhometta inspect <file>".bold());
+        println!("{:2}{}", "", "aelhometta diff <file> <file>".bold());
+        println!("{:2}{}", "", "aelhometta upgrade <from> <to>".bold());
+        println!("{:2}{}", "", "aelhometta attach [socket]".bold());
+        println!("{}", "Options:".dark_grey());
+        for (opt, desc) in [
+            ("--world <path>", format!("State file, '{}' by default; new if absent, fatal if unreadable", DEFAULT_ÆLHOMETTA_FILENAME)),
+            ("--commander <path>", format!("Commander file, '{}' by default; new if absent, fatal if unreadable", DEFAULT_COMMANDER_FILENAME)),
+            ("--data-dir <path>", String::from("Directory to work in, created if absent; relative paths are relative to it")),
+            ("--duration <seconds>", String::from("Run for that long without shell, same as bare duration")),
+            ("--ticks <number>", String::from("Run for that many ticks without shell")),
+            ("--until <condition>", String::from("Run without shell until condition holds, e.g. \"ctrls < 10 or age >= 1000000\"")),
+            ("--no-save", String::from("Neither autosave nor save on exit")),
+            ("--fresh", String::from("Start with new state instead of loading it")),
+            ("--seed <number>", String::from("Seed random number generator")),
+            ("--script <path>", String::from("Execute shell commands from file, '-' for stdin, first, then shell or run")),
+            ("--on-error <reaction>", String::from("'abort' or 'continue' script after failed command, overriding setting")),
+            ("--no-peer", String::from("Do not expose peer on load, even if it was exposed at save")),
+            ("--daemon", String::from("Run without terminal, taking shell commands over socket (Unix only)")),
+            ("--socket <path>", format!("Socket of daemon, '{}' by default", DEFAULT_SOCKET_FILENAME)),
+            ("--monitor <port>", String::from("Serve JSON and page at http://127.0.0.1:<port>/, 0 for none, overriding setting")),
+            ("-h, --help", String::from("Show this"))
+        ] {
+            println!("{:2}{}{}", "", format!("{:24}", opt).bold(), desc.dark_grey());
+        }
+        println!("{}", "Exit codes:".dark_grey());
+        for (code, desc) in [
+            (EXIT_FINISHED, "finished: quit from shell or daemon, or run lasted for duration or ticks"),
+            (EXIT_INTERRUPTED, "run interrupted by keypress"),
+            (EXIT_USAGE, "wrong command line"),
+            (EXIT_LOAD, "cannot load state, commander, or script, e.g. file exists but is unreadable or corrupt"),
+            (EXIT_RUN, "cannot run shell, ticks, or daemon"),
+            (EXIT_SAVE, "cannot save state or commander"),
+            (EXIT_TOOL, "inspect, diff, upgrade, or attach failed"),
+            (EXIT_CONDITION, "run or daemon stopped because until condition holds"),
+            (EXIT_EXTINCT, "run or daemon stopped on extinction, by on_extinction setting")
+        ] {
+            println!("{:2}{}{}", "", format!("{:<24}", code).bold(), desc.dark_grey());
+        }
+    }
+
+}
@@ -29,6 +29,7 @@
  */
 
 use std::io::{
+    self,
     Read,
     Write
 };
@@ -79,7 +80,10 @@ impl<R: Read> ReadBin<u128> for R {
         let mut buf8 = [0u8; 1];
         loop {
             self.read_exact(&mut buf8).map_err(|e| e.to_string())?;
-            x += ((buf8[0] & 0x7F) as u128) << shift;
+            if shift >= u128::BITS { // corrupt, since no u128 needs that many parts
+                return Err(String::from("LEB128 too long"));
+            }
+            x |= ((buf8[0] & 0x7F) as u128) << shift;
             if (buf8[0] & 0x80) == 0 { // Bit 7 is not set iff no more parts follow
                 break;
             }
@@ -323,3 +327,399 @@ impl<R: Read + ReadBin<usize> + ReadBin<RB>, RB> ReadBin<Option<RB>> for R {
         }
     }
 }
+
+// Wrappers that keep FNV-1a hash of all bytes passed through them, to detect torn or damaged files,
+// and, for reading, offset and name of current section, to tell where the damage is
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF29CE484222325;
+const FNV_PRIME: u64 = 0x00000100000001B3;
+
+fn fnv1a_update(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+pub struct ChecksumWriter<W: Write> {
+    inner: W,
+    checksum: u64
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            checksum: FNV_OFFSET_BASIS
+        }
+    }
+
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.checksum = fnv1a_update(self.checksum, & buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub struct ChecksumReader<R: Read> {
+    inner: R,
+    checksum: u64,
+    offset: u64,
+    section: &'static str
+}
+
+impl<R: Read> ChecksumReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            checksum: FNV_OFFSET_BASIS,
+            offset: 0,
+            section: ""
+        }
+    }
+
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn section(&self) -> &'static str {
+        self.section
+    }
+
+    pub fn begin_section(&mut self, section: &'static str) {
+        self.section = section;
+    }
+
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.checksum = fnv1a_update(self.checksum, & buf[..n]);
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+// Streaming LZ77 compression in independent blocks of bounded size, so that memory does not grow with the data.
+// Stream is LZ_SIGNATURE, then blocks, each being LEB128 raw length, LEB128 compressed length, compressed data.
+// Compressed data is sequence of LEB128 literals count, literals, and, unless raw length is reached,
+// LEB128 match length (minus LZ_MIN_MATCH), LEB128 match offset back from current position
+
+pub const LZ_SIGNATURE: &[u8] = b"AELZ\x01";
+
+const LZ_BLOCK_SIZE: usize = 0x10000;
+const LZ_MIN_MATCH: usize = 4;
+const LZ_HASH_BITS: u32 = 14;
+
+fn lz_hash(bytes: &[u8]) -> usize {
+    (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).wrapping_mul(0x9E3779B1) >> (32 - LZ_HASH_BITS)) as usize
+}
+
+fn lz_compress(src: &[u8], table: &mut [usize], dst: &mut Vec<u8>) -> Result<(), String> {
+    table.fill(usize::MAX);
+    let mut anchor = 0;
+    let mut i = 0;
+    while i + LZ_MIN_MATCH <= src.len() {
+        let h = lz_hash(& src[i..]);
+        let candidate = table[h];
+        table[h] = i;
+        if (candidate != usize::MAX) && (src[candidate..(candidate + LZ_MIN_MATCH)] == src[i..(i + LZ_MIN_MATCH)]) {
+            let mut length = LZ_MIN_MATCH;
+            while (i + length < src.len()) && (src[candidate + length] == src[i + length]) {
+                length += 1;
+            }
+            dst.write_bin(i - anchor)?;
+            dst.extend_from_slice(& src[anchor..i]);
+            dst.write_bin(length - LZ_MIN_MATCH)?;
+            dst.write_bin(i - candidate)?;
+            i += length;
+            anchor = i;
+        } else {
+            i += 1;
+        }
+    }
+    dst.write_bin(src.len() - anchor)?;
+    dst.extend_from_slice(& src[anchor..]);
+    Ok(())
+}
+
+fn lz_decompress(mut src: &[u8], raw_length: usize, dst: &mut Vec<u8>) -> Result<(), String> {
+    dst.clear();
+    loop {
+        let literals: usize = src.read_bin()?;
+        if literals > src.len() {
+            return Err(String::from("Literals beyond block"));
+        }
+        dst.extend_from_slice(& src[..literals]);
+        src = & src[literals..];
+        if dst.len() >= raw_length {
+            break;
+        }
+        let length_excess: usize = src.read_bin()?;
+        let length = length_excess.saturating_add(LZ_MIN_MATCH);
+        let offset: usize = src.read_bin()?;
+        if (offset == 0) || (offset > dst.len()) || dst.len().checked_add(length).is_none_or(|end| end > raw_length) {
+            return Err(String::from("Match beyond block"));
+        }
+        let start = dst.len() - offset;
+        for k in 0..length { // byte by byte, since match may overlap itself
+            dst.push(dst[start + k]);
+        }
+    }
+    if dst.len() == raw_length {
+        Ok(())
+    } else {
+        Err(String::from("Block length mismatch"))
+    }
+}
+
+pub struct LzWriter<W: Write> {
+    inner: W,
+    block: Vec<u8>,
+    compressed: Vec<u8>,
+    table: Vec<usize>
+}
+
+impl<W: Write> LzWriter<W> {
+    pub fn new(mut inner: W) -> Result<Self, String> {
+        inner.write_all(LZ_SIGNATURE).map_err(|e| e.to_string())?;
+        Ok(Self {
+            inner,
+            block: Vec::with_capacity(LZ_BLOCK_SIZE),
+            compressed: Vec::with_capacity(LZ_BLOCK_SIZE),
+            table: vec![usize::MAX; 1 << LZ_HASH_BITS]
+        })
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        if !self.block.is_empty() {
+            self.compressed.clear();
+            lz_compress(& self.block, &mut self.table, &mut self.compressed).map_err(io::Error::other)?;
+            self.inner.write_bin(self.block.len()).map_err(io::Error::other)?;
+            self.inner.write_bin(self.compressed.len()).map_err(io::Error::other)?;
+            self.inner.write_all(& self.compressed)?;
+            self.block.clear();
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, String> {
+        self.write_block().map_err(|e| e.to_string())?;
+        Ok(self.inner)
+    }
+
+}
+
+impl<W: Write> Write for LzWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(LZ_BLOCK_SIZE - self.block.len());
+        self.block.extend_from_slice(& buf[..n]);
+        if self.block.len() >= LZ_BLOCK_SIZE {
+            self.write_block()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        self.inner.flush()
+    }
+}
+
+// Expects LZ_SIGNATURE to be already consumed
+
+pub struct LzReader<R: Read> {
+    inner: R,
+    block: Vec<u8>,
+    compressed: Vec<u8>,
+    pos: usize
+}
+
+impl<R: Read> LzReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            block: Vec::with_capacity(LZ_BLOCK_SIZE),
+            compressed: Vec::new(),
+            pos: 0
+        }
+    }
+
+    // false at the end of stream
+    fn read_block(&mut self) -> io::Result<bool> {
+        let mut first = [0u8; 1];
+        if self.inner.read(&mut first)? == 0 {
+            return Ok(false);
+        }
+        let raw_length: usize = if (first[0] & 0x80) == 0 {
+            first[0] as usize
+        } else {
+            let rest: usize = self.inner.read_bin().map_err(io::Error::other)?;
+            ((first[0] & 0x7F) as usize) | (rest << 7)
+        };
+        let compressed_length: usize = self.inner.read_bin().map_err(io::Error::other)?;
+        if (raw_length > LZ_BLOCK_SIZE) || (compressed_length > (LZ_BLOCK_SIZE << 1)) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Compressed block too large"));
+        }
+        self.compressed.resize(compressed_length, 0);
+        self.inner.read_exact(&mut self.compressed)?;
+        lz_decompress(& self.compressed, raw_length, &mut self.block).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.pos = 0;
+        Ok(true)
+    }
+
+}
+
+impl<R: Read> Read for LzReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos >= self.block.len() {
+            if !self.read_block()? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.block.len() - self.pos);
+        buf[..n].copy_from_slice(& self.block[self.pos..(self.pos + n)]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{
+        rngs::StdRng,
+        Rng,
+        SeedableRng
+    };
+
+    use super::*;
+
+    // Written in pieces of given size, as BufWriter and the like do
+    fn compressed(data: &[u8], piece: usize) -> Vec<u8> {
+        let mut writer = LzWriter::new(Vec::new()).unwrap();
+        for chunk in data.chunks(piece) {
+            writer.write_all(chunk).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn decompressed(stream: &[u8]) -> io::Result<Vec<u8>> {
+        assert!(stream.starts_with(LZ_SIGNATURE));
+        let mut data = Vec::new();
+        LzReader::new(& stream[LZ_SIGNATURE.len()..]).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len).map(|_| rng.gen::<u8>()).collect()
+    }
+
+    fn assert_round_trip(data: &[u8]) -> usize {
+        for piece in [1, 1000, LZ_BLOCK_SIZE, data.len().max(1)] {
+            assert_eq!(decompressed(&compressed(data, piece)).unwrap(), data);
+        }
+        compressed(data, data.len().max(1)).len()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(compressed(&[], 1), LZ_SIGNATURE);
+        assert_round_trip(&[]);
+        let mut block = Vec::new();
+        lz_compress(&[], &mut [usize::MAX; 1 << LZ_HASH_BITS], &mut block).unwrap();
+        let mut data = Vec::new();
+        lz_decompress(&block, 0, &mut data).unwrap();
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn incompressible() {
+        let data = random_bytes(3 * LZ_BLOCK_SIZE + 17, 1);
+        let size = assert_round_trip(&data);
+        // Per each of 4 blocks, raw and compressed lengths and literals count, up to 3 bytes each, are all that is added
+        assert!(size <= LZ_SIGNATURE.len() + data.len() + 4 * 3 * 3);
+    }
+
+    #[test]
+    fn overlapping_matches() {
+        let zeros = vec![0u8; 5 * LZ_BLOCK_SIZE];
+        assert!(assert_round_trip(&zeros) < zeros.len() / 100);
+        let periodic: Vec<u8> = (0..(LZ_BLOCK_SIZE + 1000)).map(|i| b"abcdefg"[i % 7]).collect();
+        assert!(assert_round_trip(&periodic) < periodic.len() / 100);
+        // Short matches between literals
+        let mut mixed = random_bytes(LZ_BLOCK_SIZE, 2);
+        for i in (0..mixed.len()).step_by(16) {
+            mixed[i..(i + 5)].copy_from_slice(b"match");
+        }
+        assert_round_trip(&mixed);
+    }
+
+    #[test]
+    fn block_boundaries() {
+        let mut data = random_bytes(LZ_BLOCK_SIZE - 3, 3);
+        data.extend_from_within(..); // repeats across the boundary, but blocks are independent
+        data.extend(random_bytes(20, 4));
+        for len in [LZ_BLOCK_SIZE - 1, LZ_BLOCK_SIZE, LZ_BLOCK_SIZE + 1, 2 * LZ_BLOCK_SIZE, data.len()] {
+            assert_round_trip(& data[..len]);
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let data: Vec<u8> = (0..(2 * LZ_BLOCK_SIZE + 100)).map(|i| (i / 3) as u8).collect();
+        let stream = compressed(&data, data.len());
+        for len in LZ_SIGNATURE.len()..stream.len() {
+            // Cut at block boundary looks like shorter stream, which checksum of the file then catches
+            if let Ok(part) = decompressed(& stream[..len]) {
+                assert!((part.len() < data.len()) && data.starts_with(&part), "cut at {}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn corrupt() {
+        let data: Vec<u8> = (0..(LZ_BLOCK_SIZE + 100)).map(|i| (i % 251) as u8 ^ ((i / 1000) as u8)).collect();
+        let stream = compressed(&data, data.len());
+        // Whatever comes out, if anything, it does not panic nor grow beyond block bounds
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..500 {
+            let mut bad = stream.clone();
+            for _ in 0..rng.gen_range(1..4) {
+                let i = rng.gen_range(LZ_SIGNATURE.len()..bad.len());
+                bad[i] = rng.gen();
+            }
+            if let Ok(out) = decompressed(&bad) {
+                assert!(out.len() <= 2 * LZ_BLOCK_SIZE);
+            }
+        }
+        let mut out = Vec::new();
+        assert!(lz_decompress(&[0, 0, 1], 8, &mut out).is_err()); // offset 0
+        assert!(lz_decompress(&[1, 7, 0, 2], 8, &mut out).is_err()); // offset beyond what is decompressed
+        assert!(lz_decompress(&[1, 7, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01, 1], 8, &mut out).is_err()); // huge length
+        assert!(lz_decompress(&[9, 7], 8, &mut out).is_err()); // literals beyond block
+        assert!(lz_decompress(&[0xFF; 40], 8, &mut out).is_err()); // endless LEB128
+    }
+
+}